@@ -0,0 +1,76 @@
+//! Compares the bulk `fill_bytes` path of `generate_password` with the plain
+//! per-character `SliceRandom::choose` loop it replaced, and with the
+//! `char`-based path used for non-ASCII pools such as `--emoji`, and times a
+//! whole `--count` batch generated on one thread against `rayon` (`--jobs`).
+//!
+//! Run with `cargo bench`.
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use password_generator_rust::charset::{DIGITS, LOWERCASE, SYMBOLS, UPPERCASE};
+use password_generator_rust::{generate_password, generate_password_from_chars};
+use rand::rngs::OsRng;
+use rand::seq::SliceRandom;
+use rayon::prelude::*;
+
+/// The original implementation: one `choose` call per character.
+fn generate_with_choose(length: usize, charset: &[u8]) -> String {
+    (0..length)
+        .map(|_| {
+            *charset
+                .choose(&mut OsRng)
+                .expect("Charset must not be empty") as char
+        })
+        .collect()
+}
+
+fn bench_generate(c: &mut Criterion) {
+    let charsets = [
+        ("alphanumeric", [UPPERCASE, LOWERCASE, DIGITS].concat()),
+        ("all", [UPPERCASE, LOWERCASE, DIGITS, SYMBOLS].concat()),
+        ("hex", b"0123456789abcdef".to_vec()),
+    ];
+
+    let mut group = c.benchmark_group("generate_password");
+    for (name, charset) in &charsets {
+        let pool: Vec<char> = charset.iter().map(|&b| b as char).collect();
+        for length in [16, 128] {
+            let id = format!("{name}/{length}");
+            group.bench_with_input(BenchmarkId::new("bulk", &id), &length, |b, &length| {
+                b.iter(|| generate_password(length, charset))
+            });
+            group.bench_with_input(BenchmarkId::new("choose", &id), &length, |b, &length| {
+                b.iter(|| generate_with_choose(length, charset))
+            });
+            group.bench_with_input(BenchmarkId::new("chars", &id), &length, |b, &length| {
+                b.iter(|| generate_password_from_chars(&mut OsRng, length, &pool))
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_batch(c: &mut Criterion) {
+    const BATCH: usize = 10_000;
+    let charset = [UPPERCASE, LOWERCASE, DIGITS, SYMBOLS].concat();
+
+    let mut group = c.benchmark_group("batch");
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            (0..BATCH)
+                .map(|_| generate_password(16, &charset))
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("rayon", |b| {
+        b.iter(|| {
+            (0..BATCH)
+                .into_par_iter()
+                .map(|_| generate_password(16, &charset))
+                .collect::<Vec<_>>()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_generate, bench_batch);
+criterion_main!(benches);
@@ -0,0 +1,60 @@
+//! Fuzzes charset assembly ([`Options::charset`]) and generation
+//! ([`generate_password`]) with arbitrary option combinations.
+//!
+//! Run with `cargo +nightly fuzz run generate` from the repository root.
+
+#![no_main]
+
+use libfuzzer_sys::arbitrary::{self, Arbitrary};
+use libfuzzer_sys::fuzz_target;
+use password_generator_rust::generate_password;
+use password_generator_rust::options::Options;
+
+/// Longest password drawn per input, so runs stay fast; the interesting
+/// edge cases (zero, one, a byte's worth) are all well below it.
+const MAX_LENGTH: usize = 1024;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    length: u16,
+    uppercase: bool,
+    lowercase: bool,
+    digits: bool,
+    symbols: bool,
+    symbol_set: Option<Vec<u8>>,
+    digit_set: Option<Vec<u8>>,
+    exclude: Vec<char>,
+}
+
+fuzz_target!(|input: Input| {
+    let options = Options {
+        length: usize::from(input.length) % (MAX_LENGTH + 1),
+        uppercase: input.uppercase,
+        lowercase: input.lowercase,
+        digits: input.digits,
+        symbols: input.symbols,
+        symbol_set: input.symbol_set,
+        digit_set: input.digit_set,
+        exclude: input.exclude,
+    };
+
+    let charset = options.charset();
+    for excluded in &options.exclude {
+        assert!(
+            !charset.iter().any(|&b| char::from(b) == *excluded),
+            "excluded {excluded:?} is in the charset"
+        );
+    }
+
+    // A `String` is valid UTF-8 by construction; check length and membership.
+    let password = generate_password(options.length, &charset);
+    if charset.is_empty() {
+        assert!(password.is_empty());
+        return;
+    }
+    assert_eq!(password.chars().count(), options.length);
+    for c in password.chars() {
+        let byte = u8::try_from(u32::from(c)).expect("every byte maps to one Latin-1 char");
+        assert!(charset.contains(&byte), "{c:?} is not in the charset");
+    }
+});
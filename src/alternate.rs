@@ -0,0 +1,99 @@
+//! Passwords in which no two neighboring characters share a character class.
+//!
+//! Instead of redrawing candidates until no class repeats (which gets
+//! hopeless for long passwords), each position picks its class among the ones
+//! its predecessor did not use, then a character within that class.
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use crate::balanced::class_pools;
+
+/// Generates a password of `length` characters in which every character
+/// belongs to a different class than the one before it, e.g. `a7K!b2`.
+///
+/// The first class is chosen uniformly among the classes present in
+/// `charset`, every later one uniformly among the others, and each character
+/// uniformly within its class, all with `rng`. Bytes outside the four built-in
+/// classes form a class of their own, as in [`class_pools`]. Returns `None`
+/// if `charset` covers fewer than two classes, since no such password exists.
+///
+/// ```
+/// use password_generator_rust::alternate::generate_alternating_with_rng;
+/// use password_generator_rust::charset::class_of;
+/// use rand::rngs::OsRng;
+///
+/// let charset = b"ABCabc012!?";
+/// for _ in 0..100 {
+///     let password = generate_alternating_with_rng(&mut OsRng, 24, charset).unwrap();
+///     let classes: Vec<_> = password.bytes().map(class_of).collect();
+///     assert!(classes.windows(2).all(|pair| pair[0] != pair[1]), "{password}");
+/// }
+///
+/// // Two classes can only alternate strictly.
+/// let password = generate_alternating_with_rng(&mut OsRng, 6, b"ab01").unwrap();
+/// assert!(password.bytes().zip(password.bytes().skip(1)).all(|(a, b)| a.is_ascii_digit() != b.is_ascii_digit()));
+///
+/// assert_eq!(generate_alternating_with_rng(&mut OsRng, 8, b"abc"), None);
+/// ```
+pub fn generate_alternating_with_rng<R: Rng + ?Sized>(
+    rng: &mut R,
+    length: usize,
+    charset: &[u8],
+) -> Option<String> {
+    let pools = class_pools(charset);
+    if pools.len() < 2 {
+        return None;
+    }
+
+    let mut password = String::with_capacity(length);
+    let mut previous: Option<usize> = None;
+    for _ in 0..length {
+        let class = match previous {
+            None => rng.gen_range(0..pools.len()),
+            // Skip over the previous class so the others stay equally likely.
+            Some(previous) => {
+                let class = rng.gen_range(0..pools.len() - 1);
+                if class >= previous { class + 1 } else { class }
+            }
+        };
+        let byte = pools[class]
+            .choose(rng)
+            .expect("class pools are never empty");
+        password.push(*byte as char);
+        previous = Some(class);
+    }
+    Some(password)
+}
+
+/// Entropy in bits of a password from [`generate_alternating_with_rng`].
+///
+/// With `k` classes the first class adds `log2(k)` bits and every later one
+/// `log2(k - 1)`. Each class is equally likely at every position, so each
+/// character adds the mean of `log2(class size)` over the classes. Returns
+/// `0.0` when no alternating password exists.
+///
+/// ```
+/// use password_generator_rust::alternate::alternating_entropy_bits;
+///
+/// // Two classes alternate in one of two orders, then 26 letters and 10 digits.
+/// let bits = alternating_entropy_bits(4, b"abcdefghijklmnopqrstuvwxyz0123456789");
+/// let expected = 1.0 + 2.0 * 26f64.log2() + 2.0 * 10f64.log2();
+/// assert!((bits - expected).abs() < 1e-9);
+/// ```
+pub fn alternating_entropy_bits(length: usize, charset: &[u8]) -> f64 {
+    let pools = class_pools(charset);
+    let classes = pools.len();
+    if classes < 2 || length == 0 {
+        return 0.0;
+    }
+
+    let mean_char_bits = pools
+        .iter()
+        .map(|pool| (pool.len() as f64).log2())
+        .sum::<f64>()
+        / classes as f64;
+    (classes as f64).log2()
+        + (length - 1) as f64 * ((classes - 1) as f64).log2()
+        + length as f64 * mean_char_bits
+}
@@ -0,0 +1,111 @@
+//! Append-only audit log of generated passwords, without the passwords.
+//!
+//! Each generated password gets one JSON line recording when it was
+//! generated, with what settings and how much entropy, plus a salted
+//! Argon2id hash in PHC string format. The plaintext is never written: the
+//! hash only lets someone holding a password check whether this log
+//! recorded it, with [`was_recorded`].
+
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+
+use argon2::Argon2;
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{self, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use serde_json::{Value, json};
+
+/// What the audit log records about one generated password.
+#[derive(Debug, Clone, Copy)]
+pub struct Record<'a> {
+    /// When the password was generated, e.g. an RFC 3339 timestamp.
+    pub timestamp: &'a str,
+    /// The settings in effect, e.g. the effective command line.
+    pub settings: &'a str,
+    /// Position in the batch, starting at 1.
+    pub index: usize,
+    /// Entropy of the configuration in bits.
+    pub entropy_bits: f64,
+    /// The password. Only its salted hash is written.
+    pub password: &'a str,
+}
+
+impl Record<'_> {
+    /// Renders the record as one JSON line, hashing the password with a
+    /// fresh salt.
+    ///
+    /// ```
+    /// use password_generator_rust::audit::{Record, was_recorded};
+    ///
+    /// let password = "k3Vq9xLm!4821pQ7";
+    /// let line = Record {
+    ///     timestamp: "2024-05-01T09:30:00Z",
+    ///     settings: "password-generator-rust --length 16",
+    ///     index: 1,
+    ///     entropy_bits: 103.1,
+    ///     password,
+    /// }
+    /// .to_line()
+    /// .unwrap();
+    ///
+    /// assert!(line.contains("\"timestamp\":\"2024-05-01T09:30:00Z\""));
+    /// assert!(line.contains("\"password_hash\":\"$argon2id$"));
+    /// // No run of four or more characters from the password makes it in.
+    /// for start in 0..password.len() - 3 {
+    ///     assert!(!line.contains(&password[start..start + 4]));
+    /// }
+    ///
+    /// assert!(was_recorded(&line, password));
+    /// assert!(!was_recorded(&line, "some other password"));
+    /// ```
+    pub fn to_line(&self) -> password_hash::Result<String> {
+        let salt = SaltString::generate(&mut OsRng);
+        let hash = Argon2::default().hash_password(self.password.as_bytes(), &salt)?;
+        Ok(json!({
+            "timestamp": self.timestamp,
+            "settings": self.settings,
+            "index": self.index,
+            "entropy_bits": (self.entropy_bits * 10.0).round() / 10.0,
+            "password_hash": hash.to_string(),
+        })
+        .to_string())
+    }
+}
+
+/// Appends `lines` to the log at `path`, creating it if needed.
+///
+/// On Unix a new log file is created with `0600` permissions.
+pub fn append(path: &Path, lines: &[String]) -> io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.append(true).create(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options.open(path)?;
+    let mut contents = String::new();
+    for line in lines {
+        contents.push_str(line);
+        contents.push('\n');
+    }
+    // One write, so concurrent runs don't interleave their lines.
+    file.write_all(contents.as_bytes())
+}
+
+/// Returns `true` if the audit log `line` records `password`.
+///
+/// Malformed lines never match.
+pub fn was_recorded(line: &str, password: &str) -> bool {
+    serde_json::from_str::<Value>(line).is_ok_and(|record| {
+        record["password_hash"]
+            .as_str()
+            .and_then(|hash| PasswordHash::new(hash).ok())
+            .is_some_and(|hash| {
+                Argon2::default()
+                    .verify_password(password.as_bytes(), &hash)
+                    .is_ok()
+            })
+    })
+}
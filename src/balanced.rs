@@ -0,0 +1,125 @@
+//! Passwords in which every character class gets a near-equal share.
+//!
+//! Uniform generation only makes each class appear in proportion to its size
+//! on average; a given password may still be mostly lowercase. Balanced
+//! generation fixes the count of each class instead, which costs some entropy
+//! (see [`balanced_entropy_bits`]) in exchange for resisting filters that
+//! target one class.
+
+use rand::RngCore;
+use rand::seq::SliceRandom;
+
+use crate::charset::{CharClass, class_of};
+use crate::generate_password_with_rng;
+
+/// Splits `charset` into one pool per character class, in
+/// [`CharClass::ALL`] order, skipping classes with no characters left. Bytes
+/// outside the four classes form a final pool of their own.
+pub fn class_pools(charset: &[u8]) -> Vec<Vec<u8>> {
+    let mut pools: Vec<Vec<u8>> = CharClass::ALL
+        .iter()
+        .map(|&class| {
+            charset
+                .iter()
+                .copied()
+                .filter(|&b| class_of(b) == Some(class))
+                .collect()
+        })
+        .collect();
+    pools.push(
+        charset
+            .iter()
+            .copied()
+            .filter(|&b| class_of(b).is_none())
+            .collect(),
+    );
+    pools.retain(|pool| !pool.is_empty());
+    pools
+}
+
+/// Generates a password of `length` characters in which every class present
+/// in `charset` contributes `length / classes` characters, give or take one.
+///
+/// The remainder goes to randomly chosen classes, one extra character each.
+/// Each class's characters are drawn without modulo bias, then the whole
+/// buffer is shuffled with `rng`. Returns `None` if `charset` is empty or
+/// `length` is too small to give every class at least one character.
+///
+/// ```
+/// use password_generator_rust::balanced::generate_balanced_with_rng;
+/// use password_generator_rust::strength::classify;
+/// use rand::rngs::OsRng;
+///
+/// let charset = b"ABCDEFabcdef0123!?";
+/// for length in [4, 10, 17] {
+///     let password = generate_balanced_with_rng(&mut OsRng, length, charset).unwrap();
+///     let counts = classify(&password);
+///     let shares = [counts.upper, counts.lower, counts.digit, counts.symbol];
+///     let (min, max) = (shares.iter().min().unwrap(), shares.iter().max().unwrap());
+///     assert!(max - min <= 1, "{password}: {counts}");
+/// }
+///
+/// assert_eq!(generate_balanced_with_rng(&mut OsRng, 3, charset), None);
+/// ```
+pub fn generate_balanced_with_rng<R: RngCore + ?Sized>(
+    rng: &mut R,
+    length: usize,
+    charset: &[u8],
+) -> Option<String> {
+    let mut pools = class_pools(charset);
+    if pools.is_empty() || length < pools.len() {
+        return None;
+    }
+
+    let base = length / pools.len();
+    let extra = length % pools.len();
+    pools.shuffle(rng);
+
+    let mut chars: Vec<char> = pools
+        .iter()
+        .enumerate()
+        .flat_map(|(i, pool)| {
+            let count = base + usize::from(i < extra);
+            generate_password_with_rng(rng, count, pool)
+                .chars()
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    chars.shuffle(rng);
+    Some(chars.into_iter().collect())
+}
+
+/// A lower bound, in bits, on the entropy of [`generate_balanced_with_rng`].
+///
+/// With fixed class counts `n_i` from pools of `s_i` characters there are
+/// `length! / (n_1! * ... * n_k!)` arrangements of the classes and
+/// `s_1^n_1 * ... * s_k^n_k` choices of characters. The remainder is assumed
+/// to land on the smallest pools and the randomness of that choice is not
+/// counted. Returns `0.0` when no balanced password exists.
+///
+/// ```
+/// use password_generator_rust::balanced::balanced_entropy_bits;
+/// use password_generator_rust::entropy_bits;
+///
+/// // Balancing never adds entropy over uniform draws.
+/// let charset = b"ABCDEFGHabcdefgh01234567";
+/// assert!(balanced_entropy_bits(12, charset) < entropy_bits(12, charset.len()));
+/// ```
+pub fn balanced_entropy_bits(length: usize, charset: &[u8]) -> f64 {
+    let mut sizes: Vec<usize> = class_pools(charset).iter().map(Vec::len).collect();
+    if sizes.is_empty() || length < sizes.len() {
+        return 0.0;
+    }
+    sizes.sort_unstable();
+
+    let base = length / sizes.len();
+    let extra = length % sizes.len();
+    let log2_factorial = |n: usize| (2..=n).map(|i| (i as f64).log2()).sum::<f64>();
+
+    let mut bits = log2_factorial(length);
+    for (i, &size) in sizes.iter().enumerate() {
+        let count = base + usize::from(i < extra);
+        bits += count as f64 * (size as f64).log2() - log2_factorial(count);
+    }
+    bits
+}
@@ -0,0 +1,84 @@
+//! Substring blocklist used by `--dictionary-check`.
+//!
+//! Generated values can accidentally contain an offensive or guessable
+//! substring, which is most likely in word-based modes. A [`Blocklist`] flags
+//! such candidates so the caller can regenerate them.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::constant_time_eq;
+
+const DEFAULT_BLOCKLIST: &str = include_str!("blocklist.txt");
+
+/// A list of lowercase substrings that must not appear in generated output.
+#[derive(Debug, Clone)]
+pub struct Blocklist {
+    entries: Vec<String>,
+}
+
+impl Blocklist {
+    /// Returns the blocklist embedded in the binary.
+    pub fn embedded() -> Self {
+        Self::parse(DEFAULT_BLOCKLIST)
+    }
+
+    /// Loads a blocklist from a file with one substring per line.
+    ///
+    /// Blank lines and lines starting with `#` are ignored.
+    pub fn from_file(path: &Path) -> io::Result<Self> {
+        Ok(Self::parse(&fs::read_to_string(path)?))
+    }
+
+    fn parse(contents: &str) -> Self {
+        let entries = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_lowercase)
+            .collect();
+        Self { entries }
+    }
+
+    /// Returns `true` if the blocklist has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the first blocklisted substring found in `candidate`, if any.
+    ///
+    /// Matching is case-insensitive. The candidate is usually a secret, so
+    /// every entry is compared against every window of it with
+    /// [`constant_time_eq`] and nothing stops early on a match: the running
+    /// time depends only on the lengths involved, not on where (or whether)
+    /// the candidate matches.
+    ///
+    /// ```
+    /// use password_generator_rust::blocklist::Blocklist;
+    ///
+    /// let blocklist = Blocklist::embedded();
+    /// assert!(blocklist.find_in("xxPassWordxx").is_some());
+    /// assert!(blocklist.find_in("Xq7!vR2#").is_none());
+    /// ```
+    pub fn find_in(&self, candidate: &str) -> Option<&str> {
+        let candidate = candidate.to_lowercase();
+        let candidate = candidate.as_bytes();
+
+        let mut found = None;
+        for entry in &self.entries {
+            let needle = entry.as_bytes();
+            let mut matched = false;
+            if needle.len() <= candidate.len() {
+                for window in candidate.windows(needle.len()) {
+                    matched |= constant_time_eq(window, needle);
+                }
+            }
+            // Only the result decides this, never the comparisons themselves.
+            if matched && found.is_none() {
+                found = Some(entry.as_str());
+            }
+        }
+        found
+    }
+}
@@ -0,0 +1,160 @@
+//! Character class constants used to assemble generation pools.
+//!
+//! Library users can compose custom pools from the same constants the CLI uses
+//! instead of hardcoding their own byte strings.
+//!
+//! # Examples
+//!
+//! ```
+//! use password_generator_rust::charset::{CharClass, LOWERCASE, class_bytes};
+//!
+//! // Letters plus a hand-picked set of symbols.
+//! let mut pool = LOWERCASE.to_vec();
+//! pool.extend_from_slice(class_bytes(CharClass::Upper));
+//! pool.extend_from_slice(b"-_");
+//!
+//! assert_eq!(pool.len(), 54);
+//! ```
+
+// Define charsets as byte slices for efficiency and immutability.
+// These will be combined to form the pool of characters for password generation.
+
+/// Uppercase ASCII letters `A-Z`.
+pub const UPPERCASE: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+/// Lowercase ASCII letters `a-z`.
+pub const LOWERCASE: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+/// ASCII digits `0-9`.
+pub const DIGITS: &[u8] = b"0123456789";
+/// The symbols included by default.
+pub const SYMBOLS: &[u8] = b"!@#$%^&*()-_=+[]{};:,.<>?";
+
+/// The subset of [`SYMBOLS`] kept by `--wifi`. Quotes, backslashes,
+/// brackets, `&`, `<`, `>`, `;` and `$` are left out because router admin
+/// pages and config files are known to mangle or reject them.
+pub const WIFI_SYMBOLS: &[u8] = b"!#%*+-=?@_.,:^";
+
+/// Arabic-Indic digits `٠..٩` (`U+0660..=U+0669`), used by `--digit-script arabic`.
+///
+/// These are `char`s rather than bytes, so they go through the `char`-based
+/// generation path:
+///
+/// ```
+/// use password_generator_rust::charset::ARABIC_INDIC_DIGITS;
+/// use password_generator_rust::generate_password_from_chars;
+/// use rand::rngs::OsRng;
+///
+/// let password = generate_password_from_chars(&mut OsRng, 12, &ARABIC_INDIC_DIGITS);
+/// assert!(password.chars().all(|c| ('\u{0660}'..='\u{0669}').contains(&c)));
+/// ```
+pub const ARABIC_INDIC_DIGITS: [char; 10] = ['٠', '١', '٢', '٣', '٤', '٥', '٦', '٧', '٨', '٩'];
+
+/// Devanagari digits `०..९` (`U+0966..=U+096F`), used by `--digit-script devanagari`.
+///
+/// Like [`ARABIC_INDIC_DIGITS`], these are in digit order, so the index of a
+/// digit is its value:
+///
+/// ```
+/// use password_generator_rust::charset::{ARABIC_INDIC_DIGITS, DEVANAGARI_DIGITS};
+///
+/// for (value, (arabic, devanagari)) in ARABIC_INDIC_DIGITS.iter().zip(DEVANAGARI_DIGITS).enumerate() {
+///     assert_eq!(*arabic as u32, 0x0660 + value as u32);
+///     assert_eq!(devanagari as u32, 0x0966 + value as u32);
+/// }
+/// ```
+pub const DEVANAGARI_DIGITS: [char; 10] = ['०', '१', '२', '३', '४', '५', '६', '७', '८', '९'];
+
+/// Digits and the letters most often mistaken for them: `0`/`O` and `1`/`l`/`I`.
+pub const AMBIGUOUS_NUMERIC: &[u8] = b"0O1lI";
+/// A broader set of look-alikes for Latin-script fonts: [`AMBIGUOUS_NUMERIC`]
+/// plus `o`, `Q`, `D`, `i`, `|`, `!`, `5`/`S`, `2`/`Z` and `8`/`B`.
+pub const AMBIGUOUS_LATIN: &[u8] = b"0O1lIoQDi|!5S2Z8B";
+/// Characters a reader may transcribe in the wrong script: [`AMBIGUOUS_LATIN`]
+/// plus the ASCII letters and digits that have an identical Cyrillic or Greek
+/// twin (`A`/`А`, `p`/`р`, `3`/`З`, ...).
+///
+/// ```
+/// use password_generator_rust::charset::{AMBIGUOUS_LATIN, HOMOGLYPHS_LATIN};
+///
+/// assert!(AMBIGUOUS_LATIN.iter().all(|b| HOMOGLYPHS_LATIN.contains(b)));
+/// assert!(HOMOGLYPHS_LATIN.contains(&b'a') && !HOMOGLYPHS_LATIN.contains(&b'b'));
+/// ```
+pub const HOMOGLYPHS_LATIN: &[u8] = b"0O1lIoQDi|!5S2Z8BAaCcEeHJjKMNPpsTXxYy3";
+/// The Cyrillic and Greek twins of [`HOMOGLYPHS_LATIN`], removed from
+/// non-ASCII pools as well.
+///
+/// ```
+/// use password_generator_rust::charset::HOMOGLYPHS_CROSS_SCRIPT;
+///
+/// assert!(HOMOGLYPHS_CROSS_SCRIPT.contains(&'\u{0410}')); // Cyrillic А
+/// assert!(HOMOGLYPHS_CROSS_SCRIPT.contains(&'\u{039F}')); // Greek Ο
+/// assert!(HOMOGLYPHS_CROSS_SCRIPT.iter().all(|c| !c.is_ascii()));
+/// ```
+#[rustfmt::skip]
+pub const HOMOGLYPHS_CROSS_SCRIPT: [char; 42] = [
+    // Cyrillic
+    'А', 'В', 'С', 'Е', 'Н', 'І', 'Ј', 'К', 'М', 'О', 'Р', 'Ѕ', 'Т', 'Х', 'У', 'З',
+    'а', 'с', 'е', 'і', 'ј', 'о', 'р', 'ѕ', 'х', 'у',
+    // Greek
+    'Α', 'Β', 'Ε', 'Ζ', 'Η', 'Ι', 'Κ', 'Μ', 'Ν', 'Ο', 'Ρ', 'Τ', 'Υ', 'Χ', 'ο', 'ρ',
+];
+
+/// Code point ranges of the opt-in emoji pool: emoticons (`U+1F600..=U+1F64F`)
+/// and animals (`U+1F400..=U+1F43E`).
+///
+/// Every code point here is a complete emoji on its own with emoji
+/// presentation by default, so no variation selector or ZWJ sequence is ever
+/// needed to display it.
+pub const EMOJI_RANGES: [(u32, u32); 2] = [(0x1F600, 0x1F64F), (0x1F400, 0x1F43E)];
+
+/// Returns every emoji in [`EMOJI_RANGES`].
+///
+/// ```
+/// use password_generator_rust::charset::emoji;
+///
+/// assert_eq!(emoji().len(), 80 + 63);
+/// assert!(emoji().contains(&'😀'));
+/// assert!(emoji().iter().all(|c| c.len_utf8() == 4));
+/// ```
+pub fn emoji() -> Vec<char> {
+    EMOJI_RANGES
+        .iter()
+        .flat_map(|&(start, end)| (start..=end).filter_map(char::from_u32))
+        .collect()
+}
+
+/// A character class backed by one of the constants above.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CharClass {
+    Upper,
+    Lower,
+    Digit,
+    Symbol,
+}
+
+impl CharClass {
+    /// Every class, in the order the CLI assembles them.
+    pub const ALL: [CharClass; 4] = [
+        CharClass::Upper,
+        CharClass::Lower,
+        CharClass::Digit,
+        CharClass::Symbol,
+    ];
+}
+
+/// Returns the characters belonging to `class`.
+pub fn class_bytes(class: CharClass) -> &'static [u8] {
+    match class {
+        CharClass::Upper => UPPERCASE,
+        CharClass::Lower => LOWERCASE,
+        CharClass::Digit => DIGITS,
+        CharClass::Symbol => SYMBOLS,
+    }
+}
+
+/// Returns the class `c` belongs to, or `None` if it is in none of the
+/// built-in classes (e.g. a custom symbol).
+pub fn class_of(c: u8) -> Option<CharClass> {
+    CharClass::ALL
+        .into_iter()
+        .find(|&class| class_bytes(class).contains(&c))
+}
@@ -0,0 +1,67 @@
+//! Check characters for codes that are transcribed by hand.
+//!
+//! The check character uses the Luhn mod N algorithm, where N is the size of
+//! the charset and each character's value is its index in the charset. It
+//! detects every single-character typo and most swaps of adjacent
+//! characters. Anyone who knows the charset, in order, can verify a code:
+//!
+//! 1. Walk the body from right to left, doubling the value of the rightmost
+//!    character and then every other one.
+//! 2. Whenever a doubled value reaches N, replace it with `value / N +
+//!    value % N` (the sum of its base-N digits).
+//! 3. Add everything up. The check character is the one at index
+//!    `(N - sum % N) % N`.
+//!
+//! A code is valid when running the same walk over body and check character,
+//! now doubling the second character from the right, gives a sum divisible
+//! by N. The check character is public and adds no entropy.
+
+/// Computes the check character for `body`, or `None` if `charset` is empty or
+/// `body` contains a character outside it.
+///
+/// ```
+/// use password_generator_rust::checksum::{check_char, is_valid};
+///
+/// let charset = b"0123456789";
+/// // Plain Luhn over decimal digits, as used on payment cards.
+/// assert_eq!(check_char("7992739871", charset), Some('3'));
+/// assert!(is_valid("79927398713", charset));
+/// assert!(!is_valid("79927398714", charset));
+/// ```
+pub fn check_char(body: &str, charset: &[u8]) -> Option<char> {
+    let n = charset.len();
+    if n == 0 {
+        return None;
+    }
+    let sum = luhn_sum(body, charset, true)?;
+    Some(charset[(n - sum % n) % n] as char)
+}
+
+/// Returns `true` if the last character of `code` is the correct check
+/// character for the rest.
+///
+/// Codes containing characters outside `charset`, and codes shorter than two
+/// characters, are never valid.
+pub fn is_valid(code: &str, charset: &[u8]) -> bool {
+    !charset.is_empty()
+        && code.chars().count() >= 2
+        && luhn_sum(code, charset, false).is_some_and(|sum| sum % charset.len() == 0)
+}
+
+/// The Luhn mod N sum of `input`, walking from the right. When
+/// `double_first` is set the rightmost character is doubled.
+fn luhn_sum(input: &str, charset: &[u8], double_first: bool) -> Option<usize> {
+    let n = charset.len();
+    let mut double = double_first;
+    let mut sum = 0;
+    for c in input.chars().rev() {
+        let mut value = charset.iter().position(|&b| b as char == c)?;
+        if double {
+            value *= 2;
+            value = value / n + value % n;
+        }
+        sum += value;
+        double = !double;
+    }
+    Some(sum)
+}
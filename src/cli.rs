@@ -0,0 +1,844 @@
+//! Command-line interface: argument definitions, shared helpers and dispatch.
+//!
+//! Each subcommand lives in its own module with its arguments, validation and
+//! `run` function. A bare invocation (no subcommand) behaves like `generate`.
+
+use std::fmt::Display;
+use std::fs;
+use std::io::{self, BufRead, IsTerminal};
+use std::path::PathBuf;
+
+use clap::builder::FalseyValueParser;
+use clap::parser::ValueSource;
+use clap::{
+    ArgAction, ArgMatches, Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum,
+};
+use password_generator_rust::charset::{AMBIGUOUS_LATIN, AMBIGUOUS_NUMERIC, HOMOGLYPHS_LATIN};
+use password_generator_rust::options::Options;
+
+mod check;
+mod clipboard;
+mod combine;
+mod compare;
+mod derive;
+mod generate;
+mod passphrase;
+mod profile;
+mod selftest;
+mod stretch;
+mod token;
+
+/// Secure Password Generator
+///
+/// Generates cryptographically secure passwords with configurable length and character sets.
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None, args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Options for the default `generate` command
+    #[command(flatten)]
+    generate: generate::GenerateArgs,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Generate a random password (the default when no command is given)
+    Generate(Box<generate::GenerateArgs>),
+
+    /// Generate a passphrase of random words from the embedded EFF wordlist
+    Passphrase(passphrase::PassphraseArgs),
+
+    /// Estimate the strength of an existing password
+    ///
+    /// The password is read from a hidden prompt, or from the first line of
+    /// stdin when it is not a terminal.
+    #[command(alias = "verify")]
+    Check(check::CheckArgs),
+
+    /// Generate a random token of raw bytes in hex, base64 or base32
+    Token(token::TokenArgs),
+
+    /// Derive a deterministic site password from a master secret (Argon2id)
+    ///
+    /// The master secret is read from a hidden prompt, or from the first line of
+    /// stdin when it is not a terminal. It is never accepted on the command line.
+    #[command(visible_alias = "reproducible-from-passphrase")]
+    Derive(derive::DeriveArgs),
+
+    /// Stretch a short memorable phrase into a strong-looking password (Argon2id)
+    ///
+    /// The same phrase and salt always give the same password. The phrase is
+    /// read from a hidden prompt, or from the first line of stdin when it is not
+    /// a terminal. Stretching slows down guessing but adds no entropy: a weak
+    /// phrase still gives a weak password.
+    Stretch(stretch::StretchArgs),
+
+    /// Recover a password from the shares printed by `generate --split`
+    ///
+    /// Give at least the threshold number of shares, from files or from stdin,
+    /// one per line. The password is printed to stdout.
+    Combine(combine::CombineArgs),
+
+    /// Compare the entropy of two configurations without generating anything
+    ///
+    /// Each configuration is a comma-separated `key=value` list, e.g.
+    /// `--a length=12,symbols=true --b length=20,symbols=false`.
+    Compare(compare::CompareArgs),
+
+    /// Sanity-check the system RNG (exit status 1 on failure)
+    ///
+    /// Draws a sample from the operating system's RNG and checks that it is
+    /// non-zero, free of long constant runs and roughly uniform. This catches
+    /// gross misconfiguration only; it is not a certified randomness test.
+    Selftest(selftest::SelftestArgs),
+}
+
+/// Validates the parsed arguments before any generation takes place.
+///
+/// Every command's checks are reached from here so each invalid configuration
+/// is rejected up front with a specific message. Flag combinations that can
+/// never be valid (e.g. `--memorable` with `--length`) are rejected earlier by
+/// clap.
+fn validate(cli: &Cli) -> Result<(), String> {
+    match &cli.command {
+        None => generate::validate(&cli.generate),
+        Some(Command::Generate(args)) => generate::validate(args),
+        Some(Command::Passphrase(args)) => passphrase::validate(args),
+        Some(Command::Check(_)) => Ok(()),
+        Some(Command::Token(args)) => token::validate(args),
+        Some(Command::Derive(args)) => derive::validate(args),
+        Some(Command::Stretch(args)) => stretch::validate(args),
+        Some(Command::Combine(_)) => Ok(()),
+        Some(Command::Compare(_)) => Ok(()),
+        Some(Command::Selftest(_)) => Ok(()),
+    }
+}
+
+/// Why a command failed. `main` prints it and exits with [`AppError::exit_code`].
+#[derive(Debug, thiserror::Error)]
+pub enum AppError {
+    /// The command line asks for something invalid: flags or values rejected
+    /// by validation, like the parse errors clap reports itself.
+    #[error("{0}")]
+    Usage(String),
+    /// A valid request that couldn't be carried out, such as constraints no
+    /// candidate satisfied.
+    #[error("{0}")]
+    Runtime(String),
+    /// Reading or writing failed.
+    #[error("{context}: {source}")]
+    Io { context: String, source: io::Error },
+    /// A check the user asked for did not pass: `check` found a weak
+    /// password, `selftest` or `--histogram` a bad distribution. The report on
+    /// stdout already says why, so there is no message.
+    #[error("check failed")]
+    CheckFailed,
+}
+
+impl AppError {
+    /// The process exit status: 2 for a usage error, as clap uses for the
+    /// ones it catches, and 1 otherwise.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            AppError::Usage(_) => 2,
+            AppError::Runtime(_) | AppError::Io { .. } | AppError::CheckFailed => 1,
+        }
+    }
+}
+
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        AppError::Runtime(message)
+    }
+}
+
+/// Parses the command line and runs the selected command.
+pub fn run() -> Result<(), AppError> {
+    let argv: Vec<_> = std::env::args_os().collect();
+    let command = profile::apply(Cli::command(), &argv)?;
+    let matches = command.clone().get_matches_from(argv);
+    let mut cli = Cli::from_arg_matches(&matches)
+        .unwrap_or_else(|err| err.format(&mut command.clone()).exit());
+
+    let generate_args = match &mut cli.command {
+        None => Some(&mut cli.generate),
+        Some(Command::Generate(args)) => Some(args.as_mut()),
+        Some(_) => None,
+    };
+    if let Some(args) = generate_args {
+        generate::resolve(args)?;
+        if args.audit_log.is_some() {
+            args.invocation = canonical_command(&command, &matches);
+        }
+    }
+
+    validate(&cli).map_err(AppError::Usage)?;
+
+    let show_command = match &cli.command {
+        None => cli.generate.show_command,
+        Some(Command::Generate(args)) => args.show_command,
+        Some(_) => false,
+    };
+    if show_command {
+        // Explicitly requested, so shown even under --quiet.
+        eprintln!("{}", canonical_command(&command, &matches));
+    }
+
+    match cli.command {
+        None => generate::run(cli.generate),
+        Some(Command::Generate(args)) => generate::run(*args),
+        Some(Command::Passphrase(args)) => passphrase::run(args),
+        Some(Command::Check(args)) => check::run(args),
+        Some(Command::Token(args)) => {
+            token::run(args);
+            Ok(())
+        }
+        Some(Command::Derive(args)) => derive::run(args),
+        Some(Command::Stretch(args)) => stretch::run(args),
+        Some(Command::Combine(args)) => combine::run(args),
+        Some(Command::Compare(args)) => {
+            compare::run(args);
+            Ok(())
+        }
+        Some(Command::Selftest(args)) => selftest::run(args),
+    }
+}
+
+/// Rebuilds the invocation from `matches` as a copy-pasteable command line.
+///
+/// Only flags given on the command line or through an environment variable are
+/// included, in the order `--help` lists them and always in their long form, so
+/// two invocations that mean the same thing print the same command. Defaults
+/// are left out and `--show-command` itself is dropped.
+fn canonical_command(command: &clap::Command, matches: &ArgMatches) -> String {
+    let mut words = vec![command.get_name().to_string()];
+    let (command, matches) = match matches.subcommand() {
+        Some((name, sub_matches)) => {
+            words.push(name.to_string());
+            let sub = command
+                .find_subcommand(name)
+                .expect("clap matched a known subcommand");
+            (sub, sub_matches)
+        }
+        None => (command, matches),
+    };
+
+    for arg in command.get_arguments() {
+        let id = arg.get_id().as_str();
+        if id == "show_command"
+            || !matches!(
+                matches.value_source(id),
+                Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+            )
+        {
+            continue;
+        }
+
+        let flag = match (arg.get_long(), arg.get_short()) {
+            (Some(long), _) => format!("--{}", long),
+            (None, Some(short)) => format!("-{}", short),
+            (None, None) => String::new(),
+        };
+        match arg.get_action() {
+            ArgAction::SetTrue => {
+                if matches.get_flag(id) {
+                    words.push(flag);
+                }
+            }
+            ArgAction::Count => {
+                for _ in 0..matches.get_count(id) {
+                    words.push(flag.clone());
+                }
+            }
+            _ => {
+                for value in matches.get_raw(id).into_iter().flatten() {
+                    if !flag.is_empty() {
+                        words.push(flag.clone());
+                    }
+                    words.push(shell_word(&value.to_string_lossy()));
+                }
+            }
+        }
+    }
+    words.join(" ")
+}
+
+/// Leaves `text` bare when a POSIX shell would read it back unchanged, and
+/// quotes it otherwise.
+fn shell_word(text: &str) -> String {
+    let is_plain = |c: char| c.is_ascii_alphanumeric() || "-_.,/:=+@%".contains(c);
+    if !text.is_empty() && text.chars().all(is_plain) {
+        text.to_string()
+    } else {
+        shell_quote(text)
+    }
+}
+
+/// Wraps `text` in single quotes for a POSIX shell.
+///
+/// Nothing is special inside single quotes except the quote itself, which is
+/// written as `'\''`: close the quote, an escaped quote, reopen.
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+/// Verbosity flags shared by every command.
+#[derive(Args, Debug)]
+struct OutputArgs {
+    /// Suppress non-essential output on stderr (warnings, entropy reports)
+    #[arg(short, long, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Print details such as the charset size, applied flags and timing to stderr
+    #[arg(short, long)]
+    verbose: bool,
+}
+
+/// Character set selection flags shared by every generating command.
+#[derive(Args, Debug)]
+struct CharsetArgs {
+    /// Exclude symbols from the password
+    #[arg(long, env = "PWGEN_NO_SYMBOLS", value_parser = FalseyValueParser::new())]
+    no_symbols: bool,
+
+    /// Exclude digits from the password
+    #[arg(long, env = "PWGEN_NO_NUMBERS", value_parser = FalseyValueParser::new())]
+    no_numbers: bool,
+
+    /// Use only letters (uppercase and lowercase)
+    /// This is equivalent to --no-symbols --no-numbers
+    #[arg(long, env = "PWGEN_ONLY_LETTERS", value_parser = FalseyValueParser::new())]
+    only_letters: bool,
+
+    /// Use CHARS as the symbol set instead of the built-in one
+    /// Every character must be ASCII punctuation
+    #[arg(long, value_name = "CHARS", env = "PWGEN_SYMBOLS", allow_hyphen_values = true, conflicts_with_all = ["no_symbols", "only_letters"])]
+    symbols: Option<String>,
+
+    /// Use CHARS as the digit set instead of `0-9`, e.g. `01234567` for octal
+    /// Every character must be an ASCII letter or digit
+    #[arg(long, value_name = "CHARS", conflicts_with_all = ["no_numbers", "only_letters"])]
+    digits_set: Option<String>,
+
+    /// Characters to exclude from the password
+    #[arg(long, value_name = "CHARS", env = "PWGEN_EXCLUDE")]
+    exclude: Option<String>,
+
+    /// Exclude every character in PATH (whitespace and newlines are ignored)
+    /// Combines with --exclude
+    #[arg(long, value_name = "PATH")]
+    exclude_file: Option<PathBuf>,
+
+    /// Exclude characters that are easily confused with one another
+    #[arg(long, env = "PWGEN_NO_AMBIGUOUS", value_parser = FalseyValueParser::new())]
+    no_ambiguous: bool,
+
+    /// Which look-alikes --no-ambiguous removes
+    #[arg(long, value_enum, default_value_t = AmbiguousSet::Latin, requires = "no_ambiguous")]
+    ambiguous_set: AmbiguousSet,
+
+    /// Exclude characters with identical Cyrillic or Greek look-alikes: the Latin
+    /// --no-ambiguous set plus A a C c E e H J j K M N P p s T X x Y y 3
+    /// Non-ASCII pools also lose the Cyrillic and Greek twins themselves
+    #[arg(long)]
+    exclude_homoglyphs: bool,
+
+    /// A complete charset supplied by the command (e.g. `generate
+    /// --charset-from-stdin`) that replaces the flags above.
+    #[arg(skip)]
+    custom: Option<Vec<u8>>,
+}
+
+/// Look-alike sets selectable with `--ambiguous-set`.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum AmbiguousSet {
+    /// 0 O 1 l I o Q D i | ! 5 S 2 Z 8 B
+    Latin,
+    /// Only digit look-alikes: 0 O 1 l I
+    Numeric,
+    /// Remove nothing
+    None,
+}
+
+impl AmbiguousSet {
+    fn chars(self) -> &'static [u8] {
+        match self {
+            AmbiguousSet::Latin => AMBIGUOUS_LATIN,
+            AmbiguousSet::Numeric => AMBIGUOUS_NUMERIC,
+            AmbiguousSet::None => &[],
+        }
+    }
+}
+
+/// How much non-essential output goes to stderr.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose,
+}
+
+/// Routes every stderr message through one place so `-q` and `-v` apply uniformly.
+///
+/// The password itself always goes to stdout and is never logged.
+#[derive(Clone, Copy, Debug)]
+struct Logger {
+    verbosity: Verbosity,
+}
+
+impl Logger {
+    fn new(args: &OutputArgs) -> Self {
+        let verbosity = if args.quiet {
+            Verbosity::Quiet
+        } else if args.verbose {
+            Verbosity::Verbose
+        } else {
+            Verbosity::Normal
+        };
+        Self { verbosity }
+    }
+
+    /// Informational output shown unless `--quiet` is set.
+    fn info(&self, message: impl Display) {
+        if self.verbosity >= Verbosity::Normal {
+            eprintln!("{}", message);
+        }
+    }
+
+    /// Warnings shown unless `--quiet` is set.
+    fn warn(&self, message: impl Display) {
+        if self.verbosity >= Verbosity::Normal {
+            eprintln!("Warning: {}", message);
+        }
+    }
+
+    /// Diagnostic details shown only with `--verbose`.
+    fn verbose(&self, message: impl Display) {
+        if self.verbosity >= Verbosity::Verbose {
+            eprintln!("{}", message);
+        }
+    }
+}
+
+/// Prints an error to stderr and exits with status 1, for failures deep inside
+/// a helper that [`validate`] has already ruled out once. Everything else
+/// returns an [`AppError`] instead.
+///
+/// Errors are always shown, even under `--quiet`.
+fn exit_with_error(message: impl Display) -> ! {
+    eprintln!("Error: {}", message);
+    std::process::exit(1);
+}
+
+/// Prints a warning to stderr when `bits` is below the `--warn-below` threshold.
+///
+/// Generation still proceeds, so automation is never blocked by the warning.
+fn warn_if_weak(log: Logger, bits: f64, threshold: f64) {
+    if bits < threshold {
+        log.warn(format!(
+            "This configuration provides only {:.1} bits of entropy (threshold: {} bits). Consider a longer length or more character classes.",
+            bits, threshold
+        ));
+    }
+}
+
+/// Whether to color output going to a stream, given whether it is a
+/// terminal. Honors the `NO_COLOR` convention (<https://no-color.org>).
+fn auto_color(is_terminal: bool) -> bool {
+    is_terminal && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+}
+
+/// Longest password a command generates unless told otherwise, so an absurd
+/// length is rejected instead of aborting on a failed allocation.
+const DEFAULT_MAX_LENGTH: usize = 4096;
+
+/// Checks a requested password length against the minimum of 8 characters
+/// and against `max_length`.
+fn validate_length(length: usize, max_length: usize) -> Result<(), String> {
+    if length == 0 {
+        return Err("Password length must be greater than zero.".to_string());
+    }
+    if length < 8 {
+        return Err("Password length must be at least 8 characters.".to_string());
+    }
+    if length > max_length {
+        return Err(format!(
+            "Password length must be at most {} characters.",
+            max_length
+        ));
+    }
+    Ok(())
+}
+
+/// Translates the charset flags into library [`Options`].
+///
+/// Logic:
+/// 1. Always include letters (Upper + Lower) unless explicitly excluded (no flag for that yet).
+/// 2. If --only-letters is set, we skip digits and symbols.
+/// 3. Otherwise, include digits (--digits-set, if given) unless --no-numbers is set.
+/// 4. Otherwise, include symbols (--symbols, if given) unless --no-symbols is set.
+/// 5. Drop every character named by --exclude or --exclude-file.
+/// 6. Finally, drop the --ambiguous-set look-alikes if --no-ambiguous is set,
+///    and the broader homoglyph set if --exclude-homoglyphs is.
+///
+/// Reading --exclude-file can fail, hence `TryFrom` rather than `From`. The
+/// length is left at its default; each command sets its own.
+impl TryFrom<&CharsetArgs> for Options {
+    type Error = String;
+
+    fn try_from(args: &CharsetArgs) -> Result<Self, Self::Error> {
+        let mut exclude = excluded_chars(args)?;
+        if args.no_ambiguous {
+            exclude.extend(args.ambiguous_set.chars().iter().map(|&b| b as char));
+        }
+        if args.exclude_homoglyphs {
+            exclude.extend(HOMOGLYPHS_LATIN.iter().map(|&b| b as char));
+        }
+
+        Ok(Options {
+            digits: !args.only_letters && !args.no_numbers,
+            symbols: !args.only_letters && !args.no_symbols,
+            symbol_set: args.symbols.as_ref().map(|s| s.bytes().collect()),
+            digit_set: args.digits_set.as_ref().map(|s| s.bytes().collect()),
+            exclude,
+            ..Options::default()
+        })
+    }
+}
+
+/// Constructs the charset based on flags (see the `TryFrom<&CharsetArgs>` impl),
+/// unless a command supplied its own.
+fn build_charset(args: &CharsetArgs) -> Result<Vec<u8>, String> {
+    if let Some(custom) = &args.custom {
+        return Ok(custom.clone());
+    }
+    if let Some(symbols) = &args.symbols {
+        if symbols.is_empty() {
+            return Err(
+                "--symbols must not be empty; use --no-symbols to drop symbols.".to_string(),
+            );
+        }
+        if let Some((position, c)) = symbols
+            .chars()
+            .enumerate()
+            .find(|(_, c)| !c.is_ascii_punctuation())
+        {
+            return Err(format!(
+                "--symbols must contain only ASCII punctuation, found {} at position {}.",
+                describe_char(c),
+                position + 1
+            ));
+        }
+    }
+    if let Some(digits) = &args.digits_set {
+        if digits.is_empty() {
+            return Err(
+                "--digits-set must not be empty; use --no-numbers to drop digits.".to_string(),
+            );
+        }
+        if let Some((position, c)) = digits
+            .chars()
+            .enumerate()
+            .find(|(_, c)| !c.is_ascii_alphanumeric())
+        {
+            return Err(format!(
+                "--digits-set must contain only ASCII letters and digits, found {} at position {}.",
+                describe_char(c),
+                position + 1
+            ));
+        }
+    }
+    Options::try_from(args).map(|options| options.charset())
+}
+
+/// Names `c` for an error message, with its code point so invisible and
+/// look-alike characters can be told apart, e.g. `'“' (U+201C)`.
+fn describe_char(c: char) -> String {
+    format!("{:?} (U+{:04X})", c, c as u32)
+}
+
+/// Collects the union of `--exclude` and the contents of `--exclude-file`.
+fn excluded_chars(args: &CharsetArgs) -> Result<Vec<char>, String> {
+    // Surrounding whitespace is almost always a pasting accident.
+    let mut excluded: Vec<char> = args.exclude.iter().flat_map(|s| s.trim().chars()).collect();
+
+    if let Some(path) = &args.exclude_file {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read exclude file {}: {}", path.display(), err))?;
+        let before = excluded.len();
+        excluded.extend(contents.chars().filter(|c| !c.is_whitespace()));
+        if excluded.len() == before {
+            return Err(format!(
+                "Exclude file {} contains no characters.",
+                path.display()
+            ));
+        }
+    }
+
+    Ok(excluded)
+}
+
+/// Builds the charset and checks it isn't empty.
+fn validate_charset(args: &CharsetArgs) -> Result<Vec<u8>, String> {
+    // Exclusions can remove every character
+    let charset = build_charset(args)?;
+    if charset.is_empty() && args.exclude_homoglyphs {
+        return Err(
+            "Character set is empty: --exclude-homoglyphs and the other flags remove every character."
+                .to_string(),
+        );
+    }
+    if charset.is_empty() {
+        return Err("Character set is empty. Please check your flags.".to_string());
+    }
+    Ok(charset)
+}
+
+/// Builds the charset, exiting on error. Only used after [`validate`] has
+/// already built it successfully once.
+fn charset_or_exit(args: &CharsetArgs) -> Vec<u8> {
+    build_charset(args).unwrap_or_else(|message| exit_with_error(message))
+}
+
+/// Reads a secret from a hidden prompt, or from the first line of stdin when
+/// it is not a terminal. Secrets are never taken from the command line, where
+/// they would leak into shell history and process listings.
+fn read_secret(prompt: &str) -> io::Result<String> {
+    if io::stdin().is_terminal() {
+        return rpassword::prompt_password(prompt);
+    }
+
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line)?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Command-line parsing for the unit tests of every command.
+#[cfg(test)]
+mod test_support {
+    use std::sync::{Mutex, MutexGuard, PoisonError};
+
+    use clap::Parser;
+
+    use super::Cli;
+
+    /// Held while parsing or while changing `PWGEN_*` variables: parsing reads
+    /// them, and tests run in parallel.
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    pub(super) fn lock_env() -> MutexGuard<'static, ()> {
+        ENV_LOCK.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Parses `args` as the words after the program name.
+    pub(super) fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+        parse_with_env(&[], args)
+    }
+
+    /// Like [`parse`], with the `(name, value)` variables in `env` set for
+    /// the duration of the parse.
+    pub(super) fn parse_with_env(env: &[(&str, &str)], args: &[&str]) -> Result<Cli, clap::Error> {
+        let _env = lock_env();
+        for (name, value) in env {
+            // SAFETY: every test that reads or writes the environment holds
+            // the lock.
+            unsafe { std::env::set_var(name, value) };
+        }
+        let cli = Cli::try_parse_from(
+            std::iter::once("password-generator-rust").chain(args.iter().copied()),
+        );
+        for (name, _) in env {
+            // SAFETY: as above.
+            unsafe { std::env::remove_var(name) };
+        }
+        cli
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::lock_env;
+
+    /// Splits `line` into words like a POSIX shell, for the bare and
+    /// single-quoted words [`shell_word`] writes.
+    fn split_words(line: &str) -> Vec<String> {
+        let mut words = Vec::new();
+        let mut word: Option<String> = None;
+        let mut quoted = false;
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\'' => {
+                    quoted = !quoted;
+                    word.get_or_insert_default();
+                }
+                '\\' if !quoted => word.get_or_insert_default().extend(chars.next()),
+                ' ' if !quoted => words.extend(word.take()),
+                c => word.get_or_insert_default().push(c),
+            }
+        }
+        words.extend(word);
+        words
+    }
+
+    /// Parses `argv` into the command line `canonical_command` prints and the
+    /// `Cli` it describes.
+    fn canonical(argv: &[&str]) -> (String, Cli) {
+        let command = Cli::command();
+        let matches = command
+            .clone()
+            .try_get_matches_from(
+                std::iter::once("password-generator-rust").chain(argv.iter().copied()),
+            )
+            .expect("the command line parses");
+        let cli = Cli::from_arg_matches(&matches).expect("the matches convert");
+        (canonical_command(&command, &matches), cli)
+    }
+
+    /// Just the shared charset flags, to test them apart from any command.
+    #[derive(Parser, Debug)]
+    struct CharsetCli {
+        #[command(flatten)]
+        charset: CharsetArgs,
+    }
+
+    fn options(argv: &[&str]) -> Options {
+        let _env = lock_env();
+        let cli = CharsetCli::try_parse_from(std::iter::once("test").chain(argv.iter().copied()))
+            .expect("the charset flags parse");
+        Options::try_from(&cli.charset).expect("the charset flags convert")
+    }
+
+    #[test]
+    fn default_options() {
+        let options = Options::default();
+        assert_eq!(options.length, 16);
+        assert!(options.uppercase && options.lowercase && options.digits && options.symbols);
+        assert_eq!(options.symbol_set, None);
+        assert_eq!(options.digit_set, None);
+        assert!(options.exclude.is_empty());
+        assert_eq!(options.charset().len(), 26 + 26 + 10 + 25);
+    }
+
+    #[test]
+    fn no_flags_map_to_the_default_options() {
+        assert_eq!(options(&[]), Options::default());
+    }
+
+    #[test]
+    fn no_symbols_drops_only_symbols() {
+        let options = options(&["--no-symbols"]);
+        assert_eq!(
+            options,
+            Options {
+                symbols: false,
+                ..Options::default()
+            }
+        );
+        assert!(options.charset().iter().all(u8::is_ascii_alphanumeric));
+    }
+
+    #[test]
+    fn no_numbers_drops_only_digits() {
+        let options = options(&["--no-numbers"]);
+        assert_eq!(
+            options,
+            Options {
+                digits: false,
+                ..Options::default()
+            }
+        );
+        assert!(!options.charset().iter().any(u8::is_ascii_digit));
+        assert!(options.charset().iter().any(u8::is_ascii_punctuation));
+    }
+
+    #[test]
+    fn only_letters_drops_digits_and_symbols() {
+        let expected = Options {
+            digits: false,
+            symbols: false,
+            ..Options::default()
+        };
+        assert_eq!(options(&["--only-letters"]), expected);
+        assert!(expected.charset().iter().all(u8::is_ascii_alphabetic));
+    }
+
+    #[test]
+    fn split_words_undoes_shell_quote() {
+        for text in ["plain", "two words", "it's", "'", "", "a\\b", "--flag"] {
+            assert_eq!(split_words(&shell_word(text)), [text]);
+        }
+    }
+
+    #[test]
+    fn canonical_command_round_trips() {
+        let _env = lock_env();
+        for argv in [
+            &[][..],
+            &["--length", "24", "--count", "3", "-q"],
+            &["-l", "20", "--no-symbols", "--no-ambiguous"],
+            &[
+                "--symbols",
+                "!@#'\"$ ",
+                "--prefix",
+                "it's ",
+                "--suffix",
+                "-x",
+            ],
+            &[
+                "--charset",
+                " a b'c ",
+                "--strip-whitespace",
+                "--length",
+                "8",
+            ],
+            &["--sections", "8:alnum,4:digits", "--section-separator", "-"],
+            &[
+                "--require-from",
+                "ab",
+                "--require-from",
+                "0-9",
+                "--match",
+                "^[a-z]",
+            ],
+            &["--avoid-adjacent", "--format", "csv", "--no-header"],
+            &["generate", "-l", "12", "--prefix", ""],
+            &["passphrase", "--words", "5", "--separator", " "],
+            &["token", "--bytes", "16", "--encoding", "base64"],
+        ] {
+            let (line, cli) = canonical(argv);
+            let words = split_words(&line);
+            assert_eq!(words[0], "password-generator-rust", "{:?}", line);
+            let (again, reparsed) =
+                canonical(&words[1..].iter().map(String::as_str).collect::<Vec<_>>());
+            assert_eq!(
+                format!("{:?}", reparsed),
+                format!("{:?}", cli),
+                "{:?}",
+                line
+            );
+            assert_eq!(again, line, "{:?}", argv);
+        }
+    }
+
+    #[test]
+    fn exit_codes() {
+        assert_eq!(AppError::Usage("bad flag".to_string()).exit_code(), 2);
+        assert_eq!(AppError::Runtime("no candidate".to_string()).exit_code(), 1);
+        let io = AppError::Io {
+            context: "Failed to read".to_string(),
+            source: io::Error::other("gone"),
+        };
+        assert_eq!(io.exit_code(), 1);
+        assert_eq!(AppError::CheckFailed.exit_code(), 1);
+    }
+
+    #[test]
+    fn strings_are_runtime_errors() {
+        let err = AppError::from("no candidate".to_string());
+        assert!(matches!(err, AppError::Runtime(ref message) if message == "no candidate"));
+    }
+}
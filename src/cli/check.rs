@@ -0,0 +1,239 @@
+//! `check`: strength estimate for an existing password.
+//!
+//! The password under test is a secret, so any comparison against known
+//! values (the `--dictionary-check` blocklist) runs in constant time.
+
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
+
+use clap::{Args, ValueEnum};
+use owo_colors::OwoColorize;
+use password_generator_rust::blocklist::Blocklist;
+use password_generator_rust::charset::CharClass;
+use password_generator_rust::checksum::is_valid;
+use password_generator_rust::policy::PRESETS;
+use password_generator_rust::strength::{classes_present, classify, estimate_entropy_bits};
+
+use super::{AppError, CharsetArgs, auto_color, read_secret, validate_charset};
+
+#[derive(Args, Debug)]
+pub(super) struct CheckArgs {
+    /// Exit with status 1 when the estimated entropy is below this many bits
+    #[arg(long, value_name = "BITS", default_value_t = 60.0)]
+    min_bits: f64,
+
+    /// Also flag the password (exit status 1) if it contains a blocklisted substring
+    #[arg(long)]
+    dictionary_check: bool,
+
+    /// Replace the embedded blocklist with one substring per line from PATH
+    #[arg(long, value_name = "PATH", requires = "dictionary_check")]
+    blocklist: Option<PathBuf>,
+
+    /// Also flag the password (exit status 1) unless its last character is a valid
+    /// --append-checksum check character; pass the charset flags used to generate it
+    #[arg(long, visible_alias = "checksum")]
+    verify_checksum: bool,
+
+    /// Also flag the password (exit status 1) if it is shorter than N characters
+    #[arg(long, value_name = "N")]
+    min_length: Option<usize>,
+
+    /// Also flag the password (exit status 1) if it is longer than N characters,
+    /// for systems that cap the length
+    #[arg(long, value_name = "N")]
+    max_length: Option<usize>,
+
+    /// Also report which --policy presets the password meets, and why it fails the others
+    /// Informational only: a failed policy doesn't change the exit status
+    #[arg(long)]
+    policies: bool,
+
+    #[command(flatten)]
+    charset: CharsetArgs,
+
+    /// Color the strength verdict; `auto` colors only a terminal and honors NO_COLOR
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+}
+
+/// When to color output, selectable with `--color`.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum ColorChoice {
+    /// Color when stdout is a terminal and NO_COLOR is unset
+    Auto,
+    /// Always color
+    Always,
+    /// Never color
+    Never,
+}
+
+impl ColorChoice {
+    fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Auto => auto_color(io::stdout().is_terminal()),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+fn class_name(class: CharClass) -> &'static str {
+    match class {
+        CharClass::Upper => "upper",
+        CharClass::Lower => "lower",
+        CharClass::Digit => "digit",
+        CharClass::Symbol => "symbol",
+    }
+}
+
+/// Which of `--min-length` and `--max-length` a password of `length`
+/// characters breaks, if either.
+fn length_problem(length: usize, args: &CheckArgs) -> Option<String> {
+    match (args.min_length, args.max_length) {
+        (Some(min), _) if length < min => Some(format!("too short, minimum {}", min)),
+        (_, Some(max)) if length > max => Some(format!("too long, maximum {}", max)),
+        _ => None,
+    }
+}
+
+pub(super) fn run(args: CheckArgs) -> Result<(), AppError> {
+    if let (Some(min), Some(max)) = (args.min_length, args.max_length)
+        && min > max
+    {
+        return Err(AppError::Usage(format!(
+            "--min-length {} is above --max-length {}.",
+            min, max
+        )));
+    }
+    let password = read_secret("Password to check: ").map_err(|source| AppError::Io {
+        context: "Failed to read password".to_string(),
+        source,
+    })?;
+    if password.is_empty() {
+        return Err(AppError::Runtime("Password must not be empty.".to_string()));
+    }
+
+    let classes: Vec<&str> = classes_present(&password)
+        .into_iter()
+        .map(class_name)
+        .collect();
+    let blocklisted = args.dictionary_check && {
+        let blocklist = match &args.blocklist {
+            Some(path) => Blocklist::from_file(path).map_err(|source| AppError::Io {
+                context: format!("Failed to read blocklist {}", path.display()),
+                source,
+            })?,
+            None => Blocklist::embedded(),
+        };
+        blocklist.find_in(&password).is_some()
+    };
+    let bad_checksum = args.verify_checksum && {
+        let charset = validate_charset(&args.charset)?;
+        !is_valid(&password, &charset)
+    };
+    let length = password.chars().count();
+    let length_problem = length_problem(length, &args);
+    let bits = estimate_entropy_bits(&password);
+    let verdict = if blocklisted {
+        "weak"
+    } else if bits >= 80.0 {
+        "strong"
+    } else if bits >= 60.0 {
+        "fair"
+    } else {
+        "weak"
+    };
+
+    match &length_problem {
+        Some(problem) => println!("Length: {} ({})", length, problem),
+        None => println!("Length: {}", length),
+    }
+    println!("Classes: {}", classes.join(", "));
+    println!("Counts: {}", classify(&password));
+    println!("Estimated entropy: {:.1} bits (upper bound)", bits);
+    if args.dictionary_check {
+        let found = if blocklisted { "yes" } else { "no" };
+        println!("Blocklisted substring: {}", found);
+    }
+    if args.verify_checksum {
+        let status = if bad_checksum { "invalid" } else { "valid" };
+        println!("Checksum: {}", status);
+    }
+    if args.policies {
+        for policy in &PRESETS {
+            let violations = policy.violations(&password);
+            if violations.is_empty() {
+                println!("Policy {}: meets", policy.name);
+            } else {
+                println!("Policy {}: fails, {}", policy.name, violations.join("; "));
+            }
+        }
+    }
+    if args.color.enabled() {
+        let colored = match verdict {
+            "strong" => verdict.green().to_string(),
+            "fair" => verdict.yellow().to_string(),
+            _ => verdict.red().to_string(),
+        };
+        println!("Strength: {}", colored);
+    } else {
+        println!("Strength: {}", verdict);
+    }
+
+    if blocklisted || bad_checksum || length_problem.is_some() || bits < args.min_bits {
+        return Err(AppError::CheckFailed);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Command;
+    use crate::cli::test_support::parse;
+
+    fn check_args(argv: &[&str]) -> CheckArgs {
+        let argv: Vec<&str> = std::iter::once("check")
+            .chain(argv.iter().copied())
+            .collect();
+        match parse(&argv).expect("the command line parses").command {
+            Some(Command::Check(args)) => args,
+            other => panic!("expected check, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn length_within_bounds() {
+        let args = check_args(&["--min-length", "12", "--max-length", "16"]);
+        for length in [12, 14, 16] {
+            assert_eq!(length_problem(length, &args), None, "{}", length);
+        }
+        assert_eq!(length_problem(1000, &check_args(&[])), None);
+    }
+
+    #[test]
+    fn length_below_min_length() {
+        let args = check_args(&["--min-length", "12", "--max-length", "16"]);
+        assert_eq!(
+            length_problem(11, &args).as_deref(),
+            Some("too short, minimum 12")
+        );
+    }
+
+    #[test]
+    fn length_above_max_length() {
+        let args = check_args(&["--min-length", "12", "--max-length", "16"]);
+        assert_eq!(
+            length_problem(17, &args).as_deref(),
+            Some("too long, maximum 16")
+        );
+        assert!(length_problem(17, &check_args(&["--max-length", "16"])).is_some());
+    }
+
+    #[test]
+    fn inverted_bounds_are_a_usage_error() {
+        let args = check_args(&["--min-length", "20", "--max-length", "10"]);
+        assert!(matches!(run(args), Err(AppError::Usage(_))));
+    }
+}
@@ -0,0 +1,145 @@
+//! `--clipboard`: copy the password instead of printing it.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use arboard::Clipboard;
+
+use super::Logger;
+
+/// Copies `password`, the newline-joined batch of `count` passwords, to the
+/// system clipboard.
+///
+/// With a `timeout`, waits that long and then restores the previous clipboard
+/// text (or clears the clipboard if there was none). If the clipboard no
+/// longer holds the password by then, something else was copied in the
+/// meantime and the newer contents are left alone.
+///
+/// On Linux the clipboard is served by the process that set it, so the copy
+/// only lasts while this process runs. Without a timeout it keeps serving
+/// until another application copies something.
+///
+/// Where the platform has a way to do so, the password is marked to be kept
+/// out of clipboard history; elsewhere a warning says it may be retained.
+pub(super) fn copy(
+    password: &str,
+    count: usize,
+    timeout: Option<Duration>,
+    log: Logger,
+) -> Result<(), String> {
+    let mut clipboard =
+        Clipboard::new().map_err(|err| format!("Failed to open the clipboard: {}", err))?;
+
+    if !EXCLUDES_FROM_HISTORY {
+        log.warn(
+            "This platform offers no way to keep the password out of clipboard history; a clipboard manager may retain it.",
+        );
+    }
+
+    let copied = copied_message(count);
+    let Some(timeout) = timeout else {
+        log.info(format!("{}.", copied));
+        return set(&mut clipboard, password, None);
+    };
+
+    // Read before overwriting so it can be put back afterwards.
+    let previous = clipboard.get_text().ok();
+    let deadline = Instant::now() + timeout;
+    log.info(format!(
+        "{}; clearing in {} seconds.",
+        copied,
+        timeout.as_secs()
+    ));
+    set(&mut clipboard, password, Some(deadline))?;
+    thread::sleep(deadline.saturating_duration_since(Instant::now()));
+
+    if clipboard.get_text().ok().as_deref() != Some(password) {
+        log.info("Clipboard changed since copying; leaving it untouched.");
+        return Ok(());
+    }
+    match previous {
+        Some(text) => clipboard.set_text(text),
+        None => clipboard.clear(),
+    }
+    .map_err(|err| format!("Failed to restore the clipboard: {}", err))?;
+    log.info("Clipboard restored.");
+    Ok(())
+}
+
+/// The confirmation for copying `count` passwords, without a full stop so
+/// the timeout can be appended.
+fn copied_message(count: usize) -> String {
+    match count {
+        1 => "Copied to clipboard".to_string(),
+        _ => format!("Copied {} passwords to clipboard", count),
+    }
+}
+
+/// Whether [`set`] can keep the password out of clipboard history here.
+const EXCLUDES_FROM_HISTORY: bool = cfg!(any(
+    target_os = "linux",
+    target_os = "windows",
+    target_os = "macos"
+));
+
+/// Places `text` on the clipboard, serving it until `until` on Linux.
+#[cfg(target_os = "linux")]
+fn set(clipboard: &mut Clipboard, text: &str, until: Option<Instant>) -> Result<(), String> {
+    use arboard::SetExtLinux;
+
+    // Keep the password out of clipboard managers' histories.
+    let set = clipboard.set().exclude_from_history();
+    match until {
+        Some(deadline) => set.wait_until(deadline),
+        None => set.wait(),
+    }
+    .text(text)
+    .map_err(|err| format!("Failed to copy to the clipboard: {}", err))
+}
+
+/// Places `text` on the clipboard.
+#[cfg(target_os = "windows")]
+fn set(clipboard: &mut Clipboard, text: &str, _until: Option<Instant>) -> Result<(), String> {
+    use arboard::SetExtWindows;
+
+    // Sets ExcludeClipboardContentFromMonitorProcessing, which keeps the
+    // password out of Clipboard History and cloud clipboard sync.
+    clipboard
+        .set()
+        .exclude_from_monitoring()
+        .text(text)
+        .map_err(|err| format!("Failed to copy to the clipboard: {}", err))
+}
+
+/// Places `text` on the clipboard.
+#[cfg(target_os = "macos")]
+fn set(clipboard: &mut Clipboard, text: &str, _until: Option<Instant>) -> Result<(), String> {
+    use arboard::SetExtApple;
+
+    // Marks the entry org.nspasteboard.ConcealedType, which clipboard
+    // managers honor by not recording it.
+    clipboard
+        .set()
+        .exclude_from_history()
+        .text(text)
+        .map_err(|err| format!("Failed to copy to the clipboard: {}", err))
+}
+
+/// Places `text` on the clipboard.
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+fn set(clipboard: &mut Clipboard, text: &str, _until: Option<Instant>) -> Result<(), String> {
+    clipboard
+        .set_text(text)
+        .map_err(|err| format!("Failed to copy to the clipboard: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copied_message_counts_a_batch() {
+        assert_eq!(copied_message(1), "Copied to clipboard");
+        assert_eq!(copied_message(3), "Copied 3 passwords to clipboard");
+    }
+}
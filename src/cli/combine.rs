@@ -0,0 +1,52 @@
+//! `combine`: recover a password from the shares printed by `--split`.
+
+use std::fs;
+use std::io::{self, IsTerminal, Read};
+use std::path::PathBuf;
+
+use clap::Args;
+use password_generator_rust::shamir::combine;
+
+use super::AppError;
+
+#[derive(Args, Debug)]
+pub(super) struct CombineArgs {
+    /// Files holding the shares, one or more per file; without any, shares are
+    /// read from stdin, one per line
+    #[arg(value_name = "FILE")]
+    files: Vec<PathBuf>,
+}
+
+pub(super) fn run(args: CombineArgs) -> Result<(), AppError> {
+    let mut input = String::new();
+    if args.files.is_empty() {
+        if io::stdin().is_terminal() {
+            eprintln!("Enter the shares, one per line, then press Ctrl-D:");
+        }
+        io::stdin()
+            .lock()
+            .read_to_string(&mut input)
+            .map_err(|source| AppError::Io {
+                context: "Failed to read shares from stdin".to_string(),
+                source,
+            })?;
+    }
+    for path in &args.files {
+        let contents = fs::read_to_string(path).map_err(|source| AppError::Io {
+            context: format!("Failed to read {}", path.display()),
+            source,
+        })?;
+        input.push_str(&contents);
+        input.push('\n');
+    }
+
+    let shares: Vec<&str> = input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    let password = combine(&shares)
+        .map_err(|err| AppError::Runtime(format!("Failed to combine the shares: {}", err)))?;
+    println!("{}", password);
+    Ok(())
+}
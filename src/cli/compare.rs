@@ -0,0 +1,82 @@
+//! `compare`: side-by-side entropy of two configurations, without generating.
+
+use clap::Args;
+use password_generator_rust::entropy_bits;
+use password_generator_rust::options::Options;
+
+use super::{DEFAULT_MAX_LENGTH, validate_length};
+
+#[derive(Args, Debug)]
+pub(super) struct CompareArgs {
+    /// First configuration, e.g. `length=12,symbols=true`
+    /// Keys: length, uppercase, lowercase, digits, symbols, exclude
+    #[arg(long, value_name = "SPEC", value_parser = parse_spec)]
+    a: Options,
+
+    /// Second configuration, in the same form as --a
+    #[arg(long, value_name = "SPEC", value_parser = parse_spec)]
+    b: Options,
+}
+
+/// Parses a comma-separated `key=value` list into [`Options`]. Keys that
+/// aren't given keep their defaults (16 characters, every class).
+fn parse_spec(spec: &str) -> Result<Options, String> {
+    let mut options = Options::default();
+    for pair in spec.split(',').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair
+            .split_once('=')
+            .ok_or_else(|| format!("expected key=value, found '{}'", pair))?;
+        let flag = || {
+            value
+                .parse::<bool>()
+                .map_err(|_| format!("'{}' must be true or false, found '{}'", key, value))
+        };
+        match key.trim() {
+            "length" => {
+                options.length = value
+                    .parse()
+                    .map_err(|_| format!("'length' must be a whole number, found '{}'", value))?;
+                validate_length(options.length, DEFAULT_MAX_LENGTH)?;
+            }
+            "uppercase" => options.uppercase = flag()?,
+            "lowercase" => options.lowercase = flag()?,
+            "digits" => options.digits = flag()?,
+            "symbols" => options.symbols = flag()?,
+            "exclude" => options.exclude = value.chars().collect(),
+            other => return Err(format!("unknown key '{}'", other)),
+        }
+    }
+    if options.charset().is_empty() {
+        return Err("the character set is empty".to_string());
+    }
+    Ok(options)
+}
+
+pub(super) fn run(args: CompareArgs) {
+    let size_a = args.a.charset().len();
+    let size_b = args.b.charset().len();
+    let bits_a = entropy_bits(args.a.length, size_a);
+    let bits_b = entropy_bits(args.b.length, size_b);
+
+    println!("{:<14}{:>10}{:>10}", "", "A", "B");
+    println!("{:<14}{:>10}{:>10}", "Length", args.a.length, args.b.length);
+    println!("{:<14}{:>10}{:>10}", "Charset size", size_a, size_b);
+    println!("{:<14}{:>10.1}{:>10.1}", "Entropy (bits)", bits_a, bits_b);
+
+    let difference = bits_b - bits_a;
+    if difference.abs() < 0.05 {
+        println!("Difference: none");
+        return;
+    }
+    let (stronger, margin) = if difference > 0.0 {
+        ("B", difference)
+    } else {
+        ("A", -difference)
+    };
+    println!(
+        "Difference: {} is stronger by {:.1} bits ({:.1e} times as many guesses)",
+        stronger,
+        margin,
+        margin.exp2()
+    );
+}
@@ -0,0 +1,57 @@
+//! `derive`: deterministic site passwords from a master secret.
+
+use clap::Args;
+use password_generator_rust::derive::derive_password;
+
+use super::{
+    AppError, CharsetArgs, DEFAULT_MAX_LENGTH, charset_or_exit, read_secret, validate_charset,
+    validate_length,
+};
+
+#[derive(Args, Debug)]
+pub(super) struct DeriveArgs {
+    /// Site label the password is derived for (e.g. example.com)
+    #[arg(long, visible_alias = "salt")]
+    site: String,
+
+    /// Bump to rotate the password for a site
+    #[arg(long, default_value_t = 1)]
+    counter: u32,
+
+    /// Password length
+    #[arg(short, long, default_value_t = 16)]
+    length: usize,
+
+    #[command(flatten)]
+    charset: CharsetArgs,
+}
+
+pub(super) fn validate(args: &DeriveArgs) -> Result<(), String> {
+    validate_length(args.length, DEFAULT_MAX_LENGTH)?;
+    validate_charset(&args.charset)?;
+    Ok(())
+}
+
+pub(super) fn run(args: DeriveArgs) -> Result<(), AppError> {
+    let master = read_secret("Master password: ").map_err(|source| AppError::Io {
+        context: "Failed to read master password".to_string(),
+        source,
+    })?;
+    if master.is_empty() {
+        return Err(AppError::Runtime(
+            "Master password must not be empty.".to_string(),
+        ));
+    }
+
+    let charset = charset_or_exit(&args.charset);
+    let password = derive_password(
+        master.as_bytes(),
+        &args.site,
+        args.counter,
+        args.length,
+        &charset,
+    )
+    .map_err(|err| AppError::Runtime(format!("Key derivation failed: {}", err)))?;
+    println!("{}", password);
+    Ok(())
+}
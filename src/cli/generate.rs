@@ -0,0 +1,3001 @@
+//! `generate`: random passwords (the default command).
+
+use std::io::{self, BufRead, IsTerminal};
+use std::path::PathBuf;
+use std::time::{Instant, SystemTime};
+
+use clap::{ArgGroup, Args, ValueEnum};
+use indicatif::ProgressBar;
+use password_generator_rust::balanced::class_pools;
+use password_generator_rust::charset::{
+    ARABIC_INDIC_DIGITS, DEVANAGARI_DIGITS, DIGITS, LOWERCASE, SYMBOLS, UPPERCASE,
+};
+use password_generator_rust::checksum::check_char;
+use password_generator_rust::compat::{self, System};
+use password_generator_rust::constraints::DEFAULT_MAX_RETRIES;
+use password_generator_rust::policy::{self, Policy};
+use password_generator_rust::positions::{ClassMask, PositionRules};
+use password_generator_rust::rng::RngSource;
+use password_generator_rust::sections::Sections;
+use password_generator_rust::shamir::Scheme;
+use password_generator_rust::strength::classify;
+use password_generator_rust::template::Template;
+use password_generator_rust::token::Encoding;
+use password_generator_rust::{
+    collision_probability, crack_time_estimate, entropy_bits, entropy_per_char, grow_length,
+    length_for_bits, normalize_nfc,
+};
+
+use super::{
+    AppError, CharsetArgs, DEFAULT_MAX_LENGTH, Logger, OutputArgs, charset_or_exit, describe_char,
+    excluded_chars, shell_quote, validate_charset, validate_length, warn_if_weak,
+};
+
+mod constraints;
+mod input;
+mod modes;
+mod output;
+
+use constraints::{Constraints, check_required_sets, compile_pattern};
+use input::{
+    apply_class_mask, ascii_lookalike, custom_pool, load_charset_file, load_template_file,
+    move_ascii_pool, read_charset, read_length, read_number, warn_about_whitespace,
+};
+use modes::{Mode, build_rng, draw_parallel, print_histogram, reseed_due};
+use output::{
+    BatchSummary, csv_schema, describe_crack_time, destination, emit, is_variable_name,
+    render_meter, rfc3339, write_audit_log,
+};
+
+/// Below this many bits per character a charset is weaker than letters and
+/// digits (about 5.95 bits), and a warning points out that it needs extra length.
+const WEAK_CHAR_BITS: f64 = 5.8;
+
+/// Size of the charset used when no class flag is given, for comparison in
+/// the weak-charset warning.
+const DEFAULT_CHARSET_LEN: usize = UPPERCASE.len() + LOWERCASE.len() + DIGITS.len() + SYMBOLS.len();
+
+/// Longest password `--bits` may request, so a tiny charset with a huge
+/// target doesn't produce an absurd length.
+const BITS_MAX_LENGTH: usize = 1024;
+
+/// Passphrase lengths WPA2 and WPA3 accept, for `--wifi`.
+const WPA_LENGTH: std::ops::RangeInclusive<usize> = 8..=63;
+
+/// Largest batch `--count` accepts. The batch is held in memory before it is
+/// written, so a typo such as an extra few zeros would otherwise exhaust it.
+const MAX_COUNT: usize = 10_000_000;
+
+/// Batches of at least this many passwords show a progress bar on stderr.
+const PROGRESS_THRESHOLD: usize = 10_000;
+
+#[derive(Args, Debug)]
+// The flags that pick how the whole password is built (see `Mode`); at most
+// one of them applies, so each joins this group instead of listing the others.
+#[command(group(ArgGroup::new("mode").multiple(false)))]
+pub(super) struct GenerateArgs {
+    /// Password length
+    #[arg(short, long, default_value_t = 16, env = "PWGEN_LENGTH")]
+    length: usize,
+
+    /// Use the flags saved as profile NAME in the profile file as defaults;
+    /// flags given on the command line still override them
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+
+    /// Read --profile from PATH instead of ~/.config/password-generator-rust/profiles.json
+    #[arg(long, value_name = "PATH", requires = "profile")]
+    profile_file: Option<PathBuf>,
+
+    /// Reject any --length (or --length-from-stdin) above N characters
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_LENGTH)]
+    max_length: usize,
+
+    /// Reject any --length (or --length-from-stdin) below N characters; pairs with
+    /// `check --min-length` to generate and verify against the same bounds
+    /// A length sized by --bits is raised to N instead
+    #[arg(long, value_name = "N")]
+    min_length: Option<usize>,
+
+    /// Size the password to reach at least this many bits of entropy
+    /// The length becomes ceil(BITS / log2(charset size))
+    #[arg(
+        long,
+        visible_alias = "entropy-bits",
+        value_name = "BITS",
+        conflicts_with = "length"
+    )]
+    bits: Option<f64>,
+
+    /// Read the password length from the first line of stdin
+    #[arg(long, conflicts_with_all = ["length", "bits"])]
+    length_from_stdin: bool,
+
+    /// Set once --length-from-stdin has replaced the default length.
+    #[arg(skip)]
+    length_read: bool,
+
+    /// Size the password in bytes of entropy rather than characters
+    /// With --encoding chars the length becomes ceil(8N / log2(charset size)), like --bits 8N
+    #[arg(
+        long,
+        group = "mode",
+        value_name = "N",
+        conflicts_with_all = ["length", "length_from_stdin", "bits", "emoji", "digit_script", "no_edge_symbol", "include_space", "truncate"]
+    )]
+    bytes: Option<usize>,
+
+    /// How --bytes are rendered: drawn from the character set, or N raw random
+    /// bytes in a standard encoding (which ignores the character set flags)
+    #[arg(long, value_enum, default_value_t = ByteEncoding::Chars, requires = "bytes")]
+    encoding: ByteEncoding,
+
+    #[command(flatten)]
+    charset: CharsetArgs,
+
+    /// Read the allowed characters from stdin instead of using the charset flags
+    /// Line breaks are ignored and duplicates removed; only printable ASCII without spaces is accepted
+    #[arg(long, conflicts_with_all = ["length_from_stdin", "no_symbols", "no_numbers", "only_letters", "exclude", "exclude_file", "symbols", "digits_set", "no_ambiguous", "exclude_homoglyphs", "policy"])]
+    charset_from_stdin: bool,
+
+    /// Generate only from CHARS, ignoring the class flags; duplicates are removed
+    /// Any Unicode is allowed, though non-ASCII pools can't combine with the byte-based flags
+    #[arg(long = "charset", value_name = "CHARS", visible_alias = "generate-from-charset", allow_hyphen_values = true, group = "custom_pool", conflicts_with_all = ["charset_from_stdin", "no_symbols", "no_numbers", "only_letters", "exclude", "exclude_file", "symbols", "digits_set", "no_ambiguous", "policy", "emoji"])]
+    custom_charset: Option<String>,
+
+    /// Like --charset, but read the characters from a UTF-8 file, e.g. a team's
+    /// approved alphabet; line breaks are ignored
+    #[arg(long, value_name = "PATH", group = "custom_pool", conflicts_with_all = ["charset_from_stdin", "no_symbols", "no_numbers", "only_letters", "exclude", "exclude_file", "symbols", "digits_set", "no_ambiguous", "policy", "emoji"])]
+    charset_file: Option<PathBuf>,
+
+    /// Drop every whitespace character from --charset, not just the leading and
+    /// trailing ones that are always ignored (handy for pasted alphabets)
+    #[arg(long, requires = "custom_pool")]
+    strip_whitespace: bool,
+
+    /// Generate a memorable password like `Tiger7!Forest`
+    /// Two capitalized words joined by a random digit and symbol
+    #[arg(long, group = "mode", conflicts_with_all = ["length", "bits", "length_from_stdin", "no_symbols", "no_numbers", "only_letters", "exclude", "exclude_file", "symbols", "digits_set", "no_ambiguous", "exclude_homoglyphs", "charset_from_stdin", "custom_charset", "charset_file"])]
+    memorable: bool,
+
+    /// Generate a password satisfying a named policy preset
+    /// Overrides the character set flags and clamps --length into the policy's bounds
+    #[arg(long, group = "mode", value_enum, conflicts_with = "bits")]
+    policy: Option<PolicyPreset>,
+
+    #[command(flatten)]
+    output: OutputArgs,
+
+    /// Warn on stderr when the password's entropy falls below this many bits
+    #[arg(long, value_name = "BITS", default_value_t = 60.0)]
+    warn_below: f64,
+
+    /// Print the configuration's entropy in bits to stderr
+    /// Counts only the random part; --prefix and --suffix add nothing
+    #[arg(long)]
+    show_entropy: bool,
+
+    /// Print the number of distinct characters in the final charset to stderr,
+    /// after every inclusion and exclusion flag
+    #[arg(long, conflicts_with = "memorable")]
+    show_charset_size: bool,
+
+    /// Print the entropy each character adds, log2(charset size), to stderr
+    #[arg(long, conflicts_with_all = ["memorable", "sections", "bytes"])]
+    show_char_entropy: bool,
+
+    /// Print a plain-English summary of what will be generated to stderr, e.g. for
+    /// handing the settings to a colleague
+    #[arg(long)]
+    explain: bool,
+
+    /// Print the flags in effect, including those set through environment
+    /// variables, as a copy-pasteable command on stderr
+    #[arg(long)]
+    pub(super) show_command: bool,
+
+    /// Print a strength bar to stderr, scaled against 128 bits of entropy
+    /// Falls back to a plain percentage when stderr is not a terminal
+    #[arg(long)]
+    meter: bool,
+
+    /// Print an estimate of how long an offline attack would take to stderr
+    #[arg(long)]
+    crack_time: bool,
+
+    /// Print the probability that the batch of --count passwords contains a
+    /// duplicate to stderr, using the birthday approximation
+    #[arg(long)]
+    collision_estimate: bool,
+
+    /// Attacker speed assumed by --crack-time
+    #[arg(
+        long,
+        value_name = "RATE",
+        default_value_t = 1e10,
+        requires = "crack_time"
+    )]
+    guesses_per_second: f64,
+
+    /// Fixed text placed before the random part, e.g. `sk_live_`
+    /// --length sizes only the random part; the prefix adds no entropy
+    #[arg(long, value_name = "STRING", allow_hyphen_values = true)]
+    prefix: Option<String>,
+
+    /// Fixed text placed after the random part; it adds no entropy
+    #[arg(long, value_name = "STRING", allow_hyphen_values = true)]
+    suffix: Option<String>,
+
+    /// Refuse to generate when the configuration provides fewer than BITS of entropy
+    /// Unlike --bits, this never changes the length; it only checks it
+    #[arg(long, value_name = "BITS")]
+    min_entropy: Option<f64>,
+
+    /// With --min-entropy, lengthen the password as needed instead of failing
+    /// Starts from --length and never shortens it; gives up at --max-length (or 1024)
+    #[arg(long, requires = "min_entropy", conflicts_with_all = ["bits", "memorable", "policy", "sections", "bytes"])]
+    retry_on_weak: bool,
+
+    /// Regenerate if the output contains a blocklisted substring
+    /// Primarily meaningful for word-based modes like --memorable
+    #[arg(long)]
+    dictionary_check: bool,
+
+    /// Replace the embedded blocklist with one substring per line from PATH
+    #[arg(long, value_name = "PATH", requires = "dictionary_check")]
+    blocklist: Option<PathBuf>,
+
+    /// Regenerate if any character class appears more than N times in a row
+    /// Tight limits shrink the password space and may fail for long passwords
+    #[arg(long, value_name = "N")]
+    no_consecutive_class: Option<usize>,
+
+    /// Regenerate if any single character repeats more than N times in a row
+    #[arg(long, value_name = "N")]
+    repeat_max: Option<usize>,
+
+    /// Regenerate if any single character appears more than N times anywhere
+    #[arg(long, value_name = "N")]
+    max_char_occurrences: Option<usize>,
+
+    /// If --max-char-occurrences cannot fill --length, shorten the password to the
+    /// longest length it can fill and warn, instead of failing
+    #[arg(long, requires = "max_char_occurrences", conflicts_with_all = ["memorable", "policy", "emoji", "bits"])]
+    truncate: bool,
+
+    /// Regenerate unless the password contains at least N different characters
+    #[arg(long, value_name = "N", conflicts_with = "memorable")]
+    min_unique_chars: Option<usize>,
+
+    /// Regenerate if more than RATIO of neighboring character pairs are adjacent
+    /// keys on a US QWERTY keyboard (like `asdf`) [default RATIO: 0.25]
+    #[arg(long, value_name = "RATIO", num_args = 0..=1, default_missing_value = "0.25")]
+    avoid_adjacent: Option<f64>,
+
+    /// Regenerate if two look-alike characters (like `0O` or `l1`) sit next to
+    /// each other; milder than --no-ambiguous, which drops them entirely
+    #[arg(long)]
+    no_similar_adjacent: bool,
+
+    /// Regenerate until the password matches REGEX (brute force)
+    /// Patterns that few candidates match are slow or fail; unanchored patterns match anywhere
+    #[arg(long = "match", value_name = "REGEX")]
+    pattern: Option<String>,
+
+    /// Report on stderr how many candidates each --match took, e.g. to gauge
+    /// how rare a pattern is; --max-retries stays the ceiling
+    #[arg(long, requires = "pattern")]
+    count_until: bool,
+
+    /// Include at least one character from SET, placed at a random position
+    /// (modes with their own layout, like --balanced, regenerate instead);
+    /// repeat for several requirements [e.g. --require-from '@#$']
+    #[arg(long, value_name = "SET", conflicts_with = "memorable")]
+    require_from: Vec<String>,
+
+    /// How many candidates the constraint flags (--policy, --dictionary-check,
+    /// --no-consecutive-class, --repeat-max, --max-char-occurrences, --avoid-adjacent,
+    /// --no-similar-adjacent, --match, --require-from, --unique, --history) may draw before
+    /// giving up; the error names the flag that rejected the most
+    #[arg(long, visible_alias = "max-attempts", value_name = "N", default_value_t = DEFAULT_MAX_RETRIES)]
+    max_retries: usize,
+
+    /// Refuse to repeat any of the last few generated passwords
+    /// Only salted Argon2id hashes are stored, never the passwords themselves
+    #[arg(long)]
+    history: bool,
+
+    /// History file location [default: per-user data directory]
+    #[arg(long, value_name = "PATH", requires = "history")]
+    history_file: Option<PathBuf>,
+
+    /// How many recent password hashes the history keeps
+    #[arg(long, value_name = "N", default_value_t = 10)]
+    history_size: usize,
+
+    /// Append one JSON line per generated password to PATH: the time, the flags in
+    /// effect, the entropy and a salted Argon2id hash, never the password itself
+    /// The file is created with 0600 permissions
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["histogram", "dry_run"])]
+    pub(super) audit_log: Option<PathBuf>,
+
+    /// The flags in effect, as --show-command prints them, for the audit log.
+    #[arg(skip)]
+    pub(super) invocation: String,
+
+    /// EXPERIMENTAL: add 143 single-code-point emoji to the character set
+    /// Many systems reject emoji; --length counts characters, not bytes
+    #[arg(long, conflicts_with_all = ["memorable", "policy", "bits", "no_edge_symbol"])]
+    emoji: bool,
+
+    /// Keep the first and last characters alphanumeric; symbols may appear in between
+    #[arg(long, visible_alias = "no-leading-trailing-symbol", conflicts_with_all = ["memorable", "policy"])]
+    no_edge_symbol: bool,
+
+    /// Add the space character to the character set
+    /// The first and last characters are never spaces, since forms often trim them
+    #[arg(long, conflicts_with_all = ["memorable", "policy", "emoji", "balanced", "alternate_classes", "append_checksum", "bits"])]
+    include_space: bool,
+
+    /// Wi-Fi (WPA2/WPA3) passphrase: --length must be 8-63 and symbols are limited
+    /// to the router-safe `!#%*+-=?@_.,:^`; no spaces
+    #[arg(long, conflicts_with_all = ["memorable", "policy", "emoji", "include_space", "custom_charset", "charset_file", "charset_from_stdin", "append_checksum", "bits"])]
+    wifi: bool,
+
+    /// Work around a known system's limits: cap --length and drop the characters
+    /// it rejects; a --length or --symbols it can't accept is an error
+    #[arg(long, value_enum, value_name = "SYSTEM", conflicts_with_all = ["memorable", "policy", "sections", "bytes", "emoji", "digit_script", "include_space", "custom_charset", "charset_file", "charset_from_stdin", "wifi", "bits"])]
+    compat: Option<CompatSystem>,
+
+    /// EXPERIMENTAL: write digits in another numeral system instead of ASCII `0-9`
+    /// Many systems reject non-ASCII digits; entropy is unchanged since there are
+    /// still 10 of them
+    #[arg(long, value_enum, value_name = "SCRIPT", default_value_t = DigitScript::Ascii, conflicts_with_all = ["memorable", "policy", "emoji", "custom_charset", "charset_file", "charset_from_stdin", "balanced", "alternate_classes", "no_edge_symbol", "include_space", "append_checksum", "bits", "ascii_only", "digits_set"])]
+    digit_script: DigitScript,
+
+    /// Order of the character set before drawing; `shuffle` reorders it with `OsRng`
+    /// Every character is equally likely in either order, so this adds no security
+    #[arg(long, value_enum, value_name = "ORDER", default_value_t = AlphabetOrder::Fixed, conflicts_with_all = ["memorable", "policy"])]
+    alphabet_order: AlphabetOrder,
+
+    /// Fail instead of relaxing anything: a --policy that would clamp --length,
+    /// entropy below --warn-below and a deterministic --rng become errors
+    #[arg(long)]
+    strict: bool,
+
+    /// INSECURE, for test fixtures only: allow lengths down to 1 and replace the
+    /// weak-entropy warning with a caution
+    #[arg(long, conflicts_with_all = ["strict", "min_entropy"])]
+    weak_ok: bool,
+
+    /// Guarantee ASCII-only output; non-ASCII sources such as --emoji are rejected
+    #[arg(long, conflicts_with = "emoji")]
+    ascii_only: bool,
+
+    /// Print each password in Unicode NFC, the composed form most systems store;
+    /// a no-op for ASCII output
+    #[arg(long)]
+    normalize_unicode: bool,
+
+    /// Accept typographic punctuation such as `“` or `–` in --charset, which is
+    /// otherwise rejected as a likely paste from a document
+    #[arg(long, requires = "custom_pool", conflicts_with = "ascii_only")]
+    allow_smart_punctuation: bool,
+
+    /// Give every enabled class a near-equal share of the characters
+    /// Costs some entropy compared to uniform draws; --show-entropy reports the exact figure
+    #[arg(long, group = "mode", visible_alias = "require-class-balance", conflicts_with_all = ["emoji", "no_edge_symbol", "bits"])]
+    balanced: bool,
+
+    /// Never put two characters of the same class next to each other, e.g. `a7K!b2`
+    /// Each class is picked among the ones the previous character didn't use;
+    /// needs at least two classes
+    #[arg(long, group = "mode", conflicts_with_all = ["emoji", "no_edge_symbol", "bits"])]
+    alternate_classes: bool,
+
+    /// Build the password from independent sections, e.g. `8:alnum,4:digits,8:alnum`
+    /// Classes: upper, lower, alpha, digits, alnum, hex, symbols, all; --length is ignored
+    #[arg(
+        long,
+        group = "mode",
+        value_name = "SPEC",
+        conflicts_with_all = ["length", "length_from_stdin", "bits", "custom_charset", "charset_file", "charset_from_stdin", "emoji", "no_edge_symbol", "include_space", "wifi", "digit_script", "append_checksum", "alphabet_order", "no_symbols", "no_numbers", "only_letters", "symbols", "digits_set", "exclude", "exclude_file", "no_ambiguous", "exclude_homoglyphs"]
+    )]
+    sections: Option<Sections>,
+
+    /// Pin the class of some positions, e.g. `1-3=alpha,4=digit`; the rest use the whole charset
+    /// Classes: upper, lower, alpha, digit, alnum, symbol; positions count from 1
+    #[arg(
+        long,
+        group = "mode",
+        value_name = "RULES",
+        conflicts_with_all = ["bits", "length_from_stdin", "emoji", "digit_script", "no_edge_symbol", "include_space", "truncate"]
+    )]
+    position_rules: Option<PositionRules>,
+
+    /// Allow classes per position, one comma-separated entry each, e.g. `AL,D,*`
+    /// A upper, L lower, D digit, S symbol, combinable; * is anything; sets the length
+    #[arg(
+        long,
+        group = "mode",
+        value_name = "MASK",
+        conflicts_with_all = ["length", "length_from_stdin", "bits", "retry_on_weak", "emoji", "digit_script", "no_edge_symbol", "include_space", "truncate"]
+    )]
+    class_mask: Option<ClassMask>,
+
+    /// For numeric character sets such as `--charset 0123456789`: draw the first
+    /// digit from 1-9 so the value survives integer parsing (costs ~0.15 bits)
+    #[arg(
+        long,
+        group = "mode",
+        conflicts_with_all = ["emoji", "digit_script", "no_edge_symbol", "include_space", "truncate"]
+    )]
+    no_leading_zero: bool,
+
+    /// Join --sections with SEP
+    #[arg(long, value_name = "SEP", default_value = ".", requires = "sections")]
+    section_separator: String,
+
+    /// Append a Luhn mod N check character so typos can be caught with `check --verify-checksum`
+    /// Computed over the random part only; it adds one character and no entropy
+    #[arg(long, visible_alias = "checksum", conflicts_with_all = ["memorable", "policy", "emoji"])]
+    append_checksum: bool,
+
+    /// Print the generated password's class counts to stderr, e.g. `upper=4 lower=6 digit=3 symbol=3`
+    /// With --count, also a summary of the whole batch (hidden under --quiet)
+    #[arg(long)]
+    stats: bool,
+
+    /// Copy the password to the clipboard instead of printing it
+    /// With --count, all passwords are copied at once, one per line
+    #[arg(long)]
+    clipboard: bool,
+
+    /// Copy to the clipboard, then restore its previous contents after SECONDS
+    /// Newer contents copied in the meantime are left untouched
+    #[arg(long, value_name = "SECONDS")]
+    clipboard_timeout: Option<u64>,
+
+    /// Print N Shamir shares of the password instead of the password, any K of
+    /// which recover it with the `combine` command; store them separately
+    #[arg(
+        long,
+        value_name = "K-of-N",
+        conflicts_with_all = ["qr", "clipboard", "clipboard_timeout", "output_env", "template", "template_file", "output_dir", "table", "format", "pad_to", "mask_output", "no_newline"]
+    )]
+    split: Option<Scheme>,
+
+    /// Print `export NAME='password'` for `eval "$(...)"` instead of the bare password
+    #[arg(long, value_name = "NAME", conflicts_with_all = ["qr", "clipboard", "clipboard_timeout"])]
+    output_env: Option<String>,
+
+    /// Print each password through TEMPLATE, e.g. `{index}: {password} ({bits} bits)`
+    /// Placeholders: {password} {index} {length} {bits} {charset_size}; {{ and }} are literal braces
+    #[arg(long, value_name = "TEMPLATE", conflicts_with_all = ["qr", "clipboard", "clipboard_timeout", "output_env", "output_dir", "table", "format", "pad_to"])]
+    template: Option<Template>,
+
+    /// Like --template, but read a multi-line TEMPLATE from a file, e.g. a whole
+    /// `.env` block or YAML secret; one rendered copy is printed per password
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["template", "qr", "clipboard", "clipboard_timeout", "output_env", "output_dir", "table", "format", "pad_to"])]
+    template_file: Option<PathBuf>,
+
+    /// Accept a --template-file without a {password} placeholder
+    #[arg(long, requires = "template_file")]
+    allow_no_placeholder: bool,
+
+    /// Write each password to its own file in DIR, e.g. `password-001.txt`, with
+    /// 0600 permissions and no trailing newline; DIR is created if missing
+    #[arg(
+        long,
+        value_name = "DIR",
+        visible_alias = "split-output",
+        conflicts_with_all = ["qr", "clipboard", "clipboard_timeout", "output_env", "table", "format", "no_newline", "mask_output", "pad_to"]
+    )]
+    output_dir: Option<PathBuf>,
+
+    /// Let --output-dir overwrite existing password files
+    #[arg(long, requires = "output_dir")]
+    force: bool,
+
+    /// Before --force replaces a password file, overwrite its old contents with
+    /// random bytes and flush them to disk (best effort on SSDs and CoW filesystems)
+    #[arg(long, requires = "force")]
+    secure_erase: bool,
+
+    /// Output format for the generated passwords
+    #[arg(long, value_enum, default_value_t = OutputFormat::Plain, conflicts_with_all = ["qr", "clipboard", "clipboard_timeout", "output_env", "no_newline", "table"])]
+    format: OutputFormat,
+
+    /// Variable name for --format env; with --count, `_1`, `_2`, ... are appended
+    #[arg(long, value_name = "NAME")]
+    var_name: Option<String>,
+
+    /// Omit the header row of --format csv
+    #[arg(long)]
+    no_header: bool,
+
+    /// Print a JSON Schema of the --format csv row structure and exit
+    #[arg(long)]
+    json_schema: bool,
+
+    /// Print an aligned table of index, password, length and entropy (useful with --count)
+    #[arg(long, conflicts_with_all = ["qr", "clipboard", "clipboard_timeout", "output_env", "no_newline"])]
+    table: bool,
+
+    /// Don't print a newline after the (last) password
+    #[arg(short = 'n', long)]
+    no_newline: bool,
+
+    /// Pad shorter passwords on screen to WIDTH characters with a space and `·` markers
+    /// Cosmetic only: the padding is not part of the password
+    #[arg(long, value_name = "WIDTH", visible_alias = "obscure-length", conflicts_with_all = ["qr", "clipboard", "clipboard_timeout", "output_env", "format", "table"])]
+    pad_to: Option<usize>,
+
+    /// Never repeat a password within one --count batch
+    #[arg(long)]
+    unique: bool,
+
+    /// Random source: `os` (OS CSPRNG), `chacha` (ChaCha20 seeded once from the OS)
+    /// `seeded:<u64>` (reproducible, for testing only; never for real passwords) or
+    /// `indexed:<u64>` (like seeded, but each password is recoverable from the seed and its index)
+    #[arg(long, value_name = "SOURCE", default_value_t = RngSource::Os, conflicts_with = "memorable")]
+    rng: RngSource,
+
+    /// Validate the flags and print the plan (charset size, entropy, count, output)
+    /// to stderr without generating
+    /// Exits 0 when the configuration is valid and nonzero otherwise; the RNG is never used
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Developer diagnostic: print how often each charset character appears in
+    /// a large sample and flag any outside the expected band
+    /// Only for modes that draw every position uniformly from the charset
+    #[arg(long, hide = true, conflicts_with_all = ["mode", "encoding", "no_edge_symbol", "include_space", "max_char_occurrences", "dry_run"])]
+    histogram: bool,
+
+    /// Number of passwords to generate, one per line
+    #[arg(short, long, default_value_t = 1, env = "PWGEN_COUNT")]
+    count: usize,
+
+    /// Read the number of passwords from the first line of stdin; 0 generates none
+    #[arg(long, visible_alias = "count-stdin", conflicts_with_all = ["count", "length_from_stdin", "charset_from_stdin"])]
+    count_from_stdin: bool,
+
+    /// Generate the batch on N threads, each with its own RNG
+    /// Output order is still by index; --unique collisions are redrawn afterwards
+    #[arg(
+        short,
+        long,
+        value_name = "N",
+        default_value_t = 1,
+        conflicts_with = "history"
+    )]
+    jobs: usize,
+
+    /// Print the generation time and throughput to stderr once the batch is done
+    /// Covers only the generation loop, not argument parsing or output
+    #[arg(long, conflicts_with_all = ["histogram", "dry_run"])]
+    time: bool,
+
+    /// Record when the batch was generated, as an RFC 3339 UTC timestamp
+    /// Added as a generated_at column with --format csv, otherwise printed to stderr
+    #[arg(long, conflicts_with_all = ["histogram", "dry_run"])]
+    show_time: bool,
+
+    /// Show one sample password on stderr and ask before generating the batch
+    /// The sample is discarded; without a terminal the prompt is skipped
+    #[arg(long, conflicts_with_all = ["histogram", "dry_run"])]
+    preview: bool,
+
+    /// Reseed the --rng chacha stream from the OS every N passwords, so a leaked
+    /// RNG state only exposes the passwords drawn since the last reseed
+    /// No effect with --rng os, which already queries the OS for every draw,
+    /// or with --rng seeded:<u64>, which must stay reproducible
+    #[arg(long, value_name = "N")]
+    reseed_every: Option<usize>,
+
+    /// Also render the password as a QR code on stdout for scanning with a phone
+    /// Anyone who can see your screen can scan it too; mind shoulder-surfers.
+    /// Combine with --quiet to show only the QR code and omit the text
+    #[arg(long)]
+    qr: bool,
+
+    /// On a terminal, show each password as asterisks until Enter is pressed
+    /// Printed normally when stdout or stdin is not a terminal
+    #[arg(long, conflicts_with_all = ["qr", "table", "clipboard", "clipboard_timeout"])]
+    mask_output: bool,
+}
+
+/// Output formats selectable with `--format`.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    /// One password per line
+    Plain,
+    /// `index,password,length,entropy_bits` rows with a header, quoted as needed
+    Csv,
+    /// `NAME=password` lines for a `.env` file, named with --var-name
+    Env,
+}
+
+/// Renderings selectable with `--encoding`.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum ByteEncoding {
+    /// Characters from the character set, enough to carry the bytes' entropy
+    Chars,
+    /// Lowercase hexadecimal
+    Hex,
+    /// Standard base64 with padding
+    Base64,
+    /// URL-safe base64 without padding
+    Base64url,
+    /// RFC 4648 base32 without padding
+    Base32,
+}
+
+impl ByteEncoding {
+    /// The token encoding, or `None` for `chars`.
+    fn encoding(self) -> Option<Encoding> {
+        match self {
+            ByteEncoding::Chars => None,
+            ByteEncoding::Hex => Some(Encoding::Hex),
+            ByteEncoding::Base64 => Some(Encoding::Base64),
+            ByteEncoding::Base64url => Some(Encoding::Base64Url),
+            ByteEncoding::Base32 => Some(Encoding::Base32),
+        }
+    }
+}
+
+/// Charset orders selectable with `--alphabet-order`.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum AlphabetOrder {
+    /// The order the character set was built in
+    Fixed,
+    /// A fresh random order for every run
+    Shuffle,
+}
+
+/// Numeral systems selectable with `--digit-script`.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum DigitScript {
+    /// ASCII `0-9`
+    Ascii,
+    /// Arabic-Indic `٠-٩` (2 bytes each in UTF-8)
+    Arabic,
+    /// Devanagari `०-९` (3 bytes each in UTF-8)
+    Devanagari,
+}
+
+impl DigitScript {
+    /// The replacement for ASCII digit `d`, i.e. the digit of the same value.
+    fn map(self, d: u8) -> char {
+        let value = usize::from(d - b'0');
+        match self {
+            DigitScript::Ascii => d as char,
+            DigitScript::Arabic => ARABIC_INDIC_DIGITS[value],
+            DigitScript::Devanagari => DEVANAGARI_DIGITS[value],
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            DigitScript::Ascii => "ASCII",
+            DigitScript::Arabic => "Arabic-Indic",
+            DigitScript::Devanagari => "Devanagari",
+        }
+    }
+}
+
+/// Named policy presets selectable with `--policy`.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum PolicyPreset {
+    /// Active Directory complexity: 8-128 chars, 3 of 4 classes
+    WindowsAd,
+    /// PCI DSS: at least 12 chars with letters and digits
+    Pci,
+    /// NIST SP 800-63B: 15-64 chars, no composition rules
+    Nist,
+}
+
+/// Known systems selectable with `--compat`.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum CompatSystem {
+    /// Router Wi-Fi settings: at most 63 chars, no spaces, quotes or backslashes
+    Wpa2Router,
+    /// Oracle Database: at most 30 chars, symbols limited to `_$#`
+    Oracle,
+    /// Cisco IOS: no `?` (opens context help) and no spaces
+    CiscoIos,
+    /// Credentials inside a URL: none of `:/?#[]@%` or spaces
+    Url,
+}
+
+impl CompatSystem {
+    fn system(self) -> &'static System {
+        match self {
+            CompatSystem::Wpa2Router => &compat::WPA2_ROUTER,
+            CompatSystem::Oracle => &compat::ORACLE,
+            CompatSystem::CiscoIos => &compat::CISCO_IOS,
+            CompatSystem::Url => &compat::URL_USERINFO,
+        }
+    }
+}
+
+impl PolicyPreset {
+    fn policy(self) -> &'static Policy {
+        match self {
+            PolicyPreset::WindowsAd => &policy::WINDOWS_AD,
+            PolicyPreset::Pci => &policy::PCI,
+            PolicyPreset::Nist => &policy::NIST,
+        }
+    }
+}
+
+/// The entropy `--bits` asks for, or `--bytes` with `--encoding chars` implies.
+fn target_bits(args: &GenerateArgs) -> Option<f64> {
+    args.bits.or_else(|| {
+        args.bytes
+            .filter(|_| args.encoding == ByteEncoding::Chars)
+            .map(|bytes| (bytes * 8) as f64)
+    })
+}
+
+/// [`validate_length`], except that `--weak-ok` lets lengths below 8 through.
+fn check_length(length: usize, args: &GenerateArgs) -> Result<(), String> {
+    if args.weak_ok && length > 0 && length < 8 {
+        return Ok(());
+    }
+    validate_length(length, args.max_length)
+}
+
+/// Lists the class flags that were applied, for `--verbose` output.
+fn applied_flags(args: &CharsetArgs) -> String {
+    let flags: Vec<&str> = [
+        (args.no_symbols, "--no-symbols"),
+        (args.symbols.is_some(), "--symbols"),
+        (args.digits_set.is_some(), "--digits-set"),
+        (args.no_numbers, "--no-numbers"),
+        (args.only_letters, "--only-letters"),
+        (args.no_ambiguous, "--no-ambiguous"),
+        (args.exclude_homoglyphs, "--exclude-homoglyphs"),
+        (args.exclude.is_some(), "--exclude"),
+        (args.exclude_file.is_some(), "--exclude-file"),
+    ]
+    .into_iter()
+    .filter_map(|(set, flag)| set.then_some(flag))
+    .collect();
+
+    if flags.is_empty() {
+        "(none)".to_string()
+    } else {
+        flags.join(" ")
+    }
+}
+
+/// Fills in what some flags only point to, before [`validate`] sees them: the
+/// contents of `--charset-file` and `--template-file`, and the length a
+/// `--class-mask` implies.
+pub(super) fn resolve(args: &mut GenerateArgs) -> Result<(), String> {
+    load_charset_file(args)?;
+    load_template_file(args)?;
+    apply_class_mask(args);
+    Ok(())
+}
+
+pub(super) fn validate(args: &GenerateArgs) -> Result<(), String> {
+    if args.max_length < 8 {
+        return Err("--max-length must be at least 8.".to_string());
+    }
+    // Until --length-from-stdin is read, or when --bits sizes the password,
+    // the length is only the default; the bounds apply to the real one.
+    let length_given = (!args.length_from_stdin || args.length_read) && target_bits(args).is_none();
+    if length_given {
+        check_length(args.length, args)?;
+    }
+    if let Some(bytes) = args.bytes {
+        if bytes == 0 {
+            return Err("--bytes must be at least 1.".to_string());
+        }
+        if let Some(encoding) = args.encoding.encoding() {
+            let encoded = encoding.encode(&vec![0; bytes]).len();
+            check_length(encoded, args).map_err(|err| format!("--bytes {}: {}", bytes, err))?;
+            let unsupported = [
+                (args.append_checksum, "--append-checksum"),
+                (!args.require_from.is_empty(), "--require-from"),
+                (
+                    args.max_char_occurrences.is_some(),
+                    "--max-char-occurrences",
+                ),
+                (
+                    args.alphabet_order == AlphabetOrder::Shuffle,
+                    "--alphabet-order shuffle",
+                ),
+            ];
+            if let Some((_, flag)) = unsupported.iter().find(|(set, _)| *set) {
+                return Err(format!(
+                    "{} needs --encoding chars; other encodings don't use the character set.",
+                    flag
+                ));
+            }
+        }
+    }
+    if let Some(rules) = &args.position_rules
+        && rules.last_position() > args.length
+    {
+        return Err(format!(
+            "--position-rules constrains position {}, past the end of a {}-character password.",
+            rules.last_position(),
+            args.length
+        ));
+    }
+    if let Some(sections) = &args.sections {
+        check_length(sections.total_length(), args)
+            .map_err(|err| format!("--sections: {}", err))?;
+    }
+    if let Some(min_length) = args.min_length {
+        if min_length > args.max_length {
+            return Err(format!(
+                "--min-length {} is above --max-length {}.",
+                min_length, args.max_length
+            ));
+        }
+        if length_given {
+            check_min_length(args.length, min_length)?;
+        }
+    }
+    let custom = custom_pool(args);
+    let charset = match &custom {
+        Some(pool)
+            if pool.is_empty()
+                && args.charset.exclude_homoglyphs
+                && args
+                    .custom_charset
+                    .as_ref()
+                    .is_some_and(|s| !s.trim().is_empty()) =>
+        {
+            return Err(format!(
+                "--exclude-homoglyphs removes every character of {}.",
+                charset_flag(args)
+            ));
+        }
+        Some(pool) if pool.is_empty() => {
+            return Err(format!("{} must not be empty.", charset_flag(args)));
+        }
+        Some(pool) if pool.iter().any(|c| c.is_control()) => {
+            return Err(format!(
+                "{} must not contain control characters.",
+                charset_flag(args)
+            ));
+        }
+        Some(pool) if pool.iter().all(char::is_ascii) => pool.iter().map(|&c| c as u8).collect(),
+        Some(pool) => {
+            if args.ascii_only {
+                let (position, c) = args
+                    .custom_charset
+                    .iter()
+                    .flat_map(|chars| chars.trim().chars())
+                    .enumerate()
+                    .find(|(_, c)| !c.is_ascii())
+                    .expect("the pool has a non-ASCII character");
+                return Err(format!(
+                    "--ascii-only: {} contains the non-ASCII character {} at position {}.",
+                    charset_flag(args),
+                    describe_char(c),
+                    position + 1
+                ));
+            }
+            if let Some(&c) = pool
+                .iter()
+                .find(|&&c| !args.allow_smart_punctuation && ascii_lookalike(c).is_some())
+            {
+                // Typographic punctuation in a charset is almost always a
+                // paste from a word processor rather than a choice.
+                return Err(format!(
+                    "{} contains {}, which looks like a paste of {:?}; use the ASCII character, or pass --allow-smart-punctuation if it is intended.",
+                    charset_flag(args),
+                    describe_char(c),
+                    ascii_lookalike(c).expect("found above")
+                ));
+            }
+            let unsupported = [
+                (target_bits(args).is_some(), "--bits"),
+                (args.position_rules.is_some(), "--position-rules"),
+                (args.class_mask.is_some(), "--class-mask"),
+                (args.no_leading_zero, "--no-leading-zero"),
+                (args.no_edge_symbol, "--no-edge-symbol"),
+                (args.include_space, "--include-space"),
+                (args.balanced, "--balanced"),
+                (args.alternate_classes, "--alternate-classes"),
+                (args.append_checksum, "--append-checksum"),
+                (args.ascii_only, "--ascii-only"),
+            ];
+            if let Some((_, flag)) = unsupported.iter().find(|(set, _)| *set) {
+                return Err(format!(
+                    "{} requires an ASCII {}.",
+                    flag,
+                    charset_flag(args)
+                ));
+            }
+            Vec::new()
+        }
+        None => validate_charset(&args.charset)?,
+    };
+    // The number of distinct characters, including non-ASCII custom ones.
+    let pool_len = custom.as_ref().map_or(charset.len(), Vec::len);
+
+    if args.balanced && args.length < class_pools(&charset).len() {
+        return Err(format!(
+            "--balanced needs a length of at least {} to give every class a character.",
+            class_pools(&charset).len()
+        ));
+    }
+
+    if args.wifi && !WPA_LENGTH.contains(&args.length) {
+        return Err(format!(
+            "--wifi needs a --length between {} and {} characters, the range WPA accepts.",
+            WPA_LENGTH.start(),
+            WPA_LENGTH.end()
+        ));
+    }
+
+    if let Some(system) = args.compat.map(CompatSystem::system) {
+        if let Some(max_length) = system.max_length
+            && args.length > max_length
+        {
+            return Err(format!(
+                "--compat {} accepts at most {} characters; --length {} is longer.",
+                system.name, max_length, args.length
+            ));
+        }
+        if let Some(symbols) = &args.charset.symbols {
+            let rejected: String = symbols
+                .bytes()
+                .filter(|&b| !system.accepts(b))
+                .map(char::from)
+                .collect();
+            if !rejected.is_empty() {
+                return Err(format!(
+                    "--compat {} rejects {} from --symbols.",
+                    system.name,
+                    shell_quote(&rejected)
+                ));
+            }
+        }
+    }
+
+    if args.alternate_classes && class_pools(&charset).len() < 2 {
+        return Err(
+            "--alternate-classes needs at least two character classes to alternate between."
+                .to_string(),
+        );
+    }
+
+    if args.no_edge_symbol && !charset.iter().any(u8::is_ascii_alphanumeric) {
+        return Err("--no-edge-symbol needs letters or digits in the character set.".to_string());
+    }
+
+    if args.no_leading_zero
+        && !(charset.iter().all(u8::is_ascii_digit) && charset.iter().any(|&b| b != b'0'))
+    {
+        return Err(
+            "--no-leading-zero only applies to numeric character sets, such as --charset 0123456789."
+                .to_string(),
+        );
+    }
+
+    if let Some(bits) = target_bits(args) {
+        if !bits.is_finite() || bits <= 0.0 {
+            return Err("--bits must be a positive number.".to_string());
+        }
+        let limit = BITS_MAX_LENGTH.min(args.max_length);
+        match length_for_bits(bits, charset.len()) {
+            Some(needed) if needed <= limit => {}
+            _ => {
+                return Err(format!(
+                    "Reaching {} bits would need more than {} characters with this charset.",
+                    bits, limit
+                ));
+            }
+        }
+    }
+
+    for (flag, affix) in [("--prefix", &args.prefix), ("--suffix", &args.suffix)] {
+        if let Some(affix) = affix
+            && affix.chars().any(char::is_control)
+        {
+            return Err(format!("{} must not contain control characters.", flag));
+        }
+    }
+
+    if !args.guesses_per_second.is_finite() || args.guesses_per_second <= 0.0 {
+        return Err("--guesses-per-second must be a positive number.".to_string());
+    }
+
+    if args.strict {
+        if let Some(preset) = args.policy {
+            let policy = preset.policy();
+            if policy.clamp_length(args.length) != args.length {
+                return Err(format!(
+                    "--strict: --length {} is outside the {} policy's bounds ({}-{}).",
+                    args.length, policy.name, policy.min_length, policy.max_length
+                ));
+            }
+        }
+        if !args.rng.is_secure() {
+            return Err(format!(
+                "--strict: --rng {} is deterministic and unsuitable for real passwords.",
+                args.rng
+            ));
+        }
+    }
+
+    if args.ascii_only {
+        for (flag, affix) in [("--prefix", &args.prefix), ("--suffix", &args.suffix)] {
+            if affix.as_ref().is_some_and(|affix| !affix.is_ascii()) {
+                return Err(format!("{} must be ASCII with --ascii-only.", flag));
+            }
+        }
+    }
+
+    if let Some(bits) = args.min_entropy
+        && (!bits.is_finite() || bits < 0.0)
+    {
+        return Err("--min-entropy must be a non-negative number.".to_string());
+    }
+
+    if args.no_header && args.format != OutputFormat::Csv {
+        return Err("--no-header only applies to --format csv.".to_string());
+    }
+
+    if args.count == 0 {
+        return Err("--count must be at least 1.".to_string());
+    }
+    if args.count > MAX_COUNT {
+        return Err(format!("--count must be at most {}.", MAX_COUNT));
+    }
+    if args.qr && args.count > 1 {
+        return Err(
+            "--qr renders a single password; it cannot be combined with --count.".to_string(),
+        );
+    }
+    if args.split.is_some() && args.count > 1 {
+        return Err(
+            "--split shares a single password; it cannot be combined with --count.".to_string(),
+        );
+    }
+
+    if let Some(name) = &args.output_env {
+        if args.count > 1 {
+            return Err(
+                "--output-env prints a single assignment; it cannot be combined with --count."
+                    .to_string(),
+            );
+        }
+        if !is_variable_name(name) {
+            return Err(format!(
+                "--output-env: '{}' is not a valid variable name.",
+                name
+            ));
+        }
+    }
+    match (&args.var_name, args.format) {
+        (None, OutputFormat::Env) => {
+            return Err("--format env needs --var-name, e.g. --var-name DB_PASSWORD.".to_string());
+        }
+        (Some(name), OutputFormat::Env) if !is_variable_name(name) => {
+            return Err(format!(
+                "--var-name: '{}' is not a valid variable name.",
+                name
+            ));
+        }
+        (Some(_), OutputFormat::Env) | (None, _) => {}
+        (Some(_), _) => return Err("--var-name only applies to --format env.".to_string()),
+    }
+    if args.clipboard_timeout == Some(0) {
+        return Err("--clipboard-timeout must be at least 1 second.".to_string());
+    }
+
+    if args.no_consecutive_class == Some(0) {
+        return Err("--no-consecutive-class must be at least 1.".to_string());
+    }
+    if let Some(limit) = args.repeat_max {
+        if limit == 0 {
+            return Err("--repeat-max must be at least 1.".to_string());
+        }
+        // A single-character charset can only ever produce one long run.
+        if !args.memorable && args.policy.is_none() && pool_len == 1 && args.length > limit {
+            return Err(format!(
+                "--repeat-max {} cannot be met: the character set has only one character.",
+                limit
+            ));
+        }
+    }
+    if args.require_from.iter().any(String::is_empty) {
+        return Err("--require-from must not be empty.".to_string());
+    }
+    // Each requirement may need a position of its own.
+    if args.require_from.len() > args.length {
+        return Err(format!(
+            "--require-from is given {} times, more than --length {} can accommodate.",
+            args.require_from.len(),
+            args.length
+        ));
+    }
+    if let Some(minimum) = args.min_unique_chars {
+        if minimum == 0 {
+            return Err("--min-unique-chars must be at least 1.".to_string());
+        }
+        if minimum > args.length {
+            return Err(format!(
+                "--min-unique-chars {} cannot be met by --length {}.",
+                minimum, args.length
+            ));
+        }
+        let pool_len = distinct_pool_len(args);
+        if args.policy.is_none() && !args.emoji && minimum > pool_len {
+            return Err(format!(
+                "--min-unique-chars {} cannot be met: the character set has only {} different characters.",
+                minimum, pool_len
+            ));
+        }
+    }
+    if let Some(limit) = args.max_char_occurrences {
+        if limit == 0 {
+            return Err("--max-char-occurrences must be at least 1.".to_string());
+        }
+        // Each character may fill at most `limit` positions.
+        let pool_len = distinct_pool_len(args);
+        let capacity = pool_len.saturating_mul(limit);
+        if !args.memorable && args.policy.is_none() && !args.emoji && capacity < args.length {
+            if !args.truncate {
+                return Err(format!(
+                    "--max-char-occurrences {} cannot be met: {} characters can fill at most {} positions, fewer than --length {}. Pass --truncate to shorten the password instead.",
+                    limit, pool_len, capacity, args.length
+                ));
+            }
+            if capacity < 8 {
+                return Err(format!(
+                    "--max-char-occurrences {} cannot be met even with --truncate: {} characters can fill at most {} positions, below the 8-character minimum.",
+                    limit, pool_len, capacity
+                ));
+            }
+            if let Some(min_length) = args.min_length
+                && capacity < min_length
+            {
+                return Err(format!(
+                    "--max-char-occurrences {} cannot be met even with --truncate: {} characters can fill at most {} positions, below --min-length {}.",
+                    limit, pool_len, capacity, min_length
+                ));
+            }
+        }
+    }
+    if let Some(ratio) = args.avoid_adjacent
+        && !(0.0..=1.0).contains(&ratio)
+    {
+        return Err("--avoid-adjacent must be between 0 and 1.".to_string());
+    }
+    if let Some(pattern) = &args.pattern {
+        compile_pattern(pattern)?;
+    }
+    if args.jobs == 0 {
+        return Err("--jobs must be at least 1.".to_string());
+    }
+    if args.jobs > 1 && !args.rng.is_secure() && !args.rng.per_index() {
+        return Err(format!(
+            "--rng {} can't be reproduced across --jobs threads; use --jobs 1.",
+            args.rng
+        ));
+    }
+    if args.reseed_every == Some(0) {
+        return Err("--reseed-every must be at least 1.".to_string());
+    }
+    if args.max_retries == 0 {
+        return Err("--max-retries must be at least 1.".to_string());
+    }
+    if args.history && args.history_size == 0 {
+        return Err("--history-size must be at least 1.".to_string());
+    }
+
+    Ok(())
+}
+
+pub(super) fn run(mut args: GenerateArgs) -> Result<(), AppError> {
+    let log = Logger::new(&args.output);
+
+    if args.json_schema {
+        println!("{}", csv_schema());
+        return Ok(());
+    }
+
+    if args.length_from_stdin {
+        read_length(&mut args, io::stdin().lock())?;
+        log.verbose(format!("Length from stdin: {}", args.length));
+    }
+
+    if args.count_from_stdin {
+        args.count = read_number(io::stdin().lock(), "count")?;
+        log.verbose(format!("Count from stdin: {}", args.count));
+        if args.count == 0 {
+            return Ok(());
+        }
+        // --qr and --output-env only allow a single password.
+        validate(&args).map_err(AppError::Usage)?;
+    }
+
+    warn_about_whitespace(&args, log);
+    move_ascii_pool(&mut args);
+
+    if args.charset_from_stdin {
+        let charset = read_charset()?;
+        args.charset.custom = Some(charset);
+        // Everything that depends on the charset was checked against the
+        // default one; check again now that the real one is known.
+        validate(&args).map_err(AppError::Usage)?;
+    }
+
+    if let Some(bits) = target_bits(&args) {
+        let charset_len = charset_or_exit(&args.charset).len();
+        args.length = sized_length(&args, bits, charset_len);
+        log.info(format!(
+            "Length: {} characters ({:.1} bits of entropy)",
+            args.length,
+            entropy_bits(args.length, charset_len)
+        ));
+    }
+
+    if let Some(limit) = args.max_char_occurrences
+        && let Some(length) = truncated_length(&args)
+    {
+        log.warn(format!(
+            "--truncate: shortened the password from {} to {} characters, the most --max-char-occurrences {} allows.",
+            args.length, length, limit
+        ));
+        args.length = length;
+    }
+
+    if args.retry_on_weak
+        && let Some(required) = args.min_entropy
+    {
+        let mode = Mode::from_args(&args);
+        let mut limit = BITS_MAX_LENGTH.min(args.max_length);
+        if let Some(occurrences) = args.max_char_occurrences {
+            limit = limit.min(distinct_pool_len(&args).saturating_mul(occurrences));
+        }
+        if let Mode::Distinct { charset } = &mode {
+            limit = limit.min(charset.len());
+        }
+        // Out of reach: the --min-entropy check below reports it.
+        if let Some(length) = grow_length(args.length, limit, required, |length| {
+            mode.entropy_bits(length)
+        }) {
+            args.length = length;
+            log.info(format!(
+                "Length: {} characters ({:.1} bits of entropy)",
+                args.length,
+                mode.entropy_bits(args.length)
+            ));
+        }
+    }
+
+    let mut constraints = Constraints::from_args(&args)?;
+
+    let mut mode = Mode::from_args(&args);
+    if args.alphabet_order == AlphabetOrder::Shuffle {
+        mode.shuffle_charset();
+        log.verbose("Charset order: shuffled");
+        if !args.rng.is_secure() {
+            log.warn(format!(
+                "--alphabet-order shuffle uses OsRng, so --rng {} no longer reproduces the same passwords.",
+                args.rng
+            ));
+        }
+    }
+    if let Some(pool) = mode.pool() {
+        check_required_sets(&args.require_from, &pool)?;
+    }
+    mode.describe(&args, log);
+    if let Some(system) = args.compat.map(CompatSystem::system) {
+        log.verbose(format!(
+            "Compat: {} (drops {} characters)",
+            system.name,
+            system.forbidden.len()
+        ));
+    }
+    if mode.pool().is_some()
+        && let Ok(excluded) = excluded_chars(&args.charset)
+        && !excluded.is_empty()
+    {
+        let excluded: String = excluded.into_iter().collect();
+        log.verbose(format!("Excluded: {}", excluded));
+    }
+    // --bits already reported the length it chose, and the other modes
+    // don't use --length.
+    if target_bits(&args).is_none()
+        && !matches!(
+            mode,
+            Mode::Memorable | Mode::Policy(_) | Mode::Sections { .. } | Mode::Encoded { .. }
+        )
+    {
+        log.verbose(format!("Length: {}", args.length));
+    }
+    let bits = mode.entropy_bits(args.length);
+    if args.explain {
+        // Explicitly requested, so shown even under --quiet.
+        eprintln!("{}", mode.explain(&args, bits));
+    }
+    if args.show_charset_size
+        && let Some(pool) = mode.pool()
+    {
+        // Explicitly requested, so shown even under --quiet.
+        eprintln!("{}", pool.len());
+    }
+    if let Some(pool) = mode.pool() {
+        let per_char = entropy_per_char(pool.len());
+        if args.show_char_entropy {
+            // Explicitly requested, so shown even under --quiet.
+            eprintln!(
+                "Entropy per character: {:.2} bits ({} characters)",
+                per_char,
+                pool.len()
+            );
+        }
+        if per_char < WEAK_CHAR_BITS && !args.weak_ok {
+            log.warn(format!(
+                "Each character of this {}-character set adds only {:.1} bits of entropy ({:.1} with the default set), so it takes a longer password to be as strong.",
+                pool.len(),
+                per_char,
+                entropy_per_char(DEFAULT_CHARSET_LEN)
+            ));
+        }
+    }
+    if args.show_entropy {
+        // Explicitly requested, so shown even under --quiet.
+        eprintln!("Entropy: {:.1} bits", bits);
+    } else if matches!(mode, Mode::Memorable) {
+        log.info(format!("Entropy: {:.1} bits", bits));
+    }
+    if args.meter {
+        // Explicitly requested, so shown even under --quiet.
+        eprintln!("{}", render_meter(bits));
+    }
+    if args.crack_time {
+        let estimate = crack_time_estimate(bits, args.guesses_per_second);
+        // Explicitly requested, so shown even under --quiet.
+        eprintln!(
+            "Crack time: {} on average at {:e} guesses/second",
+            describe_crack_time(estimate),
+            args.guesses_per_second
+        );
+    }
+    if args.collision_estimate {
+        // Explicitly requested, so shown even under --quiet.
+        eprintln!(
+            "Collision probability: {} for {} passwords of {:.1} bits",
+            describe_probability(collision_probability(args.count, bits)),
+            args.count,
+            bits
+        );
+    }
+    if let Some(required) = args.min_entropy
+        && bits < required
+    {
+        return Err(AppError::Runtime(format!(
+            "This configuration provides only {:.1} bits of entropy; --min-entropy requires {} bits. {}",
+            bits,
+            required,
+            mode.suggestion(required)
+        )));
+    }
+    if args.strict && bits < args.warn_below {
+        return Err(AppError::Runtime(format!(
+            "--strict: this configuration provides only {:.1} bits of entropy, below --warn-below {} bits. {}",
+            bits,
+            args.warn_below,
+            mode.suggestion(args.warn_below)
+        )));
+    }
+    if args.weak_ok {
+        if args.length < 8 || bits < args.warn_below {
+            // A safety notice, so shown even under --quiet.
+            eprintln!(
+                "Caution: --weak-ok: this configuration is insecure ({:.1} bits of entropy); use the passwords only for testing.",
+                bits
+            );
+        }
+    } else {
+        warn_if_weak(log, bits, args.warn_below);
+    }
+
+    // Fewer than `count` possible passwords can't yield `count` distinct ones.
+    if args.unique && bits < (args.count as f64).log2() {
+        return Err(AppError::Runtime(format!(
+            "--unique: this configuration has only about {:.0} possible passwords, fewer than --count {}.",
+            bits.exp2(),
+            args.count
+        )));
+    }
+
+    if args.emoji {
+        log.warn(
+            "--emoji is experimental. Many systems reject or mangle emoji in passwords, and the entropy estimate assumes they are stored exactly as generated.",
+        );
+        // Every emoji in the pool is 4 bytes of UTF-8; ASCII characters are 1.
+        log.warn(format!(
+            "--length {} counts characters; the UTF-8 encoding may take up to {} bytes, which can overflow byte-based length limits.",
+            args.length,
+            args.length * 4
+        ));
+    }
+
+    if args.digit_script != DigitScript::Ascii {
+        log.warn(format!(
+            "{} digits are experimental. Many systems reject or normalize non-ASCII digits; the entropy estimate counts 10 digits regardless of script and assumes they are stored exactly as generated.",
+            args.digit_script.name()
+        ));
+    }
+
+    if !args.rng.is_secure() {
+        log.warn(format!(
+            "--rng {} is deterministic; anyone who knows the seed can reproduce these passwords. Use it for testing only.",
+            args.rng
+        ));
+    }
+    log.verbose(format!("RNG: {}", args.rng));
+    if args.reseed_every.is_some() && !args.rng.reseeds() {
+        log.warn(format!(
+            "--reseed-every has no effect with --rng {}; it only applies to --rng chacha.",
+            args.rng
+        ));
+    }
+
+    if args.dry_run {
+        // Explicitly requested, so shown even under --quiet. Returns before
+        // the RNG is built, so nothing is drawn or written.
+        eprintln!("Plan: {}", mode.explain(&args, bits));
+        if let Some(pool) = mode.pool() {
+            eprintln!("Charset size: {}", pool.len());
+        }
+        eprintln!("Entropy: {:.1} bits per password", bits);
+        eprintln!("Count: {}", args.count);
+        eprintln!("Output: {}", destination(&args));
+        eprintln!("RNG: {}", args.rng);
+        return Ok(());
+    }
+
+    let mut rng = build_rng(args.rng, None);
+
+    if args.histogram {
+        return print_histogram(&mode, &mut *rng);
+    }
+
+    let checksum_charset = args.append_checksum.then(|| charset_or_exit(&args.charset));
+    if let Some(charset) = &checksum_charset {
+        log.verbose(format!(
+            "Checksum charset: {}",
+            String::from_utf8_lossy(charset)
+        ));
+    }
+
+    if args.preview {
+        // A throwaway draw: it is never recorded, so the batch stays
+        // independent of what was shown.
+        let sample = constraints.generate(log, || mode.generate(&mut *rng, args.length))?;
+        // Explicitly requested, so shown even under --quiet.
+        eprintln!(
+            "Sample: {}{}{}",
+            args.prefix.as_deref().unwrap_or_default(),
+            sample,
+            args.suffix.as_deref().unwrap_or_default()
+        );
+        if !confirm(&format!(
+            "Generate {} passwords to {}?",
+            args.count,
+            destination(&args)
+        ))? {
+            return Err(AppError::Runtime(
+                "Cancelled at the --preview prompt; nothing was generated.".to_string(),
+            ));
+        }
+    }
+
+    // Only for people watching: never under --quiet or when stderr is redirected.
+    let progress =
+        (args.count >= PROGRESS_THRESHOLD && !args.output.quiet && io::stderr().is_terminal())
+            .then(|| ProgressBar::new(args.count as u64));
+
+    let started = Instant::now();
+    let mut drawn = if args.jobs > 1 {
+        draw_parallel(&args, &mode, &constraints, log, progress.as_ref())?
+    } else {
+        Vec::new()
+    }
+    .into_iter();
+
+    let mut passwords = Vec::with_capacity(args.count);
+    let mut summary = BatchSummary::default();
+    for index in 0..args.count {
+        if reseed_due(&args, index) {
+            rng = build_rng(args.rng, None);
+        }
+        if args.rng.per_index() {
+            rng = build_rng(args.rng, Some(index as u64));
+        }
+        let mut random = match drawn.next() {
+            // Drawn in parallel without knowing the rest of the batch, so
+            // only the --unique check is left to do.
+            Some(random) if constraints.is_new(&random) => random,
+            parallel => {
+                if parallel.is_none()
+                    && let Some(progress) = &progress
+                {
+                    progress.inc(1);
+                }
+                constraints.generate(log, || mode.generate(&mut *rng, args.length))?
+            }
+        };
+        constraints.record(&random, args.history_size)?;
+        if let Some(charset) = &checksum_charset {
+            random.push(check_char(&random, charset).expect("drawn from the same charset"));
+        }
+        let mut password = format!(
+            "{}{}{}",
+            args.prefix.as_deref().unwrap_or_default(),
+            random,
+            args.suffix.as_deref().unwrap_or_default()
+        );
+        if args.normalize_unicode {
+            password = normalize_nfc(&password);
+        }
+
+        // A last line of defense for scripts relying on --ascii-only.
+        if args.ascii_only && !password.is_ascii() {
+            return Err(AppError::Runtime(
+                "--ascii-only: generated a non-ASCII password; nothing was emitted.".to_string(),
+            ));
+        }
+
+        if args.stats {
+            // Explicitly requested, so shown even under --quiet.
+            eprintln!("{}", classify(&password));
+            summary.add(&password, bits);
+        }
+        passwords.push(password);
+    }
+    if let Some(progress) = progress {
+        progress.finish_and_clear();
+    }
+    if summary.count > 1 {
+        log.info(summary.to_string());
+    }
+    let elapsed = started.elapsed();
+    if args.time {
+        // Explicitly requested, so shown even under --quiet.
+        eprintln!(
+            "Generated {} passwords in {:.3}s ({:.0} passwords/s)",
+            passwords.len(),
+            elapsed.as_secs_f64(),
+            passwords.len() as f64 / elapsed.as_secs_f64()
+        );
+    } else {
+        log.verbose(format!("Generated in {:?}", elapsed));
+    }
+    let generated_at = args.show_time.then(|| rfc3339(SystemTime::now()));
+    if let Some(timestamp) = &generated_at
+        && args.format != OutputFormat::Csv
+    {
+        // Explicitly requested, so shown even under --quiet.
+        eprintln!("Generated at {}", timestamp);
+    }
+
+    let charset_size = mode.pool().map(|pool| pool.len());
+    emit(
+        &args,
+        log,
+        &passwords,
+        bits,
+        charset_size,
+        generated_at.as_deref(),
+    )?;
+
+    if let Some(path) = &args.audit_log {
+        let timestamp = generated_at.unwrap_or_else(|| rfc3339(SystemTime::now()));
+        write_audit_log(path, &args.invocation, &timestamp, &passwords, bits)?;
+        log.verbose(format!(
+            "Appended {} lines to {}",
+            passwords.len(),
+            path.display()
+        ));
+    }
+    Ok(())
+}
+
+/// The length `--bits` (or `--bytes`) asks for over `charset_len` characters.
+///
+/// Never below the minimum enforced for `--length`, nor below `--min-length`.
+fn sized_length(args: &GenerateArgs, bits: f64, charset_len: usize) -> usize {
+    length_for_bits(bits, charset_len)
+        .expect("validated above")
+        .max(if args.weak_ok { 1 } else { 8 })
+        .max(args.min_length.unwrap_or_default())
+}
+
+/// Asks `question` on stderr and reads a yes/no answer from stdin.
+///
+/// Without a terminal on both ends there is nobody to ask, so this answers
+/// yes instead of blocking a script.
+fn confirm(question: &str) -> Result<bool, String> {
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return Ok(true);
+    }
+    eprint!("{} [y/N] ", question);
+    let mut line = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .map_err(|err| format!("Failed to read the answer from stdin: {}", err))?;
+    Ok(matches!(
+        line.trim().to_ascii_lowercase().as_str(),
+        "y" | "yes"
+    ))
+}
+
+/// Rejects a `length` below `--min-length`.
+fn check_min_length(length: usize, min_length: usize) -> Result<(), String> {
+    if length < min_length {
+        return Err(format!(
+            "Password length {} is below --min-length {}.",
+            length, min_length
+        ));
+    }
+    Ok(())
+}
+
+/// The length `--truncate` shortens the password to when
+/// `--max-char-occurrences` can't fill `--length`, or `None` if it fits.
+fn truncated_length(args: &GenerateArgs) -> Option<usize> {
+    let limit = args.max_char_occurrences.filter(|_| args.truncate)?;
+    let capacity = distinct_pool_len(args).saturating_mul(limit);
+    (capacity < args.length).then_some(capacity)
+}
+
+/// Formats a probability for people: a percentage when it is large enough
+/// to read as one, scientific notation when it is tiny.
+fn describe_probability(p: f64) -> String {
+    if p == 0.0 {
+        "0".to_string()
+    } else if p >= 0.001 {
+        format!("{:.1}%", p * 100.0)
+    } else {
+        format!("{:.1e}", p)
+    }
+}
+
+/// The flag the custom pool came from, for messages about it.
+fn charset_flag(args: &GenerateArgs) -> &'static str {
+    if args.charset_file.is_some() {
+        "--charset-file"
+    } else {
+        "--charset"
+    }
+}
+
+/// The number of different characters in the charset, for the
+/// `--max-char-occurrences` capacity.
+fn distinct_pool_len(args: &GenerateArgs) -> usize {
+    let mut pool = custom_pool(args).unwrap_or_else(|| {
+        charset_or_exit(&args.charset)
+            .into_iter()
+            .map(char::from)
+            .collect()
+    });
+    pool.sort_unstable();
+    pool.dedup();
+    pool.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::collections::HashSet;
+    use std::fs;
+
+    use clap::error::ErrorKind;
+    use rand::RngCore;
+    use rand::rngs::OsRng;
+
+    use super::*;
+    use crate::cli::test_support::{parse, parse_with_env};
+
+    thread_local! {
+        /// How many generators [`build_rng`] has built on this thread.
+        pub(super) static RNG_BUILDS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    /// Parses `argv` as the default command and resolves it like `cli::run`.
+    fn args(argv: &[&str]) -> GenerateArgs {
+        let mut args = parse(argv).expect("the command line parses").generate;
+        resolve(&mut args).expect("the command line resolves");
+        args
+    }
+
+    /// Like [`args`], with the charset prepared the way `run` does before
+    /// choosing a [`Mode`].
+    fn prepared(argv: &[&str]) -> GenerateArgs {
+        let mut args = args(argv);
+        move_ascii_pool(&mut args);
+        args
+    }
+
+    /// The message `validate` rejects `argv` with.
+    fn rejection(argv: &[&str]) -> String {
+        validate(&args(argv)).expect_err("validate rejects the command line")
+    }
+
+    /// The usage error `read_length` rejects `input` with.
+    fn length_rejection(args: &mut GenerateArgs, input: &str) -> String {
+        match read_length(args, input.as_bytes()) {
+            Err(AppError::Usage(message)) => message,
+            other => panic!("expected a usage error, got {:?}", other),
+        }
+    }
+
+    fn assert_rejected(argv: &[&str], expected: &str) {
+        let message = rejection(argv);
+        assert!(
+            message.contains(expected),
+            "{:?} was rejected with {:?}, expected {:?}",
+            argv,
+            message,
+            expected
+        );
+    }
+
+    #[test]
+    fn accepts_the_defaults() {
+        assert_eq!(validate(&args(&[])), Ok(()));
+    }
+
+    #[test]
+    fn clap_rejects_mode_conflicts() {
+        for argv in [
+            &["--memorable", "--length", "20"][..],
+            &["--bytes", "16", "--length", "20"],
+            &["--sections", "8:alnum", "--policy", "nist"],
+            &["--class-mask", "A,L", "--position-rules", "1=digit"],
+        ] {
+            let err = parse(argv).expect_err("clap rejects the conflict");
+            assert_eq!(err.kind(), ErrorKind::ArgumentConflict, "{:?}", argv);
+        }
+    }
+
+    #[test]
+    fn clap_allows_one_mode_at_a_time() {
+        let modes: [&[&str]; 9] = [
+            &["--memorable"],
+            &["--policy", "nist"],
+            &["--sections", "8:alnum"],
+            &["--bytes", "16"],
+            &["--position-rules", "1=digit"],
+            &["--class-mask", "A,L"],
+            &["--balanced"],
+            &["--alternate-classes"],
+            &["--no-leading-zero"],
+        ];
+        for (i, first) in modes.iter().enumerate() {
+            assert!(parse(first).is_ok(), "{:?}", first);
+            for second in &modes[i + 1..] {
+                let argv = [*first, *second].concat();
+                let err = parse(&argv).expect_err("clap rejects two modes");
+                assert_eq!(err.kind(), ErrorKind::ArgumentConflict, "{:?}", argv);
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_small_max_length() {
+        assert_rejected(&["--max-length", "7"], "--max-length must be at least 8");
+    }
+
+    #[test]
+    fn rejects_lengths_out_of_bounds() {
+        assert_rejected(&["--length", "0"], "greater than zero");
+        assert_rejected(&["--length", "7"], "at least 8 characters");
+        assert_rejected(&["--length", "4097"], "at most 4096 characters");
+        assert_rejected(&["--length", "40", "--max-length", "32"], "at most 32");
+    }
+
+    #[test]
+    fn weak_ok_allows_short_but_not_empty_lengths() {
+        assert_eq!(validate(&args(&["--length", "4", "--weak-ok"])), Ok(()));
+        assert_rejected(&["--length", "0", "--weak-ok"], "greater than zero");
+    }
+
+    #[test]
+    fn rejects_zero_bytes() {
+        assert_rejected(&["--bytes", "0"], "--bytes must be at least 1");
+    }
+
+    #[test]
+    fn rejects_encoded_bytes_out_of_bounds() {
+        assert_rejected(&["--bytes", "2", "--encoding", "hex"], "--bytes 2:");
+    }
+
+    #[test]
+    fn rejects_charset_flags_with_raw_encodings() {
+        assert_rejected(
+            &["--bytes", "16", "--encoding", "hex", "--require-from", "ab"],
+            "--require-from needs --encoding chars",
+        );
+    }
+
+    #[test]
+    fn rejects_position_rules_past_the_end() {
+        assert_rejected(
+            &["--length", "8", "--position-rules", "9=digit"],
+            "--position-rules constrains position 9",
+        );
+    }
+
+    #[test]
+    fn rejects_sections_out_of_bounds() {
+        assert_rejected(&["--sections", "4:alnum"], "--sections:");
+    }
+
+    #[test]
+    fn rejects_min_length_above_max_length() {
+        assert_rejected(
+            &["--min-length", "40", "--max-length", "32"],
+            "--min-length 40 is above --max-length 32",
+        );
+    }
+
+    #[test]
+    fn rejects_length_below_min_length() {
+        assert_rejected(
+            &["--length", "12", "--min-length", "16"],
+            "Password length 12 is below --min-length 16",
+        );
+    }
+
+    #[test]
+    fn rejects_empty_custom_charset() {
+        assert_rejected(&["--charset", " "], "--charset must not be empty");
+    }
+
+    #[test]
+    fn rejects_custom_charset_emptied_by_homoglyphs() {
+        assert_rejected(
+            &["--charset", "0O1l", "--exclude-homoglyphs"],
+            "--exclude-homoglyphs removes every character of --charset",
+        );
+    }
+
+    #[test]
+    fn rejects_control_characters_in_charset() {
+        assert_rejected(
+            &["--charset", "ab\u{7}c"],
+            "--charset must not contain control characters",
+        );
+    }
+
+    #[test]
+    fn rejects_non_ascii_charset_with_ascii_only() {
+        assert_rejected(
+            &["--charset", "abcé", "--ascii-only"],
+            "--ascii-only: --charset contains the non-ASCII character",
+        );
+    }
+
+    #[test]
+    fn rejects_smart_punctuation_in_charset() {
+        assert_rejected(&["--charset", "abc\u{2019}"], "looks like a paste of");
+        assert_eq!(
+            validate(&args(&[
+                "--charset",
+                "abc\u{2019}",
+                "--allow-smart-punctuation"
+            ])),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn rejects_ascii_only_modes_with_non_ascii_charset() {
+        assert_rejected(
+            &["--charset", "abcé", "--balanced"],
+            "--balanced requires an ASCII --charset",
+        );
+    }
+
+    #[test]
+    fn rejects_empty_builtin_charset() {
+        assert_rejected(
+            &[
+                "--only-letters",
+                "--exclude",
+                "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ",
+            ],
+            "Character set is empty",
+        );
+    }
+
+    #[test]
+    fn rejects_balanced_shorter_than_the_classes() {
+        assert_rejected(
+            &["--balanced", "--length", "3", "--weak-ok"],
+            "--balanced needs a length of at least 4",
+        );
+    }
+
+    #[test]
+    fn rejects_wifi_outside_wpa_lengths() {
+        assert_rejected(
+            &["--wifi", "--length", "64"],
+            "--wifi needs a --length between 8 and 63",
+        );
+    }
+
+    #[test]
+    fn rejects_compat_length_above_the_system_maximum() {
+        assert_rejected(
+            &["--compat", "oracle", "--length", "31"],
+            "--compat oracle accepts at most 30 characters",
+        );
+    }
+
+    #[test]
+    fn rejects_compat_symbols_the_system_refuses() {
+        assert_rejected(
+            &["--compat", "oracle", "--symbols", "_$!"],
+            "--compat oracle rejects '!' from --symbols",
+        );
+    }
+
+    #[test]
+    fn rejects_alternate_classes_with_one_class() {
+        assert_rejected(
+            &["--alternate-classes", "--charset", "abcdef"],
+            "--alternate-classes needs at least two character classes",
+        );
+    }
+
+    #[test]
+    fn rejects_no_edge_symbol_without_alphanumerics() {
+        assert_rejected(
+            &["--no-edge-symbol", "--charset", "!@#$"],
+            "--no-edge-symbol needs letters or digits",
+        );
+    }
+
+    #[test]
+    fn rejects_no_leading_zero_for_non_numeric_charsets() {
+        assert_rejected(
+            &["--no-leading-zero"],
+            "--no-leading-zero only applies to numeric",
+        );
+        assert_rejected(
+            &["--no-leading-zero", "--charset", "0"],
+            "--no-leading-zero only applies to numeric",
+        );
+    }
+
+    #[test]
+    fn rejects_non_positive_bits() {
+        assert_rejected(&["--bits", "0"], "--bits must be a positive number");
+        assert_rejected(&["--bits", "NaN"], "--bits must be a positive number");
+    }
+
+    #[test]
+    fn rejects_bits_beyond_max_length() {
+        assert_rejected(&["--bits", "10000"], "would need more than 1024 characters");
+    }
+
+    #[test]
+    fn rejects_control_characters_in_affixes() {
+        assert_rejected(
+            &["--prefix", "a\tb"],
+            "--prefix must not contain control characters",
+        );
+        assert_rejected(
+            &["--suffix", "a\nb"],
+            "--suffix must not contain control characters",
+        );
+    }
+
+    #[test]
+    fn rejects_non_positive_guess_rate() {
+        assert_rejected(
+            &["--crack-time", "--guesses-per-second", "0"],
+            "--guesses-per-second must be a positive number",
+        );
+    }
+
+    #[test]
+    fn strict_rejects_length_outside_the_policy() {
+        assert_rejected(
+            &["--strict", "--policy", "pci", "--length", "10"],
+            "--strict: --length 10 is outside the pci policy's bounds (12-128)",
+        );
+    }
+
+    #[test]
+    fn strict_rejects_deterministic_rngs() {
+        assert_rejected(
+            &["--strict", "--rng", "seeded:1"],
+            "--strict: --rng seeded:1 is deterministic",
+        );
+    }
+
+    #[test]
+    fn rejects_non_ascii_affix_with_ascii_only() {
+        assert_rejected(
+            &["--ascii-only", "--suffix", "é"],
+            "--suffix must be ASCII with --ascii-only",
+        );
+    }
+
+    #[test]
+    fn rejects_negative_min_entropy() {
+        assert_rejected(
+            &["--min-entropy=-1"],
+            "--min-entropy must be a non-negative number",
+        );
+    }
+
+    #[test]
+    fn rejects_no_header_outside_csv() {
+        assert_rejected(&["--no-header"], "--no-header only applies to --format csv");
+    }
+
+    #[test]
+    fn rejects_zero_count() {
+        assert_rejected(&["--count", "0"], "--count must be at least 1");
+    }
+
+    #[test]
+    fn rejects_huge_count() {
+        assert_rejected(
+            &["--count", "18446744073709551615"],
+            "--count must be at most 10000000",
+        );
+        assert_eq!(validate(&args(&["--count", "10000000"])), Ok(()));
+    }
+
+    #[test]
+    fn rejects_single_password_outputs_with_count() {
+        assert_rejected(&["--qr", "--count", "2"], "--qr renders a single password");
+        assert_rejected(
+            &["--split", "2-of-3", "--count", "2"],
+            "--split shares a single password",
+        );
+        assert_rejected(
+            &["--output-env", "TOKEN", "--count", "2"],
+            "--output-env prints a single assignment",
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_variable_names() {
+        assert_rejected(
+            &["--output-env", "9LIVES"],
+            "--output-env: '9LIVES' is not a valid",
+        );
+        assert_rejected(
+            &["--format", "env", "--var-name", "DB-PASSWORD"],
+            "--var-name: 'DB-PASSWORD' is not a valid",
+        );
+    }
+
+    #[test]
+    fn rejects_env_format_without_var_name() {
+        assert_rejected(&["--format", "env"], "--format env needs --var-name");
+    }
+
+    #[test]
+    fn rejects_var_name_outside_env_format() {
+        assert_rejected(
+            &["--var-name", "DB_PASSWORD"],
+            "--var-name only applies to --format env",
+        );
+    }
+
+    #[test]
+    fn rejects_zero_clipboard_timeout() {
+        assert_rejected(
+            &["--clipboard-timeout", "0"],
+            "--clipboard-timeout must be at least 1 second",
+        );
+    }
+
+    #[test]
+    fn rejects_zero_no_consecutive_class() {
+        assert_rejected(
+            &["--no-consecutive-class", "0"],
+            "--no-consecutive-class must be at least 1",
+        );
+    }
+
+    #[test]
+    fn rejects_zero_repeat_max() {
+        assert_rejected(&["--repeat-max", "0"], "--repeat-max must be at least 1");
+    }
+
+    #[test]
+    fn rejects_repeat_max_with_a_single_character() {
+        assert_rejected(
+            &["--repeat-max", "2", "--charset", "a"],
+            "--repeat-max 2 cannot be met",
+        );
+    }
+
+    #[test]
+    fn rejects_empty_require_from() {
+        assert_rejected(&["--require-from", ""], "--require-from must not be empty");
+    }
+
+    #[test]
+    fn rejects_more_require_from_than_positions() {
+        let mut argv = vec!["--length", "8"];
+        argv.extend(["--require-from", "ab"].repeat(9));
+        assert_rejected(&argv, "--require-from is given 9 times");
+    }
+
+    #[test]
+    fn rejects_zero_min_unique_chars() {
+        assert_rejected(
+            &["--min-unique-chars", "0"],
+            "--min-unique-chars must be at least 1",
+        );
+    }
+
+    #[test]
+    fn rejects_min_unique_chars_above_the_length() {
+        assert_rejected(
+            &["--min-unique-chars", "17"],
+            "--min-unique-chars 17 cannot be met by --length 16",
+        );
+    }
+
+    #[test]
+    fn rejects_min_unique_chars_above_the_charset() {
+        assert_rejected(
+            &[
+                "--min-unique-chars",
+                "12",
+                "--charset",
+                "0123456789",
+                "--length",
+                "16",
+            ],
+            "the character set has only 10 different characters",
+        );
+    }
+
+    #[test]
+    fn rejects_zero_max_char_occurrences() {
+        assert_rejected(
+            &["--max-char-occurrences", "0"],
+            "--max-char-occurrences must be at least 1",
+        );
+    }
+
+    #[test]
+    fn rejects_max_char_occurrences_over_capacity() {
+        assert_rejected(
+            &[
+                "--max-char-occurrences",
+                "1",
+                "--charset",
+                "0123456789",
+                "--length",
+                "12",
+            ],
+            "Pass --truncate to shorten the password instead",
+        );
+    }
+
+    #[test]
+    fn rejects_truncation_below_the_minimum_length() {
+        assert_rejected(
+            &[
+                "--max-char-occurrences",
+                "1",
+                "--charset",
+                "abcd",
+                "--truncate",
+            ],
+            "cannot be met even with --truncate",
+        );
+    }
+
+    #[test]
+    fn rejects_avoid_adjacent_outside_zero_to_one() {
+        assert_rejected(
+            &["--avoid-adjacent", "1.5"],
+            "--avoid-adjacent must be between 0 and 1",
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_match_pattern() {
+        assert!(rejection(&["--match", "("]).contains("--match"));
+    }
+
+    #[test]
+    fn rejects_zero_jobs() {
+        assert_rejected(&["--jobs", "0"], "--jobs must be at least 1");
+    }
+
+    #[test]
+    fn rejects_seeded_rng_across_jobs() {
+        assert_rejected(
+            &["--jobs", "2", "--rng", "seeded:1"],
+            "can't be reproduced across --jobs threads",
+        );
+    }
+
+    /// Runs `argv` with `--output-dir`, returning the passwords it wrote.
+    fn written(argv: &[&str]) -> Vec<String> {
+        let dir = tempfile::tempdir().unwrap();
+        let output_dir = dir.path().join("out");
+        let argv = [argv, &["--output-dir", output_dir.to_str().unwrap()]].concat();
+        run(args(&argv)).expect("the batch generates");
+        fs::read_dir(&output_dir)
+            .unwrap()
+            .map(|entry| fs::read_to_string(entry.unwrap().path()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn jobs_write_the_whole_count() {
+        let passwords = written(&["--jobs", "4", "--count", "200"]);
+        assert_eq!(passwords.len(), 200);
+        assert!(passwords.iter().all(|password| !password.is_empty()));
+    }
+
+    /// Draws the batch of `argv` with [`draw_parallel`].
+    fn drawn_in_parallel(argv: &[&str]) -> Vec<String> {
+        let args = prepared(argv);
+        let constraints = Constraints::from_args(&args).unwrap();
+        let log = Logger::new(&args.output);
+        draw_parallel(&args, &Mode::from_args(&args), &constraints, log, None).unwrap()
+    }
+
+    #[test]
+    fn parallel_draws_meet_the_constraints_on_every_thread() {
+        let passwords = drawn_in_parallel(&[
+            "--jobs",
+            "4",
+            "--count",
+            "300",
+            "--match",
+            "^[0-9]",
+            "--require-from",
+            "xyz",
+            "--max-retries",
+            "1000",
+        ]);
+        assert_eq!(passwords.len(), 300);
+        for password in &passwords {
+            assert_eq!(password.chars().count(), 16, "{}", password);
+            assert!(
+                password.starts_with(|c: char| c.is_ascii_digit()),
+                "{}",
+                password
+            );
+            assert!(password.contains(['x', 'y', 'z']), "{}", password);
+        }
+    }
+
+    #[test]
+    fn parallel_draws_with_and_without_unique() {
+        for unique in [false, true] {
+            let mut argv = vec!["--jobs", "4", "--count", "300"];
+            if unique {
+                argv.push("--unique");
+            }
+            let passwords = drawn_in_parallel(&argv);
+            assert_eq!(passwords.len(), 300);
+            let distinct: HashSet<&String> = passwords.iter().collect();
+            assert_eq!(distinct.len(), 300, "--unique {}", unique);
+        }
+    }
+
+    #[test]
+    fn unique_holds_across_threads() {
+        // 10,000 possible passwords, so the threads collide and `run` redraws.
+        let passwords = written(&[
+            "--jobs",
+            "4",
+            "--count",
+            "2000",
+            "--unique",
+            "--charset",
+            "0123456789",
+            "--length",
+            "4",
+            "--weak-ok",
+        ]);
+        assert_eq!(passwords.len(), 2000);
+        let distinct: HashSet<&String> = passwords.iter().collect();
+        assert_eq!(distinct.len(), 2000);
+    }
+
+    #[test]
+    fn rejects_zero_reseed_every() {
+        assert_rejected(
+            &["--reseed-every", "0"],
+            "--reseed-every must be at least 1",
+        );
+    }
+
+    #[test]
+    fn rejects_zero_max_retries() {
+        assert_rejected(&["--max-retries", "0"], "--max-retries must be at least 1");
+    }
+
+    #[test]
+    fn rejects_zero_history_size() {
+        assert_rejected(
+            &["--history", "--history-size", "0"],
+            "--history-size must be at least 1",
+        );
+    }
+
+    #[test]
+    fn edge_entropy_covers_lengths_below_two() {
+        for argv in [
+            &["--weak-ok", "--length", "1", "--no-edge-symbol"][..],
+            &["--weak-ok", "--length", "1", "--include-space"],
+        ] {
+            let args = args(argv);
+            assert_eq!(validate(&args), Ok(()));
+            let mode = Mode::from_args(&args);
+            let Mode::Edges { edges, .. } = &mode else {
+                panic!("{:?} selects the edges mode", argv);
+            };
+            assert_eq!(mode.entropy_bits(1), entropy_bits(1, edges.len()));
+        }
+    }
+
+    #[test]
+    fn histogram_rejects_sections() {
+        let err = parse(&["--histogram", "--sections", "8:alnum"]).expect_err("clap rejects it");
+        assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn histogram_rejects_bytes() {
+        for argv in [
+            &["--histogram", "--bytes", "16"][..],
+            &["--histogram", "--bytes", "16", "--encoding", "hex"],
+        ] {
+            let err = parse(argv).expect_err("clap rejects it");
+            assert_eq!(err.kind(), ErrorKind::ArgumentConflict, "{:?}", argv);
+        }
+    }
+
+    #[test]
+    fn histogram_rejects_non_uniform_modes() {
+        for argv in [
+            &["--histogram", "--include-space"][..],
+            &["--histogram", "--balanced"],
+            &["--histogram", "--alternate-classes"],
+            &["--histogram", "--position-rules", "1=digit"],
+            &["--histogram", "--class-mask", "A,L,D,S,*,*,*,*"],
+            &[
+                "--histogram",
+                "--no-leading-zero",
+                "--charset",
+                "0123456789",
+            ],
+        ] {
+            let err = parse(argv).expect_err("clap rejects it");
+            assert_eq!(err.kind(), ErrorKind::ArgumentConflict, "{:?}", argv);
+        }
+    }
+
+    #[test]
+    fn histogram_fails_without_a_pool() {
+        let mode = Mode::from_args(&args(&["--sections", "8:alnum"]));
+        assert!(mode.pool().is_none());
+        assert!(matches!(
+            print_histogram(&mode, &mut OsRng),
+            Err(AppError::Usage(_))
+        ));
+    }
+
+    #[test]
+    fn histogram_rejects_max_char_occurrences() {
+        let err =
+            parse(&["--histogram", "--max-char-occurrences", "1"]).expect_err("clap rejects it");
+        assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn distinct_fails_when_the_charset_is_too_small() {
+        let args = prepared(&[
+            "--charset",
+            "abcdefghij",
+            "--length",
+            "10",
+            "--max-char-occurrences",
+            "1",
+        ]);
+        let mode = Mode::from_args(&args);
+        assert!(matches!(mode, Mode::Distinct { .. }));
+        let message = mode
+            .generate(&mut OsRng, 11)
+            .expect_err("11 positions can't be filled");
+        assert!(
+            message.contains("--max-char-occurrences 1 cannot be met"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn distinct_entropy_counts_permutations() {
+        let mode = Mode::from_args(&prepared(&[
+            "--charset",
+            "abcdefghij",
+            "--max-char-occurrences",
+            "1",
+            "--truncate",
+        ]));
+        // log2 of the number of ordered picks: 10!/6! and 10!.
+        for (length, permutations) in [(4, 5040.0f64), (10, 3_628_800.0)] {
+            assert!((mode.entropy_bits(length) - permutations.log2()).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn truncate_is_required_when_the_charset_is_too_small() {
+        assert_rejected(
+            &[
+                "--charset",
+                "abcdefghij",
+                "--length",
+                "16",
+                "--max-char-occurrences",
+                "1",
+            ],
+            "Pass --truncate to shorten the password instead",
+        );
+    }
+
+    #[test]
+    fn truncate_shortens_to_the_charset_size() {
+        let mut args = prepared(&[
+            "--charset",
+            "abcdefghij",
+            "--length",
+            "16",
+            "--max-char-occurrences",
+            "1",
+            "--truncate",
+        ]);
+        assert_eq!(validate(&args), Ok(()));
+        assert_eq!(truncated_length(&args), Some(10));
+        args.length = 10;
+
+        let mode = Mode::from_args(&args);
+        let mut password: Vec<char> = mode
+            .generate(&mut OsRng, args.length)
+            .unwrap()
+            .chars()
+            .collect();
+        password.sort_unstable();
+        assert_eq!(password.into_iter().collect::<String>(), "abcdefghij");
+    }
+
+    #[test]
+    fn truncate_leaves_lengths_that_fit() {
+        let args = args(&[
+            "--charset",
+            "abcdefghij",
+            "--length",
+            "8",
+            "--max-char-occurrences",
+            "1",
+            "--truncate",
+        ]);
+        assert_eq!(truncated_length(&args), None);
+    }
+
+    #[test]
+    fn length_from_stdin_is_validated() {
+        let mut limited = args(&["--length-from-stdin", "--max-char-occurrences", "1"]);
+        let message = length_rejection(&mut limited, "200\n");
+        assert!(
+            message.contains("--max-char-occurrences 1 cannot be met"),
+            "{}",
+            message
+        );
+
+        let mut unique = args(&["--length-from-stdin", "--min-unique-chars", "20"]);
+        let message = length_rejection(&mut unique, "12\n");
+        assert!(
+            message.contains("cannot be met by --length 12"),
+            "{}",
+            message
+        );
+
+        let mut plain = args(&["--length-from-stdin"]);
+        assert!(read_length(&mut plain, &b" 24 \n"[..]).is_ok());
+        assert_eq!(plain.length, 24);
+    }
+
+    #[test]
+    fn length_from_stdin_must_be_a_number() {
+        let mut args = args(&["--length-from-stdin"]);
+        for input in [&b"twelve\n"[..], b""] {
+            assert!(matches!(
+                read_length(&mut args, input),
+                Err(AppError::Runtime(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn wifi_bounds_apply_to_length_from_stdin() {
+        let mut args = args(&["--wifi", "--length-from-stdin"]);
+        let message = length_rejection(&mut args, "64\n");
+        assert!(
+            message.contains("--wifi needs a --length between 8 and 63"),
+            "{}",
+            message
+        );
+        assert!(read_length(&mut args, &b"63\n"[..]).is_ok());
+    }
+
+    #[test]
+    fn compat_caps_apply_to_length_from_stdin() {
+        let mut args = args(&["--compat", "oracle", "--length-from-stdin"]);
+        let message = length_rejection(&mut args, "31\n");
+        assert!(
+            message.contains("--compat oracle accepts at most 30 characters"),
+            "{}",
+            message
+        );
+        assert!(read_length(&mut args, &b"30\n"[..]).is_ok());
+    }
+
+    /// An RNG stuck at zero, so every draw picks the first character.
+    struct Zeros;
+
+    impl RngCore for Zeros {
+        fn next_u32(&mut self) -> u32 {
+            0
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            0
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            dest.fill(0);
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+            dest.fill(0);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn histogram_of_a_stuck_rng_fails_the_check() {
+        let mode = Mode::from_args(&args(&["--histogram"]));
+        assert!(matches!(
+            print_histogram(&mode, &mut Zeros),
+            Err(AppError::CheckFailed)
+        ));
+    }
+
+    #[test]
+    fn unmet_min_entropy_is_a_runtime_error() {
+        let args = args(&["--min-entropy", "500"]);
+        assert_eq!(validate(&args), Ok(()));
+        match run(args) {
+            Err(AppError::Runtime(message)) => assert!(message.contains("--min-entropy requires")),
+            other => panic!("expected a runtime error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn dry_run_draws_and_writes_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let output_dir = dir.path().join("secrets");
+        let history_file = dir.path().join("history");
+        let args = args(&[
+            "--dry-run",
+            "--count",
+            "3",
+            "--output-dir",
+            output_dir.to_str().unwrap(),
+            "--history",
+            "--history-file",
+            history_file.to_str().unwrap(),
+        ]);
+
+        let before = RNG_BUILDS.with(Cell::get);
+        assert!(run(args).is_ok());
+        assert_eq!(RNG_BUILDS.with(Cell::get), before);
+        assert!(!output_dir.exists());
+        assert!(!history_file.exists());
+    }
+
+    #[test]
+    fn generating_builds_the_rng() {
+        let dir = tempfile::tempdir().unwrap();
+        let output_dir = dir.path().join("secrets");
+        let args = args(&["--count", "3", "--output-dir", output_dir.to_str().unwrap()]);
+
+        let before = RNG_BUILDS.with(Cell::get);
+        assert!(run(args).is_ok());
+        assert!(RNG_BUILDS.with(Cell::get) > before);
+        assert_eq!(fs::read_dir(&output_dir).unwrap().count(), 3);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn output_dir_writes_private_files() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let output_dir = dir.path().join("secrets");
+        let args = args(&["--count", "3", "--output-dir", output_dir.to_str().unwrap()]);
+        assert!(run(args).is_ok());
+
+        let mut names: Vec<String> = fs::read_dir(&output_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        assert_eq!(
+            names,
+            ["password-001.txt", "password-002.txt", "password-003.txt"]
+        );
+        for name in &names {
+            let path = output_dir.join(name);
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600, "{}", name);
+            assert_eq!(fs::read_to_string(&path).unwrap().chars().count(), 16);
+        }
+    }
+
+    #[test]
+    fn split_output_is_output_dir() {
+        let expected = destination(&args(&["--output-dir", "secrets"]));
+        for flag in ["--output-dir", "--split-output"] {
+            let parsed = args(&[flag, "secrets", "--force"]);
+            assert_eq!(parsed.output_dir, Some(PathBuf::from("secrets")));
+            assert_eq!(destination(&parsed), expected);
+
+            let err = parse(&[flag, "secrets", "--qr"]).expect_err("clap rejects it");
+            assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let output_dir = dir.path().join("secrets");
+        let args = args(&[
+            "--count",
+            "2",
+            "--split-output",
+            output_dir.to_str().unwrap(),
+        ]);
+        assert!(run(args).is_ok());
+        assert_eq!(fs::read_dir(&output_dir).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn count_until_reports_the_attempts_drawn() {
+        let args = args(&["--match", "^[0-9]", "--count-until", "--rng", "seeded:7"]);
+        let constraints = Constraints::from_args(&args).unwrap();
+        assert!(constraints.report_attempts);
+        let mode = Mode::from_args(&args);
+        let log = Logger::new(&args.output);
+        let draw = || {
+            let mut rng = args.rng.build();
+            constraints
+                .draw(log, || mode.generate(&mut *rng, args.length))
+                .unwrap()
+        };
+
+        // Counted independently from the same seed.
+        let mut rng = args.rng.build();
+        let expected = (1..)
+            .find_map(|attempt| {
+                let candidate = mode.generate(&mut *rng, args.length).unwrap();
+                candidate
+                    .starts_with(|c: char| c.is_ascii_digit())
+                    .then_some((candidate, attempt))
+            })
+            .unwrap();
+
+        // Most candidates start with a letter or symbol, so this seed takes a few.
+        assert!(expected.1 > 1);
+        assert_eq!(draw(), expected);
+        assert_eq!(draw(), expected);
+    }
+
+    fn args_with_env(env: &[(&str, &str)], argv: &[&str]) -> GenerateArgs {
+        parse_with_env(env, argv)
+            .expect("the command line parses")
+            .generate
+    }
+
+    #[test]
+    fn env_supplies_length_and_count() {
+        let args = args_with_env(&[("PWGEN_LENGTH", "24"), ("PWGEN_COUNT", "3")], &[]);
+        assert_eq!((args.length, args.count), (24, 3));
+    }
+
+    #[test]
+    fn flags_override_env() {
+        let args = args_with_env(
+            &[
+                ("PWGEN_LENGTH", "24"),
+                ("PWGEN_COUNT", "3"),
+                ("PWGEN_EXCLUDE", "abc"),
+            ],
+            &["--length", "12", "-c", "2", "--exclude", "xyz"],
+        );
+        assert_eq!((args.length, args.count), (12, 2));
+        assert_eq!(args.charset.exclude.as_deref(), Some("xyz"));
+    }
+
+    #[test]
+    fn env_switches_accept_falsey_values() {
+        let args = args_with_env(
+            &[
+                ("PWGEN_NO_SYMBOLS", "1"),
+                ("PWGEN_NO_NUMBERS", "true"),
+                ("PWGEN_NO_AMBIGUOUS", "yes"),
+                ("PWGEN_EXCLUDE", "abc"),
+            ],
+            &[],
+        );
+        assert!(args.charset.no_symbols && args.charset.no_numbers && args.charset.no_ambiguous);
+        assert_eq!(args.charset.exclude.as_deref(), Some("abc"));
+
+        let args = args_with_env(
+            &[("PWGEN_ONLY_LETTERS", "0"), ("PWGEN_NO_SYMBOLS", "false")],
+            &[],
+        );
+        assert!(!args.charset.only_letters && !args.charset.no_symbols);
+
+        // There is no --no-only-letters, but a flag still wins over a false one.
+        let args = args_with_env(&[("PWGEN_ONLY_LETTERS", "off")], &["--only-letters"]);
+        assert!(args.charset.only_letters);
+    }
+
+    #[test]
+    fn env_values_are_validated_like_flags() {
+        let args = args_with_env(&[("PWGEN_LENGTH", "4")], &[]);
+        assert!(
+            validate(&args)
+                .unwrap_err()
+                .contains("at least 8 characters")
+        );
+        assert!(parse_with_env(&[("PWGEN_COUNT", "many")], &[]).is_err());
+    }
+
+    #[test]
+    fn unset_env_leaves_the_defaults() {
+        let args = args_with_env(&[], &[]);
+        assert_eq!((args.length, args.count), (16, 1));
+        assert!(!args.charset.no_symbols && args.charset.exclude.is_none());
+    }
+
+    #[test]
+    fn env_supplies_the_symbol_set() {
+        let args = args_with_env(&[("PWGEN_SYMBOLS", "!@")], &[]);
+        assert_eq!(args.charset.symbols.as_deref(), Some("!@"));
+        let charset = validate_charset(&args.charset).unwrap();
+        assert!(
+            charset
+                .iter()
+                .filter(|b| b.is_ascii_punctuation())
+                .all(|b| b"!@".contains(b))
+        );
+
+        let args = args_with_env(&[("PWGEN_SYMBOLS", "!@")], &["--symbols", "#$"]);
+        assert_eq!(args.charset.symbols.as_deref(), Some("#$"));
+    }
+
+    #[test]
+    fn env_symbol_set_is_validated_like_the_flag() {
+        let args = args_with_env(&[("PWGEN_SYMBOLS", "ab")], &[]);
+        assert!(validate(&args).unwrap_err().contains("--symbols"));
+    }
+
+    #[test]
+    fn length_within_min_and_max_length() {
+        for length in ["12", "14", "16"] {
+            let args = args(&[
+                "--length",
+                length,
+                "--min-length",
+                "12",
+                "--max-length",
+                "16",
+            ]);
+            assert_eq!(validate(&args), Ok(()), "{}", length);
+        }
+        assert_rejected(
+            &["--length", "17", "--min-length", "12", "--max-length", "16"],
+            "at most 16 characters",
+        );
+    }
+
+    #[test]
+    fn bounds_wait_for_length_from_stdin() {
+        let mut args = args(&[
+            "--length-from-stdin",
+            "--min-length",
+            "20",
+            "--max-length",
+            "30",
+        ]);
+        assert_eq!(validate(&args), Ok(()));
+        assert!(read_length(&mut args, &b"24\n"[..]).is_ok());
+        assert!(length_rejection(&mut args, "19\n").contains("below --min-length 20"));
+        assert!(length_rejection(&mut args, "31\n").contains("at most 30 characters"));
+    }
+
+    #[test]
+    fn bits_respect_min_length() {
+        let args = args(&["--bits", "30", "--min-length", "20"]);
+        assert_eq!(validate(&args), Ok(()));
+        assert_eq!(sized_length(&args, 30.0, 94), 20);
+        assert_eq!(sized_length(&args, 200.0, 94), 31);
+        assert_rejected(
+            &["--bits", "300", "--max-length", "20"],
+            "more than 20 characters",
+        );
+    }
+
+    #[test]
+    fn truncate_respects_min_length() {
+        assert_rejected(
+            &[
+                "--charset",
+                "abcdefghij",
+                "--max-char-occurrences",
+                "1",
+                "--truncate",
+                "--min-length",
+                "12",
+            ],
+            "below --min-length 12",
+        );
+    }
+
+    fn pool(argv: &[&str]) -> String {
+        custom_pool(&args(argv))
+            .expect("a custom charset was given")
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn charset_drops_pasted_surrounding_whitespace() {
+        assert_eq!(pool(&["--charset", "abc\n"]), "abc");
+        assert_eq!(pool(&["--charset", "\t abc \r\n"]), "abc");
+        assert_eq!(validate(&args(&["--charset", " 0123456789\n"])), Ok(()));
+    }
+
+    #[test]
+    fn charset_keeps_inner_whitespace_unless_stripped() {
+        assert_eq!(pool(&["--charset", " a b\tc \n"]), "a b\tc");
+        assert_eq!(
+            pool(&["--charset", " a b\tc \n", "--strip-whitespace"]),
+            "abc"
+        );
+    }
+
+    #[test]
+    fn charset_file_drops_the_trailing_newline() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("charset.txt");
+        fs::write(&path, "0123456789abcdef\n").unwrap();
+        assert_eq!(
+            pool(&["--charset-file", path.to_str().unwrap()]),
+            "0123456789abcdef"
+        );
+    }
+
+    #[test]
+    fn exclude_drops_pasted_surrounding_whitespace() {
+        let args = args(&["--exclude", " xyz\n"]);
+        assert_eq!(excluded_chars(&args.charset), Ok(vec!['x', 'y', 'z']));
+        let charset = validate_charset(&args.charset).unwrap();
+        assert!(!charset.contains(&b' ') && !charset.contains(&b'x'));
+    }
+
+    /// `--policy` redraws whole candidates rather than planting its required
+    /// classes somewhere, so no class should favour any position.
+    #[test]
+    fn policy_classes_are_spread_evenly_over_positions() {
+        use password_generator_rust::charset::{CharClass, class_bytes, class_of};
+
+        const DRAWS: usize = 3000;
+        let args = prepared(&["--policy", "pci", "--length", "12", "--rng", "seeded:7"]);
+        let mode = Mode::from_args(&args);
+        let constraints = Constraints::from_args(&args).unwrap();
+        let log = Logger::new(&args.output);
+        let mut rng = build_rng(args.rng, None);
+
+        let mut counts = [[0usize; 12]; 4];
+        for _ in 0..DRAWS {
+            let password = constraints
+                .generate(log, || mode.generate(&mut *rng, args.length))
+                .unwrap();
+            for (position, byte) in password.bytes().enumerate() {
+                let class = class_of(byte).expect("policies draw from the built-in classes");
+                counts[class as usize][position] += 1;
+            }
+        }
+
+        let charset_size = policy::PCI.charset().len() as f64;
+        for class in CharClass::ALL {
+            let shares = counts[class as usize].map(|count| count as f64 / DRAWS as f64);
+            let mean = shares.iter().sum::<f64>() / shares.len() as f64;
+            // Redrawing candidates without a digit nudges the digit share up a little.
+            let expected = class_bytes(class).len() as f64 / charset_size;
+            assert!(
+                (mean - expected).abs() < 0.05,
+                "{:?}: {} vs {}",
+                class,
+                mean,
+                expected
+            );
+            for (position, share) in shares.iter().enumerate() {
+                assert!(
+                    (share - mean).abs() < 0.03,
+                    "{:?} at position {}: {} vs {}",
+                    class,
+                    position,
+                    share,
+                    mean
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn require_from_places_small_sets_at_minimum_length() {
+        let sets = ["a", "B", "3", "#", "xy", "Z9", "!?", "qQ"];
+        let argv: Vec<&str> = sets
+            .iter()
+            .flat_map(|set| ["--require-from", set])
+            .collect();
+        let args = prepared(&[argv.as_slice(), &["--length", "8"]].concat());
+        let mode = Mode::from_args(&args);
+        assert!(matches!(mode, Mode::Required { .. }));
+        let constraints = Constraints::from_args(&args).unwrap();
+        let log = Logger::new(&args.output);
+
+        let mut starts = HashSet::new();
+        for _ in 0..500 {
+            let password = constraints
+                .generate(log, || mode.generate(&mut OsRng, args.length))
+                .expect("placing the sets never needs a retry");
+            assert_eq!(password.chars().count(), 8);
+            for set in sets {
+                assert!(
+                    password.contains(|c| set.contains(c)),
+                    "{} lacks {}",
+                    password,
+                    set
+                );
+            }
+            starts.extend(password.chars().next());
+        }
+        assert!(
+            starts.contains(&'a') && starts.contains(&'#'),
+            "{:?}",
+            starts
+        );
+    }
+}
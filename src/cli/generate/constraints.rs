@@ -0,0 +1,324 @@
+//! `--policy`, `--match`, `--blocklist` and the other checks a drawn
+//! password must pass, redrawing until it does.
+
+use std::collections::HashSet;
+
+use password_generator_rust::blocklist::Blocklist;
+use password_generator_rust::constraints::{
+    distinct_chars, has_similar_adjacent, keyboard_adjacency_ratio, max_char_count, max_class_run,
+    max_run, try_generate_with_constraints,
+};
+use password_generator_rust::history::History;
+use password_generator_rust::policy::Policy;
+use regex::Regex;
+
+use super::{GenerateArgs, PolicyPreset};
+use crate::cli::{Logger, shell_quote};
+
+/// Checks that every `--require-from` set can be met from `pool`.
+///
+/// A required character outside the pool could never be drawn, so it is an
+/// error rather than a silent extension of the character set.
+pub(super) fn check_required_sets(sets: &[String], pool: &[char]) -> Result<(), String> {
+    for set in sets {
+        let missing: String = set.chars().filter(|c| !pool.contains(c)).collect();
+        if !missing.is_empty() {
+            return Err(format!(
+                "--require-from {} contains characters outside the character set: {}",
+                shell_quote(set),
+                missing
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// The constraints every generated candidate must pass.
+pub(super) struct Constraints {
+    /// Rejects candidates missing the policy's class requirements (`--policy`).
+    policy: Option<&'static Policy>,
+    /// Rejects candidates containing a blocklisted substring (`--dictionary-check`).
+    blocklist: Option<Blocklist>,
+    /// Rejects candidates matching a recently generated password (`--history`).
+    history: Option<History>,
+    /// Rejects candidates with longer class runs (`--no-consecutive-class`).
+    max_class_run: Option<usize>,
+    /// Rejects candidates repeating one character more often in a row (`--repeat-max`).
+    max_run: Option<usize>,
+    /// Rejects candidates using one character more often (`--max-char-occurrences`).
+    max_char_count: Option<usize>,
+    /// Rejects candidates with fewer different characters (`--min-unique-chars`).
+    min_distinct: Option<usize>,
+    /// Rejects candidates with more keyboard-adjacent pairs (`--avoid-adjacent`).
+    max_adjacency: Option<f64>,
+    /// Rejects candidates with confusable neighbors (`--no-similar-adjacent`).
+    no_similar_adjacent: bool,
+    /// Rejects candidates that don't match the pattern (`--match`).
+    pattern: Option<Regex>,
+    /// Rejects candidates missing a character from any of these sets (`--require-from`).
+    required: Vec<Vec<char>>,
+    /// Rejects candidates already generated in this batch (`--unique`).
+    seen: Option<HashSet<String>>,
+    /// How many candidates may be drawn before giving up (`--max-retries`).
+    max_retries: usize,
+    /// Prints how many candidates each match took (`--count-until`).
+    pub(super) report_attempts: bool,
+}
+
+impl Constraints {
+    pub(super) fn from_args(args: &GenerateArgs) -> Result<Self, String> {
+        Ok(Self {
+            policy: args.policy.map(PolicyPreset::policy),
+            blocklist: load_blocklist(args)?,
+            history: load_history(args)?,
+            max_class_run: args.no_consecutive_class,
+            max_run: args.repeat_max,
+            max_char_count: args.max_char_occurrences,
+            min_distinct: args.min_unique_chars,
+            max_adjacency: args.avoid_adjacent,
+            no_similar_adjacent: args.no_similar_adjacent,
+            pattern: args.pattern.as_deref().map(compile_pattern).transpose()?,
+            required: args
+                .require_from
+                .iter()
+                .map(|set| set.chars().collect())
+                .collect(),
+            seen: args.unique.then(HashSet::new),
+            max_retries: args.max_retries,
+            report_attempts: args.count_until,
+        })
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.policy.is_none()
+            && self.blocklist.is_none()
+            && self.history.is_none()
+            && self.max_class_run.is_none()
+            && self.max_run.is_none()
+            && self.max_char_count.is_none()
+            && self.min_distinct.is_none()
+            && self.max_adjacency.is_none()
+            && !self.no_similar_adjacent
+            && self.pattern.is_none()
+            && self.required.is_empty()
+            && self.seen.is_none()
+    }
+
+    /// The flag of the first active constraint `candidate` fails, or `None`
+    /// if it passes them all.
+    fn rejected_by(&self, candidate: &str) -> Option<&'static str> {
+        if self
+            .policy
+            .is_some_and(|policy| !policy.is_satisfied_by(candidate))
+        {
+            return Some("--policy");
+        }
+        if self
+            .blocklist
+            .as_ref()
+            .is_some_and(|blocklist| blocklist.find_in(candidate).is_some())
+        {
+            return Some("--dictionary-check");
+        }
+        if self
+            .max_class_run
+            .is_some_and(|limit| max_class_run(candidate) > limit)
+        {
+            return Some("--no-consecutive-class");
+        }
+        if self.max_run.is_some_and(|limit| max_run(candidate) > limit) {
+            return Some("--repeat-max");
+        }
+        if self
+            .max_char_count
+            .is_some_and(|limit| max_char_count(candidate) > limit)
+        {
+            return Some("--max-char-occurrences");
+        }
+        if self
+            .min_distinct
+            .is_some_and(|minimum| distinct_chars(candidate) < minimum)
+        {
+            return Some("--min-unique-chars");
+        }
+        if self
+            .max_adjacency
+            .is_some_and(|limit| keyboard_adjacency_ratio(candidate) > limit)
+        {
+            return Some("--avoid-adjacent");
+        }
+        if self.no_similar_adjacent && has_similar_adjacent(candidate) {
+            return Some("--no-similar-adjacent");
+        }
+        if self
+            .pattern
+            .as_ref()
+            .is_some_and(|pattern| !pattern.is_match(candidate))
+        {
+            return Some("--match");
+        }
+        if self
+            .required
+            .iter()
+            .any(|set| !candidate.chars().any(|c| set.contains(&c)))
+        {
+            return Some("--require-from");
+        }
+        if !self.is_new(candidate) {
+            return Some("--unique");
+        }
+        // Checked last: hashing against the history is by far the slowest test.
+        if self
+            .history
+            .as_ref()
+            .is_some_and(|history| history.contains(candidate))
+        {
+            return Some("--history");
+        }
+        None
+    }
+
+    /// Draws candidates from `generate` until one passes every active constraint.
+    ///
+    /// Failing candidates are discarded, so the result stays uniform over all
+    /// passwords that satisfy the constraints. Without any constraint the first
+    /// candidate is returned as is.
+    pub(super) fn generate(
+        &self,
+        log: Logger,
+        generate: impl FnMut() -> Result<String, String>,
+    ) -> Result<String, String> {
+        let (password, attempts) = self.draw(log, generate)?;
+        if self.report_attempts {
+            // Explicitly requested, so shown even under --quiet.
+            eprintln!("Matched after {} attempt(s)", attempts);
+        }
+        Ok(password)
+    }
+
+    /// Like [`generate`](Self::generate), also returning how many candidates
+    /// were drawn.
+    pub(super) fn draw(
+        &self,
+        log: Logger,
+        mut generate: impl FnMut() -> Result<String, String>,
+    ) -> Result<(String, usize), String> {
+        if self.is_empty() {
+            return Ok((generate()?, 1));
+        }
+
+        // How many candidates each flag rejected, so a failure can name the
+        // constraint that is actually in the way.
+        let mut rejections: Vec<(&str, usize)> = Vec::new();
+        let (password, attempts) =
+            try_generate_with_constraints(self.max_retries, generate, |candidate| {
+                let Some(flag) = self.rejected_by(candidate) else {
+                    return true;
+                };
+                match rejections.iter_mut().find(|(seen, _)| *seen == flag) {
+                    Some((_, count)) => *count += 1,
+                    None => rejections.push((flag, 1)),
+                }
+                false
+            })?
+            .ok_or_else(|| {
+                let (flag, count) = rejections
+                    .iter()
+                    .max_by_key(|(_, count)| *count)
+                    .copied()
+                    .expect("every attempt was rejected");
+                let mut message = format!(
+                    "No candidate satisfied the constraints after {} attempts; {} rejected {} of them. Relax it or raise --max-retries.",
+                    self.max_retries, flag, count
+                );
+                if let Some(pattern) = &self.pattern {
+                    message.push_str(&format!(
+                        " Note that --match '{}' is checked by brute force; a pattern that random passwords rarely match may never succeed.",
+                        pattern
+                    ));
+                }
+                message
+            })?;
+        if rejections.is_empty() {
+            log.verbose(format!(
+                "Constraints satisfied after {} attempt(s)",
+                attempts
+            ));
+        } else {
+            let rejections: Vec<String> = rejections
+                .iter()
+                .map(|(flag, count)| format!("{} rejected {}", flag, count))
+                .collect();
+            log.verbose(format!(
+                "Constraints satisfied after {} attempt(s); {}",
+                attempts,
+                rejections.join(", ")
+            ));
+        }
+        Ok((password, attempts))
+    }
+
+    /// Returns `true` unless `--unique` has already seen `candidate`.
+    pub(super) fn is_new(&self, candidate: &str) -> bool {
+        self.seen
+            .as_ref()
+            .is_none_or(|seen| !seen.contains(candidate))
+    }
+
+    /// Remembers the accepted password for `--unique` and records it in the
+    /// history file if `--history` is on.
+    pub(super) fn record(&mut self, password: &str, capacity: usize) -> Result<(), String> {
+        if let Some(seen) = &mut self.seen {
+            seen.insert(password.to_string());
+        }
+
+        let Some(history) = &mut self.history else {
+            return Ok(());
+        };
+        history
+            .record(password, capacity)
+            .map_err(|err| format!("Failed to hash password for history: {}", err))?;
+        history
+            .save()
+            .map_err(|err| format!("Failed to write history file: {}", err))
+    }
+}
+
+/// Compiles the `--match` pattern with the `regex` crate's default flags.
+pub(super) fn compile_pattern(pattern: &str) -> Result<Regex, String> {
+    Regex::new(pattern).map_err(|err| format!("Invalid --match pattern: {}", err))
+}
+
+/// Loads the blocklist for `--dictionary-check`, or `None` when the check is off.
+fn load_blocklist(args: &GenerateArgs) -> Result<Option<Blocklist>, String> {
+    if !args.dictionary_check {
+        return Ok(None);
+    }
+
+    let blocklist = match &args.blocklist {
+        Some(path) => Blocklist::from_file(path)
+            .map_err(|err| format!("Failed to read blocklist {}: {}", path.display(), err))?,
+        None => Blocklist::embedded(),
+    };
+    if blocklist.is_empty() {
+        return Err("Blocklist contains no entries.".to_string());
+    }
+    Ok(Some(blocklist))
+}
+
+/// Loads the password history for `--history`, or `None` when it is off.
+fn load_history(args: &GenerateArgs) -> Result<Option<History>, String> {
+    if !args.history {
+        return Ok(None);
+    }
+
+    let path = match &args.history_file {
+        Some(path) => path.clone(),
+        None => History::default_path().ok_or(
+            "Could not determine a history file location; pass --history-file.".to_string(),
+        )?,
+    };
+    History::load(&path)
+        .map(Some)
+        .map_err(|err| format!("Failed to read history file {}: {}", path.display(), err))
+}
@@ -0,0 +1,218 @@
+//! Reading flag values from files and stdin, and preparing a custom
+//! `--charset` pool.
+
+use std::fs;
+use std::io::{self, BufRead, Read};
+
+use password_generator_rust::charset::{HOMOGLYPHS_CROSS_SCRIPT, HOMOGLYPHS_LATIN};
+use password_generator_rust::template::Template;
+
+use super::{GenerateArgs, charset_flag, validate};
+use crate::cli::{AppError, Logger};
+
+/// Reads the `--length-from-stdin` length from `input` into `args`.
+///
+/// Everything that depends on the length, from the bounds to `--wifi` and
+/// `--max-char-occurrences`, was checked against the default one; it is
+/// checked again now that the real one is known.
+pub(super) fn read_length(args: &mut GenerateArgs, input: impl BufRead) -> Result<(), AppError> {
+    args.length = read_number(input, "length")?;
+    args.length_read = true;
+    validate(args).map_err(AppError::Usage)
+}
+
+/// Moves an ASCII `--charset` or `--charset-file` into `args.charset`, so it
+/// takes the fast byte path like any other charset.
+pub(super) fn move_ascii_pool(args: &mut GenerateArgs) {
+    if let Some(pool) = custom_pool(args)
+        && pool.iter().all(char::is_ascii)
+    {
+        args.charset.custom = Some(pool.into_iter().map(|c| c as u8).collect());
+        args.custom_charset = None;
+    }
+}
+
+/// Reads a non-negative whole number from the first line of `input`, which
+/// is stdin outside of tests; `what` names it in error messages.
+pub(super) fn read_number(mut input: impl BufRead, what: &str) -> Result<usize, String> {
+    let mut line = String::new();
+    let read = input
+        .read_line(&mut line)
+        .map_err(|err| format!("Failed to read {} from stdin: {}", what, err))?;
+    if read == 0 {
+        return Err(format!(
+            "Expected a {} on stdin but reached end of input.",
+            what
+        ));
+    }
+
+    let line = line.trim();
+    line.parse().map_err(|_| {
+        format!(
+            "Invalid {} on stdin: '{}' is not a whole number.",
+            what, line
+        )
+    })
+}
+
+/// The deduplicated `--charset` pool, if one was given.
+///
+/// Leading and trailing whitespace is dropped, as it usually comes from
+/// pasting; with `--strip-whitespace` whitespace inside is dropped too, and
+/// with `--exclude-homoglyphs` so are Latin homoglyphs and their Cyrillic and
+/// Greek twins.
+pub(super) fn custom_pool(args: &GenerateArgs) -> Option<Vec<char>> {
+    let chars = args.custom_charset.as_ref()?.trim();
+    let mut pool = Vec::new();
+    for c in chars.chars() {
+        if args.strip_whitespace && c.is_whitespace() {
+            continue;
+        }
+        if args.charset.exclude_homoglyphs && is_homoglyph(c) {
+            continue;
+        }
+        if !pool.contains(&c) {
+            pool.push(c);
+        }
+    }
+    Some(pool)
+}
+
+/// The ASCII character a word processor commonly replaces with `c`, such as
+/// `"` for `“`, or `None` if `c` isn't such a replacement.
+pub(super) fn ascii_lookalike(c: char) -> Option<char> {
+    match c {
+        '‘' | '’' | '‚' | '′' => Some('\''),
+        '“' | '”' | '„' | '″' => Some('"'),
+        '‐' | '‑' | '‒' | '–' | '—' | '−' => Some('-'),
+        '…' => Some('.'),
+        '\u{a0}' | '\u{202f}' => Some(' '),
+        _ => None,
+    }
+}
+
+/// Whether `--exclude-homoglyphs` drops `c` from a pool.
+fn is_homoglyph(c: char) -> bool {
+    if c.is_ascii() {
+        HOMOGLYPHS_LATIN.contains(&(c as u8))
+    } else {
+        HOMOGLYPHS_CROSS_SCRIPT.contains(&c)
+    }
+}
+
+/// Warns about whitespace in `--charset`: what was dropped, and what is
+/// kept inside the pool without `--strip-whitespace`.
+pub(super) fn warn_about_whitespace(args: &GenerateArgs, log: Logger) {
+    let Some(chars) = &args.custom_charset else {
+        return;
+    };
+    let flag = charset_flag(args);
+    let trimmed = chars.trim();
+    let surrounding = chars.chars().count() - trimmed.chars().count();
+    let inside = trimmed.chars().filter(|c| c.is_whitespace()).count();
+    if surrounding > 0 {
+        log.warn(format!(
+            "Ignored {} whitespace character{} around {}.",
+            surrounding,
+            if surrounding == 1 { "" } else { "s" },
+            flag
+        ));
+    }
+    if inside == 0 {
+        return;
+    }
+    if args.strip_whitespace {
+        log.warn(format!(
+            "--strip-whitespace removed {} whitespace character{} from {}.",
+            inside,
+            if inside == 1 { "" } else { "s" },
+            flag
+        ));
+    } else {
+        log.warn(format!(
+            "{} contains {} whitespace character{}, which can appear in the password; pass --strip-whitespace to drop {}.",
+            flag,
+            inside,
+            if inside == 1 { "" } else { "s" },
+            if inside == 1 { "it" } else { "them" }
+        ));
+    }
+}
+
+/// Reads `--charset-file` into `--charset`, so the file's characters go
+/// through exactly the same checks as an inline charset. Line breaks are
+/// dropped, since an alphabet file almost always ends with one.
+pub(super) fn load_charset_file(args: &mut GenerateArgs) -> Result<(), String> {
+    let Some(path) = &args.charset_file else {
+        return Ok(());
+    };
+    let contents = fs::read_to_string(path)
+        .map_err(|err| format!("Failed to read charset file {}: {}", path.display(), err))?;
+    args.custom_charset = Some(
+        contents
+            .chars()
+            .filter(|&c| c != '\n' && c != '\r')
+            .collect(),
+    );
+    Ok(())
+}
+
+/// Takes the length from `--class-mask`, which has one entry per position,
+/// so the usual length checks apply to it.
+pub(super) fn apply_class_mask(args: &mut GenerateArgs) {
+    if let Some(mask) = &args.class_mask {
+        args.length = mask.length();
+    }
+}
+
+/// Reads and parses `--template-file` into `--template`. One trailing line
+/// break is dropped, since each rendered copy is printed with its own.
+pub(super) fn load_template_file(args: &mut GenerateArgs) -> Result<(), String> {
+    let Some(path) = &args.template_file else {
+        return Ok(());
+    };
+    let contents = fs::read_to_string(path)
+        .map_err(|err| format!("Failed to read template file {}: {}", path.display(), err))?;
+    let contents = contents
+        .strip_suffix('\n')
+        .map(|rest| rest.strip_suffix('\r').unwrap_or(rest))
+        .unwrap_or(&contents);
+    let template: Template = contents
+        .parse()
+        .map_err(|err| format!("Invalid template file {}: {}", path.display(), err))?;
+    if !template.uses_password() && !args.allow_no_placeholder {
+        return Err(format!(
+            "Template file {} has no {{password}} placeholder; pass --allow-no-placeholder to use it anyway",
+            path.display()
+        ));
+    }
+    args.template = Some(template);
+    Ok(())
+}
+
+/// Reads `--charset-from-stdin`: every character of the input except line
+/// breaks, in order of first appearance.
+pub(super) fn read_charset() -> Result<Vec<u8>, String> {
+    let mut input = String::new();
+    io::stdin()
+        .lock()
+        .read_to_string(&mut input)
+        .map_err(|err| format!("Failed to read charset from stdin: {}", err))?;
+
+    let mut charset = Vec::new();
+    for c in input.chars().filter(|&c| c != '\n' && c != '\r') {
+        if !c.is_ascii_graphic() {
+            return Err(format!(
+                "Charset on stdin may only contain printable ASCII without spaces, found {:?}.",
+                c
+            ));
+        }
+        if !charset.contains(&(c as u8)) {
+            charset.push(c as u8);
+        }
+    }
+    if charset.is_empty() {
+        return Err("Expected a charset on stdin but the input was empty.".to_string());
+    }
+    Ok(charset)
+}
@@ -0,0 +1,684 @@
+//! The generation modes `generate` picks from its flags, and the drawing
+//! helpers they share.
+
+use clap::ValueEnum;
+use indicatif::ProgressBar;
+use password_generator_rust::alternate::{alternating_entropy_bits, generate_alternating_with_rng};
+use password_generator_rust::balanced::{
+    balanced_entropy_bits, class_pools, generate_balanced_with_rng,
+};
+use password_generator_rust::charset::{CharClass, WIFI_SYMBOLS, class_of, emoji};
+use password_generator_rust::distinct::{distinct_entropy_bits, generate_distinct_with_rng};
+use password_generator_rust::policy::Policy;
+use password_generator_rust::positions::{generate_from_pools_with_rng, pools_entropy_bits};
+use password_generator_rust::rng::RngSource;
+use password_generator_rust::sections::Sections;
+use password_generator_rust::selftest::{HISTOGRAM_BAND_SIGMAS, HISTOGRAM_SAMPLE_SIZE, histogram};
+use password_generator_rust::token::Encoding;
+use password_generator_rust::words::{generate_memorable, memorable_entropy_bits};
+use password_generator_rust::{
+    entropy_bits, generate_password_from_chars, generate_password_with_edges_rng,
+    generate_password_with_required, generate_password_with_rng, length_for_bits,
+    required_entropy_bits,
+};
+use rand::RngCore;
+use rand::rngs::OsRng;
+use rand::seq::SliceRandom;
+use rayon::ThreadPoolBuilder;
+use rayon::prelude::*;
+
+use super::constraints::Constraints;
+use super::input::custom_pool;
+use super::{DigitScript, GenerateArgs, applied_flags};
+use crate::cli::{AppError, Logger, charset_or_exit, exit_with_error};
+
+/// Names the character classes present in `pool` in prose, e.g.
+/// `uppercase, lowercase and digits`. Non-ASCII characters are called
+/// `non_ascii`.
+fn class_names(pool: &[char], non_ascii: &str) -> String {
+    let mut names: Vec<&str> = CharClass::ALL
+        .into_iter()
+        .filter(|&class| {
+            pool.iter()
+                .any(|&c| c.is_ascii() && class_of(c as u8) == Some(class))
+        })
+        .map(|class| match class {
+            CharClass::Upper => "uppercase",
+            CharClass::Lower => "lowercase",
+            CharClass::Digit => "digits",
+            CharClass::Symbol => "symbols",
+        })
+        .collect();
+    if pool.contains(&' ') {
+        names.push("spaces");
+    }
+    if pool.iter().any(|&c| !c.is_ascii()) {
+        names.push(non_ascii);
+    } else if pool
+        .iter()
+        .any(|&c| c != ' ' && class_of(c as u8).is_none())
+    {
+        names.push("other characters");
+    }
+
+    match names.split_last() {
+        Some((last, [])) => last.to_string(),
+        Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+        None => "no characters".to_string(),
+    }
+}
+
+/// What kind of password `generate` produces, resolved from the flags.
+pub(super) enum Mode {
+    /// `--memorable`: two words joined by a digit and a symbol.
+    Memorable,
+    /// `--policy`: full charset; [`Constraints`] redraws until the policy is met.
+    Policy(&'static Policy),
+    /// The default: every character drawn uniformly from `charset`.
+    Random { charset: Vec<u8> },
+    /// `--no-edge-symbol` or `--include-space`: like `Random`, but the ends
+    /// come from `edges`.
+    Edges { charset: Vec<u8>, edges: Vec<u8> },
+    /// `--emoji` or a non-ASCII `--charset`: like `Random`, over a pool of
+    /// arbitrary chars.
+    Chars { pool: Vec<char> },
+    /// `--require-from` over what would otherwise be `Random` or `Chars`: one
+    /// character from each of the `required` sets, the rest from `pool`, in
+    /// shuffled order. Other modes leave `--require-from` to [`Constraints`].
+    Required {
+        pool: Vec<char>,
+        required: Vec<Vec<char>>,
+    },
+    /// `--balanced`: each class of `charset` gets a near-equal share.
+    Balanced { charset: Vec<u8> },
+    /// `--alternate-classes`: neighbors never share a class of `charset`.
+    Alternating { charset: Vec<u8> },
+    /// `--max-char-occurrences 1`: `charset` drawn without replacement.
+    Distinct { charset: Vec<u8> },
+    /// `--position-rules`: position `i` drawn from `pools[i]`, each a subset
+    /// of `charset`.
+    Positions {
+        charset: Vec<u8>,
+        pools: Vec<Vec<u8>>,
+    },
+    /// `--bytes` with an `--encoding` other than `chars`.
+    Encoded { bytes: usize, encoding: Encoding },
+    /// `--sections`: independent parts joined by `separator`.
+    Sections {
+        sections: Sections,
+        separator: String,
+    },
+}
+
+impl Mode {
+    pub(super) fn from_args(args: &GenerateArgs) -> Self {
+        let mode = Self::unrequired(args);
+        if args.require_from.is_empty() {
+            return mode;
+        }
+        let pool = match mode {
+            Mode::Random { charset } => charset.into_iter().map(char::from).collect(),
+            Mode::Chars { pool } => pool,
+            mode => return mode,
+        };
+        let required = args
+            .require_from
+            .iter()
+            .map(|set| {
+                let mut set: Vec<char> = set.chars().collect();
+                set.sort_unstable();
+                set.dedup();
+                set
+            })
+            .collect();
+        Mode::Required { pool, required }
+    }
+
+    /// The mode the flags other than `--require-from` select.
+    fn unrequired(args: &GenerateArgs) -> Self {
+        if args.memorable {
+            return Mode::Memorable;
+        }
+        if let Some(preset) = args.policy {
+            return Mode::Policy(preset.policy());
+        }
+        if let Some(sections) = &args.sections {
+            return Mode::Sections {
+                sections: sections.clone(),
+                separator: args.section_separator.clone(),
+            };
+        }
+        if let Some(bytes) = args.bytes
+            && let Some(encoding) = args.encoding.encoding()
+        {
+            return Mode::Encoded { bytes, encoding };
+        }
+
+        if let Some(pool) = custom_pool(args) {
+            // ASCII pools were moved into `args.charset` by `run`.
+            return Mode::Chars { pool };
+        }
+
+        let mut charset = charset_or_exit(&args.charset);
+        if args.ascii_only {
+            charset.retain(u8::is_ascii);
+        }
+        if args.wifi {
+            charset.retain(|b| !b.is_ascii_punctuation() || WIFI_SYMBOLS.contains(b));
+        }
+        if let Some(preset) = args.compat {
+            charset = preset.system().filter(&charset);
+        }
+        if let Some(rules) = &args.position_rules {
+            let pools = rules
+                .pools(args.length, &charset)
+                .unwrap_or_else(|message| {
+                    exit_with_error(format!("--position-rules: {}", message))
+                });
+            return Mode::Positions { charset, pools };
+        }
+        if let Some(mask) = &args.class_mask {
+            let pools = mask
+                .pools(&charset)
+                .unwrap_or_else(|message| exit_with_error(format!("--class-mask: {}", message)));
+            return Mode::Positions { charset, pools };
+        }
+        if args.no_leading_zero {
+            let mut pools = vec![charset.clone(); args.length];
+            pools[0].retain(|&b| b != b'0');
+            return Mode::Positions { charset, pools };
+        }
+        if args.emoji {
+            let mut pool: Vec<char> = charset.iter().map(|&b| b as char).collect();
+            pool.extend(emoji());
+            return Mode::Chars { pool };
+        }
+        if args.digit_script != DigitScript::Ascii {
+            let pool = charset
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_digit() {
+                        args.digit_script.map(b)
+                    } else {
+                        b as char
+                    }
+                })
+                .collect();
+            return Mode::Chars { pool };
+        }
+        if args.balanced {
+            return Mode::Balanced { charset };
+        }
+        if args.alternate_classes {
+            return Mode::Alternating { charset };
+        }
+        if args.include_space && !charset.contains(&b' ') {
+            charset.push(b' ');
+        }
+        if args.no_edge_symbol || args.include_space {
+            let edges = charset
+                .iter()
+                .copied()
+                .filter(|&b| {
+                    if args.no_edge_symbol {
+                        b.is_ascii_alphanumeric()
+                    } else {
+                        b != b' '
+                    }
+                })
+                .collect();
+            Mode::Edges { charset, edges }
+        } else if args.max_char_occurrences == Some(1) {
+            // Redrawing until nothing repeats is hopeless near the charset
+            // size; drawing without replacement gives the same distribution.
+            charset.sort_unstable();
+            charset.dedup();
+            Mode::Distinct { charset }
+        } else {
+            Mode::Random { charset }
+        }
+    }
+
+    /// Reorders the characters drawn from with `OsRng`, for `--alphabet-order shuffle`.
+    ///
+    /// Draws pick every character with equal probability whatever its place,
+    /// so this changes nothing about the output distribution.
+    pub(super) fn shuffle_charset(&mut self) {
+        match self {
+            Mode::Memorable | Mode::Policy(_) | Mode::Encoded { .. } | Mode::Sections { .. } => {}
+            Mode::Random { charset }
+            | Mode::Balanced { charset }
+            | Mode::Alternating { charset }
+            | Mode::Distinct { charset } => charset.shuffle(&mut OsRng),
+            Mode::Edges { charset, edges } => {
+                charset.shuffle(&mut OsRng);
+                edges.shuffle(&mut OsRng);
+            }
+            Mode::Chars { pool } | Mode::Required { pool, .. } => pool.shuffle(&mut OsRng),
+            Mode::Positions { charset, pools } => {
+                charset.shuffle(&mut OsRng);
+                pools.iter_mut().for_each(|pool| pool.shuffle(&mut OsRng));
+            }
+        }
+    }
+
+    /// Entropy in bits of one password of `length` characters.
+    pub(super) fn entropy_bits(&self, length: usize) -> f64 {
+        match self {
+            Mode::Memorable => memorable_entropy_bits(),
+            Mode::Policy(policy) => {
+                entropy_bits(policy.clamp_length(length), policy.charset().len())
+            }
+            Mode::Random { charset } => entropy_bits(length, charset.len()),
+            // --weak-ok lets lengths below 2 through; then every position is an edge.
+            Mode::Edges { charset, edges } => {
+                entropy_bits(length.saturating_sub(2), charset.len())
+                    + entropy_bits(length.min(2), edges.len())
+            }
+            Mode::Chars { pool } => entropy_bits(length, pool.len()),
+            Mode::Required { pool, required } => {
+                required_entropy_bits(length, pool.len(), required)
+            }
+            Mode::Balanced { charset } => balanced_entropy_bits(length, charset),
+            Mode::Alternating { charset } => alternating_entropy_bits(length, charset),
+            Mode::Distinct { charset } => distinct_entropy_bits(length, charset.len()),
+            // Positions past the pools, e.g. after --retry-on-weak grew the
+            // length, draw from the whole charset.
+            Mode::Positions { charset, pools } => {
+                pools_entropy_bits(pools)
+                    + entropy_bits(length.saturating_sub(pools.len()), charset.len())
+            }
+            Mode::Encoded { bytes, .. } => (bytes * 8) as f64,
+            Mode::Sections { sections, .. } => sections.entropy_bits(),
+        }
+    }
+
+    /// The characters each position is drawn from, or `None` for the
+    /// word-based `--memorable` mode and for `--sections`, whose positions
+    /// draw from different pools.
+    pub(super) fn pool(&self) -> Option<Vec<char>> {
+        let bytes = match self {
+            Mode::Memorable | Mode::Encoded { .. } | Mode::Sections { .. } => return None,
+            Mode::Policy(policy) => policy.charset(),
+            Mode::Random { charset }
+            | Mode::Edges { charset, .. }
+            | Mode::Balanced { charset }
+            | Mode::Alternating { charset }
+            | Mode::Distinct { charset }
+            | Mode::Positions { charset, .. } => charset.clone(),
+            Mode::Chars { pool } | Mode::Required { pool, .. } => return Some(pool.clone()),
+        };
+        Some(bytes.into_iter().map(char::from).collect())
+    }
+
+    /// Advice for reaching `bits` of entropy, for the `--min-entropy` error.
+    pub(super) fn suggestion(&self, bits: f64) -> String {
+        let needed = match self {
+            Mode::Random { charset } => length_for_bits(bits, charset.len()),
+            Mode::Chars { pool } => length_for_bits(bits, pool.len()),
+            // The two edge characters come from the smaller pool, so size the
+            // interior to cover whatever they leave short.
+            Mode::Edges { charset, edges } => {
+                let interior = (bits - entropy_bits(2, edges.len())).max(0.0);
+                length_for_bits(interior, charset.len()).map(|n| n + 2)
+            }
+            Mode::Required { pool, required } => {
+                let placed = required_entropy_bits(required.len(), pool.len(), required);
+                length_for_bits((bits - placed).max(0.0), pool.len()).map(|n| n + required.len())
+            }
+            Mode::Memorable
+            | Mode::Policy(_)
+            | Mode::Balanced { .. }
+            | Mode::Alternating { .. }
+            | Mode::Distinct { .. }
+            | Mode::Positions { .. }
+            | Mode::Encoded { .. }
+            | Mode::Sections { .. } => None,
+        };
+        match needed {
+            Some(length) => format!("Use --length {} or more.", length.max(8)),
+            None => "Use a longer --length or more character classes.".to_string(),
+        }
+    }
+
+    /// Prints mode details for `--verbose`.
+    pub(super) fn describe(&self, args: &GenerateArgs, log: Logger) {
+        match self {
+            Mode::Memorable => log.verbose("Mode: memorable"),
+            Mode::Encoded { bytes, .. } => log.verbose(format!(
+                "Mode: {} random bytes as {}",
+                bytes,
+                args.encoding
+                    .to_possible_value()
+                    .expect("no skipped variants")
+                    .get_name()
+            )),
+            Mode::Sections { sections, .. } => {
+                for (index, section) in sections.0.iter().enumerate() {
+                    log.verbose(format!(
+                        "Section {}: {} {} characters ({} possible)",
+                        index + 1,
+                        section.length,
+                        section.class,
+                        section.charset.len()
+                    ));
+                }
+            }
+            Mode::Policy(policy) => {
+                let length = policy.clamp_length(args.length);
+                log.verbose(format!("Policy: {} (length {})", policy.name, length));
+                log.verbose(format!("Charset size: {}", policy.charset().len()));
+            }
+            Mode::Random { charset } | Mode::Edges { charset, .. } | Mode::Distinct { charset } => {
+                log.verbose(format!("Flags applied: {}", applied_flags(&args.charset)));
+                log.verbose(format!("Charset size: {}", charset.len()));
+            }
+            Mode::Positions { charset, .. } => {
+                log.verbose(format!("Flags applied: {}", applied_flags(&args.charset)));
+                log.verbose(format!("Charset size: {}", charset.len()));
+                if let Some(rules) = &args.position_rules {
+                    let rules: Vec<String> = rules.0.iter().map(ToString::to_string).collect();
+                    log.verbose(format!("Position rules: {}", rules.join(", ")));
+                }
+                if let Some(mask) = &args.class_mask {
+                    log.verbose(format!("Class mask: {}", mask));
+                }
+                if args.no_leading_zero {
+                    log.verbose("First digit: 1-9");
+                }
+            }
+            Mode::Chars { pool } if args.emoji => {
+                log.verbose(format!("Flags applied: {}", applied_flags(&args.charset)));
+                log.verbose(format!("Charset size: {} (including emoji)", pool.len()));
+            }
+            Mode::Chars { pool } if args.digit_script != DigitScript::Ascii => {
+                log.verbose(format!("Flags applied: {}", applied_flags(&args.charset)));
+                log.verbose(format!(
+                    "Charset size: {} ({} digits)",
+                    pool.len(),
+                    args.digit_script.name()
+                ));
+            }
+            Mode::Chars { pool } => log.verbose(format!("Charset size: {} (custom)", pool.len())),
+            Mode::Required { pool, required } => {
+                log.verbose(format!("Charset size: {}", pool.len()));
+                log.verbose(format!(
+                    "Required sets: {} (one character placed from each)",
+                    required.len()
+                ));
+            }
+            Mode::Balanced { charset } => {
+                log.verbose(format!("Flags applied: {}", applied_flags(&args.charset)));
+                log.verbose(format!("Charset size: {}", charset.len()));
+                log.verbose(format!("Balanced classes: {}", class_pools(charset).len()));
+            }
+            Mode::Alternating { charset } => {
+                log.verbose(format!("Flags applied: {}", applied_flags(&args.charset)));
+                log.verbose(format!("Charset size: {}", charset.len()));
+                log.verbose(format!(
+                    "Alternating classes: {}",
+                    class_pools(charset).len()
+                ));
+            }
+        }
+    }
+
+    /// A one-sentence summary of one password, for `--explain`.
+    pub(super) fn explain(&self, args: &GenerateArgs, bits: f64) -> String {
+        if let Mode::Encoded { bytes, .. } = self {
+            return format!(
+                "{} random bytes encoded as {} ({:.0} bits of entropy).",
+                bytes,
+                args.encoding
+                    .to_possible_value()
+                    .expect("no skipped variants")
+                    .get_name(),
+                bits
+            );
+        }
+        if let Mode::Sections {
+            sections,
+            separator,
+        } = self
+        {
+            let parts: Vec<String> = sections
+                .0
+                .iter()
+                .map(|section| format!("{} {}", section.length, section.class))
+                .collect();
+            return format!(
+                "{} sections ({}) joined by {:?} (~{:.0} bits of entropy).",
+                parts.len(),
+                parts.join(", "),
+                separator,
+                bits
+            );
+        }
+        let (length, pool) = match (self, self.pool()) {
+            (Mode::Policy(policy), Some(pool)) => (policy.clamp_length(args.length), pool),
+            (_, Some(pool)) => (args.length, pool),
+            (_, None) => {
+                return format!(
+                    "Two random dictionary words joined by a digit and a symbol (~{:.0} bits of entropy).",
+                    bits
+                );
+            }
+        };
+
+        let mut summary = format!("{}-character password", length);
+        if let Mode::Policy(policy) = self {
+            summary.push_str(&format!(" meeting the {} policy", policy.name));
+        }
+        let non_ascii = if args.emoji {
+            "emoji".to_string()
+        } else if args.digit_script != DigitScript::Ascii {
+            format!("{} digits", args.digit_script.name())
+        } else {
+            "non-ASCII characters".to_string()
+        };
+        summary.push_str(&format!(" using {}", class_names(&pool, &non_ascii)));
+        match self {
+            Mode::Edges { .. } if args.no_edge_symbol => {
+                summary.push_str(", with no symbol at either end")
+            }
+            Mode::Edges { .. } => summary.push_str(", with no space at either end"),
+            Mode::Balanced { .. } => summary.push_str(", with each class equally represented"),
+            Mode::Alternating { .. } => summary.push_str(", never two of the same class in a row"),
+            Mode::Distinct { .. } => summary.push_str(", with no character used twice"),
+            Mode::Required { .. } => summary
+                .push_str(", with a character from each --require-from set at a random position"),
+            Mode::Positions { .. } => {
+                if let Some(rules) = &args.position_rules {
+                    let rules: Vec<String> = rules.0.iter().map(ToString::to_string).collect();
+                    summary.push_str(&format!(", with positions {}", rules.join(", ")));
+                }
+                if let Some(mask) = &args.class_mask {
+                    summary.push_str(&format!(", with class mask {}", mask));
+                }
+                if args.no_leading_zero {
+                    summary.push_str(", with no leading zero");
+                }
+            }
+            _ => {}
+        }
+        summary.push_str(&format!(
+            " ({} possible characters, ~{:.0} bits of entropy).",
+            pool.len(),
+            bits
+        ));
+
+        if args.append_checksum {
+            summary.push_str(" A check character is appended.");
+        }
+        if let Some(prefix) = &args.prefix {
+            summary.push_str(&format!(" It starts with {:?}.", prefix));
+        }
+        if let Some(suffix) = &args.suffix {
+            summary.push_str(&format!(" It ends with {:?}.", suffix));
+        }
+        summary
+    }
+
+    /// Draws one candidate password from `rng`.
+    ///
+    /// Fails if the mode can't fill `length` positions, which [`validate`]
+    /// normally rules out. `--memorable` always uses `OsRng`; clap rejects it
+    /// together with `--rng`.
+    pub(super) fn generate(&self, rng: &mut dyn RngCore, length: usize) -> Result<String, String> {
+        let password = match self {
+            Mode::Memorable => generate_memorable(),
+            Mode::Policy(policy) => {
+                generate_password_with_rng(rng, policy.clamp_length(length), &policy.charset())
+            }
+            Mode::Random { charset } => generate_password_with_rng(rng, length, charset),
+            Mode::Positions { pools, .. } => generate_from_pools_with_rng(rng, pools),
+            Mode::Edges { charset, edges } => {
+                generate_password_with_edges_rng(rng, length, charset, edges)
+            }
+            Mode::Chars { pool } => generate_password_from_chars(rng, length, pool),
+            Mode::Required { pool, required } => {
+                generate_password_with_required(rng, length, pool, required).ok_or_else(|| {
+                    format!(
+                        "--require-from is given {} times, more than --length {} can accommodate.",
+                        required.len(),
+                        length
+                    )
+                })?
+            }
+            Mode::Balanced { charset } => generate_balanced_with_rng(rng, length, charset)
+                .ok_or_else(|| {
+                    format!(
+                        "--balanced needs a length of at least {} to give every class a character.",
+                        class_pools(charset).len()
+                    )
+                })?,
+            Mode::Alternating { charset } => generate_alternating_with_rng(rng, length, charset)
+                .ok_or_else(|| {
+                    "--alternate-classes needs at least two character classes to alternate between."
+                        .to_string()
+                })?,
+            Mode::Distinct { charset } => generate_distinct_with_rng(rng, length, charset)
+                .ok_or_else(|| {
+                    format!(
+                        "--max-char-occurrences 1 cannot be met: {} characters can fill at most {} positions, fewer than --length {}.",
+                        charset.len(),
+                        charset.len(),
+                        length
+                    )
+                })?,
+            Mode::Encoded { bytes, encoding } => {
+                let mut buf = vec![0u8; *bytes];
+                rng.fill_bytes(&mut buf);
+                encoding.encode(&buf)
+            }
+            Mode::Sections {
+                sections,
+                separator,
+            } => sections.generate_with_rng(rng, separator),
+        };
+        Ok(password)
+    }
+}
+
+/// Draws `args.count` candidates on `args.jobs` threads, each with its own
+/// RNG from `--rng`, in index order.
+///
+/// Every candidate passes the constraints except `--unique`, which needs the
+/// whole batch and is checked by the caller.
+pub(super) fn draw_parallel(
+    args: &GenerateArgs,
+    mode: &Mode,
+    constraints: &Constraints,
+    log: Logger,
+    progress: Option<&ProgressBar>,
+) -> Result<Vec<String>, String> {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(args.jobs)
+        .build()
+        .map_err(|err| format!("Failed to start {} threads: {}", args.jobs, err))?;
+    log.verbose(format!("Threads: {}", args.jobs));
+
+    pool.install(|| {
+        (0..args.count)
+            .into_par_iter()
+            .map_init(
+                || (build_rng(args.rng, None), 0),
+                |(rng, drawn), index| {
+                    if args.rng.per_index() {
+                        *rng = build_rng(args.rng, Some(index as u64));
+                    }
+                    // Each thread counts its own draws towards --reseed-every.
+                    if reseed_due(args, *drawn) {
+                        *rng = build_rng(args.rng, None);
+                    }
+                    *drawn += 1;
+                    let random =
+                        constraints.generate(log, || mode.generate(&mut **rng, args.length));
+                    if let Some(progress) = progress {
+                        progress.inc(1);
+                    }
+                    random
+                },
+            )
+            .collect()
+    })
+}
+
+/// Builds the `--rng` generator, for the password at `index` of the batch if
+/// given. Every generator `run` draws from comes from here, so tests can
+/// count them.
+pub(super) fn build_rng(source: RngSource, index: Option<u64>) -> Box<dyn RngCore> {
+    #[cfg(test)]
+    super::tests::RNG_BUILDS.with(|builds| builds.set(builds.get() + 1));
+    match index {
+        Some(index) => source.build_for_index(index),
+        None => source.build(),
+    }
+}
+
+/// Returns `true` if `--reseed-every` calls for a fresh RNG before the
+/// password at `index`. The RNG built for index 0 is already fresh.
+pub(super) fn reseed_due(args: &GenerateArgs, index: usize) -> bool {
+    args.rng.reseeds()
+        && index > 0
+        && args
+            .reseed_every
+            .is_some_and(|interval| index.is_multiple_of(interval))
+}
+
+/// Prints the `--histogram` diagnostic and fails with
+/// [`AppError::CheckFailed`] if any character falls outside the expected band.
+pub(super) fn print_histogram(mode: &Mode, rng: &mut dyn RngCore) -> Result<(), AppError> {
+    let Some(pool) = mode.pool() else {
+        return Err(AppError::Usage(
+            "--histogram needs a mode that draws from a character set.".to_string(),
+        ));
+    };
+    let sample = mode.generate(rng, HISTOGRAM_SAMPLE_SIZE)?;
+    let buckets = histogram(&sample, &pool);
+
+    for bucket in &buckets {
+        let flag = if bucket.outlier {
+            "  <-- outside band"
+        } else {
+            ""
+        };
+        println!(
+            "{:?}\t{}\t(expected {:.1}){}",
+            bucket.ch, bucket.observed, bucket.expected, flag
+        );
+    }
+
+    let outliers = buckets.iter().filter(|bucket| bucket.outlier).count();
+    println!(
+        "{} characters sampled; {} of {} outside the {}-sigma band.",
+        HISTOGRAM_SAMPLE_SIZE,
+        outliers,
+        buckets.len(),
+        HISTOGRAM_BAND_SIGMAS
+    );
+    if outliers > 0 {
+        return Err(AppError::CheckFailed);
+    }
+    Ok(())
+}
@@ -0,0 +1,666 @@
+//! Printing and writing a generated batch: the output formats, files, the
+//! audit log and the stderr summaries.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use owo_colors::OwoColorize;
+use password_generator_rust::template::Fields;
+use password_generator_rust::{CRACK_TIME_CAP, audit, qr};
+use rand::RngCore;
+use rand::rngs::OsRng;
+use schemars::JsonSchema;
+use schemars::generate::SchemaSettings;
+use serde::Serialize;
+
+use super::{GenerateArgs, OutputFormat};
+use crate::cli::{AppError, Logger, auto_color, clipboard, shell_quote};
+
+/// Appends one [`audit::Record`] per password to the `--audit-log` file.
+pub(super) fn write_audit_log(
+    path: &Path,
+    settings: &str,
+    timestamp: &str,
+    passwords: &[String],
+    bits: f64,
+) -> Result<(), AppError> {
+    let lines = passwords
+        .iter()
+        .enumerate()
+        .map(|(index, password)| {
+            audit::Record {
+                timestamp,
+                settings,
+                index: index + 1,
+                entropy_bits: bits,
+                password,
+            }
+            .to_line()
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| {
+            AppError::Runtime(format!("Failed to hash password for audit log: {}", err))
+        })?;
+    audit::append(path, &lines).map_err(|source| AppError::Io {
+        context: format!("Failed to write audit log {}", path.display()),
+        source,
+    })
+}
+
+/// Where [`emit`] will send the passwords, for `--dry-run`.
+pub(super) fn destination(args: &GenerateArgs) -> String {
+    if let Some(dir) = &args.output_dir {
+        return format!("one file per password in {}", dir.display());
+    }
+    if let Some(seconds) = args.clipboard_timeout {
+        return format!("clipboard, restored after {} seconds", seconds);
+    }
+    if let Some(scheme) = args.split {
+        return format!("stdout, as {} Shamir shares", scheme);
+    }
+    if args.clipboard {
+        return "clipboard".to_string();
+    }
+    let form = if args.table {
+        "a table"
+    } else if args.format == OutputFormat::Csv {
+        "CSV"
+    } else if args.qr {
+        "a QR code"
+    } else if args.output_env.is_some() {
+        "an export statement"
+    } else if args.format == OutputFormat::Env {
+        ".env assignments"
+    } else if args.template_file.is_some() {
+        "copies of --template-file"
+    } else if args.template.is_some() {
+        "lines from --template"
+    } else {
+        return "stdout".to_string();
+    };
+    format!("stdout, as {}", form)
+}
+
+/// Writes the generated passwords in the form the output flags select.
+pub(super) fn emit(
+    args: &GenerateArgs,
+    log: Logger,
+    passwords: &[String],
+    bits: f64,
+    charset_size: Option<usize>,
+    generated_at: Option<&str>,
+) -> Result<(), AppError> {
+    if let Some(dir) = &args.output_dir {
+        write_files(dir, passwords, args.force, args.secure_erase)?;
+        log.info(format!(
+            "Wrote {} passwords to {}",
+            passwords.len(),
+            dir.display()
+        ));
+        return Ok(());
+    }
+    if let Some(scheme) = args.split {
+        for password in passwords {
+            for share in scheme.split_with_rng(&mut OsRng, password) {
+                println!("{}", share);
+            }
+        }
+        log.info(format!(
+            "Printed {} shares; any {} of them recover the password with `combine`. Store them separately.",
+            scheme.shares, scheme.threshold
+        ));
+        return Ok(());
+    }
+    if args.table {
+        print_table(passwords, bits);
+        return Ok(());
+    }
+    if args.format == OutputFormat::Csv {
+        return write_csv(passwords, bits, !args.no_header, generated_at).map_err(|err| {
+            AppError::Io {
+                context: "Failed to write CSV".to_string(),
+                source: err.into(),
+            }
+        });
+    }
+    if args.clipboard || args.clipboard_timeout.is_some() {
+        let timeout = args.clipboard_timeout.map(Duration::from_secs);
+        clipboard::copy(&clipboard_payload(passwords), passwords.len(), timeout, log)?;
+        return Ok(());
+    }
+
+    for (index, password) in passwords.iter().enumerate() {
+        if args.qr {
+            let code = qr::render(password, supports_unicode())
+                .map_err(|err| AppError::Runtime(format!("Failed to render QR code: {}", err)))?;
+            println!("{}", code);
+            if args.output.quiet {
+                continue;
+            }
+        }
+        let line = if let Some(template) = &args.template {
+            template.render(&Fields {
+                password,
+                index: index + 1,
+                bits,
+                charset_size,
+            })
+        } else if let Some(name) = &args.output_env {
+            format!("export {}={}", name, shell_quote(password))
+        } else if let (OutputFormat::Env, Some(name)) = (args.format, &args.var_name) {
+            if passwords.len() > 1 {
+                format!("{}_{}={}", name, index + 1, dotenv_quote(password))
+            } else {
+                format!("{}={}", name, dotenv_quote(password))
+            }
+        } else if let Some(width) = args.pad_to {
+            pad_display(password, width)
+        } else {
+            password.clone()
+        };
+        if args.mask_output && io::stdout().is_terminal() && io::stdin().is_terminal() {
+            wait_to_reveal(&line).map_err(|source| AppError::Io {
+                context: "Failed to read from the terminal".to_string(),
+                source,
+            })?;
+        }
+        if args.no_newline && index + 1 == passwords.len() {
+            print!("{}", line);
+            io::stdout().flush().map_err(|source| AppError::Io {
+                context: "Failed to write password".to_string(),
+                source,
+            })?;
+        } else {
+            println!("{}", line);
+        }
+    }
+    Ok(())
+}
+
+/// What `--clipboard` copies: the whole batch in one piece, one password
+/// per line.
+fn clipboard_payload(passwords: &[String]) -> String {
+    passwords.join("\n")
+}
+
+/// Shows `line` masked for `--mask-output` and waits for Enter, then clears
+/// the mask so the real value is printed in its place.
+fn wait_to_reveal(line: &str) -> io::Result<()> {
+    let mask = "*".repeat(line.chars().count());
+    // Hidden input, so a stray keystroke isn't echoed next to the mask.
+    rpassword::prompt_password(format!("{}  (press Enter to reveal)", mask))?;
+    // The hidden prompt ends with a newline: go back up and clear that line.
+    print!("\x1b[1A\r\x1b[2K");
+    Ok(())
+}
+
+/// Aggregate figures over a `--stats` batch.
+#[derive(Default)]
+pub(super) struct BatchSummary {
+    pub(super) count: usize,
+    total_bits: f64,
+    min_length: Option<usize>,
+    max_length: usize,
+}
+
+impl BatchSummary {
+    /// Adds one password of `bits` entropy to the totals.
+    pub(super) fn add(&mut self, password: &str, bits: f64) {
+        let length = password.chars().count();
+        self.count += 1;
+        self.total_bits += bits;
+        self.min_length = Some(self.min_length.map_or(length, |min| min.min(length)));
+        self.max_length = self.max_length.max(length);
+    }
+}
+
+impl std::fmt::Display for BatchSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Batch: {} passwords, {:.1} bits of entropy on average, {}-{} characters long",
+            self.count,
+            self.total_bits / self.count as f64,
+            self.min_length.unwrap_or_default(),
+            self.max_length
+        )
+    }
+}
+
+/// Entropy at which `--meter` shows a full bar.
+const METER_FULL_BITS: f64 = 128.0;
+
+/// Renders `--meter`: a colored bar on a terminal, otherwise a plain line.
+pub(super) fn render_meter(bits: f64) -> String {
+    const WIDTH: usize = 20;
+
+    let fraction = (bits / METER_FULL_BITS).clamp(0.0, 1.0);
+    let percent = (fraction * 100.0).round();
+    if !io::stderr().is_terminal() {
+        return format!(
+            "Strength: {}% ({:.1} of {} bits)",
+            percent, bits, METER_FULL_BITS
+        );
+    }
+
+    let filled = (fraction * WIDTH as f64).round() as usize;
+    let (full, empty) = if supports_unicode() {
+        ('█', '░')
+    } else {
+        ('#', '-')
+    };
+    let bar: String = std::iter::repeat_n(full, filled)
+        .chain(std::iter::repeat_n(empty, WIDTH - filled))
+        .collect();
+    let bar = if !auto_color(true) {
+        bar
+    } else if bits < 60.0 {
+        bar.red().to_string()
+    } else if bits < 80.0 {
+        bar.yellow().to_string()
+    } else {
+        bar.green().to_string()
+    };
+    format!("[{}] {}%", bar, percent)
+}
+
+/// Renders a [`crack_time_estimate`] the way people think about it: a rounded
+/// count of the largest fitting unit, or a phrase for the very long ones.
+pub(super) fn describe_crack_time(estimate: Duration) -> String {
+    const MINUTE: u64 = 60;
+    const HOUR: u64 = 60 * MINUTE;
+    const DAY: u64 = 24 * HOUR;
+    const YEAR: u64 = 365 * DAY;
+    const AGE_OF_UNIVERSE: u64 = 13_800_000_000 * YEAR;
+
+    if estimate == CRACK_TIME_CAP {
+        return "longer than the heat death of the universe".to_string();
+    }
+    let seconds = estimate.as_secs();
+    let (count, unit) = match seconds {
+        0 => return "less than a second".to_string(),
+        s if s < MINUTE => (s, "second"),
+        s if s < HOUR => (s / MINUTE, "minute"),
+        s if s < DAY => (s / HOUR, "hour"),
+        s if s < YEAR => (s / DAY, "day"),
+        s if s < 100 * YEAR => (s / YEAR, "year"),
+        s if s < 1000 * YEAR => return "centuries".to_string(),
+        s if s < AGE_OF_UNIVERSE => return "millennia".to_string(),
+        _ => return "longer than the age of the universe".to_string(),
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    format!("{} {}{}", count, unit, plural)
+}
+
+/// Whether `name` is a legal environment variable name: ASCII letters,
+/// digits and `_`, not starting with a digit.
+pub(super) fn is_variable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Quotes `value` for a `.env` file, if it needs it.
+///
+/// Values made only of characters that no dotenv dialect treats specially
+/// stay bare. Anything else is single-quoted, which dotenv reads literally,
+/// unless it contains `'` itself; then it is double-quoted with `\`, `"`,
+/// `$` and `` ` `` escaped so nothing is interpolated.
+fn dotenv_quote(value: &str) -> String {
+    let bare = |c: char| c.is_ascii_alphanumeric() || "_-./:@%+,".contains(c);
+    if !value.is_empty() && value.chars().all(bare) {
+        return value.to_string();
+    }
+    if !value.contains('\'') {
+        return format!("'{}'", value);
+    }
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        if matches!(c, '\\' | '"' | '$' | '`') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Pads `password` for `--pad-to`: a space, which no charset contains, then
+/// markers up to `width` characters. Passwords already that wide are returned
+/// unchanged.
+fn pad_display(password: &str, width: usize) -> String {
+    let length = password.chars().count();
+    if length + 1 >= width {
+        return password.to_string();
+    }
+    let marker = if supports_unicode() { '·' } else { '~' };
+    let markers: String = std::iter::repeat_n(marker, width - length - 1).collect();
+    format!("{} {}", password, markers)
+}
+
+/// Prints `--table` output: one aligned row per password, numbers right-aligned.
+fn print_table(passwords: &[String], bits: f64) {
+    let header = ["#", "Password", "Length", "Entropy"];
+    let rows: Vec<[String; 4]> = passwords
+        .iter()
+        .enumerate()
+        .map(|(index, password)| {
+            [
+                (index + 1).to_string(),
+                password.clone(),
+                password.chars().count().to_string(),
+                format!("{:.1}", bits),
+            ]
+        })
+        .collect();
+
+    let mut widths = header.map(|title| title.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let [index, password, length, entropy] = widths;
+    println!(
+        "{:>index$}  {:<password$}  {:>length$}  {:>entropy$}",
+        header[0], header[1], header[2], header[3]
+    );
+    for [i, p, l, e] in &rows {
+        println!("{i:>index$}  {p:<password$}  {l:>length$}  {e:>entropy$}");
+    }
+}
+
+/// One `--format csv` row. The header row and `--json-schema` both come
+/// from this struct, so they cannot drift apart; the field docs are the
+/// schema's descriptions.
+#[derive(Serialize, JsonSchema)]
+#[schemars(
+    title = "password-generator-rust --format csv row",
+    description = "One row of --format csv output. Every password is one row; the header row, omitted with --no-header, lists the property names in this order.",
+    deny_unknown_fields
+)]
+struct CsvRow<'a> {
+    /// Position in the batch, starting at 1
+    #[schemars(range(min = 1))]
+    index: usize,
+    /// The generated password, including any prefix and suffix
+    password: &'a str,
+    /// Length of the password in characters
+    length: usize,
+    /// Entropy of the configuration in bits, to one decimal
+    entropy_bits: f64,
+    /// When the batch was generated, as an RFC 3339 UTC timestamp; only with --show-time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "String")]
+    generated_at: Option<&'a str>,
+}
+
+/// A JSON Schema (draft 2020-12) for one `--format csv` row, read as an
+/// object keyed by the header names.
+pub(super) fn csv_schema() -> String {
+    let schema = SchemaSettings::draft2020_12()
+        .into_generator()
+        .into_root_schema_for::<CsvRow>();
+    serde_json::to_string_pretty(&schema).expect("a schema always serializes")
+}
+
+/// Prints `--format csv` output. Passwords containing commas or quotes are
+/// quoted and escaped by the `csv` writer.
+///
+/// With `generated_at`, every row ends with that timestamp.
+fn write_csv(
+    passwords: &[String],
+    bits: f64,
+    header: bool,
+    generated_at: Option<&str>,
+) -> csv::Result<()> {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(header)
+        .from_writer(io::stdout().lock());
+    for (index, password) in passwords.iter().enumerate() {
+        writer.serialize(CsvRow {
+            index: index + 1,
+            password,
+            length: password.chars().count(),
+            entropy_bits: (bits * 10.0).round() / 10.0,
+            generated_at,
+        })?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Formats `time` as an RFC 3339 UTC timestamp, e.g. `2024-05-01T09:30:00Z`.
+///
+/// Uses Howard Hinnant's `civil_from_days` to turn days since the epoch
+/// into a calendar date.
+pub(super) fn rfc3339(time: SystemTime) -> String {
+    let seconds = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, of_day) = (seconds / 86_400, seconds % 86_400);
+
+    // Shift the epoch to 0000-03-01 so leap days fall at the end of a year.
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        of_day / 3_600,
+        of_day / 60 % 60,
+        of_day % 60
+    )
+}
+
+/// Writes `--output-dir` files: `password-001.txt` and so on, numbered from 1
+/// and padded to at least three digits.
+///
+/// Unless `force` is set, an existing file is an error, and every name is
+/// checked before anything is written so a clash leaves no partial batch.
+/// With `secure_erase`, an existing file's old contents are first overwritten
+/// in place by [`erase_contents`].
+fn write_files(
+    dir: &Path,
+    passwords: &[String],
+    force: bool,
+    secure_erase: bool,
+) -> Result<(), AppError> {
+    let width = passwords.len().to_string().len().max(3);
+    let paths: Vec<PathBuf> = (1..=passwords.len())
+        .map(|index| dir.join(format!("password-{:0width$}.txt", index, width = width)))
+        .collect();
+    if !force && let Some(existing) = paths.iter().find(|path| path.exists()) {
+        return Err(AppError::Runtime(format!(
+            "{} already exists; pass --force to overwrite it.",
+            existing.display()
+        )));
+    }
+
+    fs::create_dir_all(dir).map_err(|source| AppError::Io {
+        context: format!("Failed to create {}", dir.display()),
+        source,
+    })?;
+    for (path, password) in paths.iter().zip(passwords) {
+        if secure_erase && path.exists() {
+            erase_contents(path).map_err(|source| AppError::Io {
+                context: format!("Failed to erase {}", path.display()),
+                source,
+            })?;
+        }
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        options
+            .open(path)
+            .and_then(|mut file| file.write_all(password.as_bytes()))
+            .map_err(|source| AppError::Io {
+                context: format!("Failed to write {}", path.display()),
+                source,
+            })?;
+    }
+    Ok(())
+}
+
+/// Overwrites the current contents of the file at `path` with random bytes
+/// from `OsRng`, keeping its length, and flushes them to disk.
+///
+/// This only replaces the blocks the file occupies now. Copy-on-write
+/// filesystems and SSD wear leveling may keep the old data elsewhere, so it
+/// is a best effort, not a guarantee.
+fn erase_contents(path: &Path) -> io::Result<()> {
+    #[cfg(test)]
+    tests::ERASED.with(|erased| erased.set(erased.get() + 1));
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    let mut remaining = file.metadata()?.len();
+    let mut noise = [0u8; 4096];
+    while remaining > 0 {
+        let chunk = remaining.min(noise.len() as u64) as usize;
+        OsRng.fill_bytes(&mut noise[..chunk]);
+        file.write_all(&noise[..chunk])?;
+        remaining -= chunk as u64;
+    }
+    file.sync_all()
+}
+
+/// Guesses whether the terminal can render Unicode block characters.
+///
+/// Falls back to ASCII on `TERM=dumb` or when the locale isn't UTF-8.
+fn supports_unicode() -> bool {
+    if std::env::var("TERM").is_ok_and(|term| term == "dumb") {
+        return false;
+    }
+    if cfg!(windows) {
+        return true;
+    }
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+        .is_some_and(|locale| {
+            let locale = locale.to_ascii_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    thread_local! {
+        /// How many files [`erase_contents`] has overwritten on this thread.
+        pub(super) static ERASED: Cell<usize> = const { Cell::new(0) };
+    }
+
+    #[test]
+    fn output_dir_keeps_existing_files_without_force() {
+        let dir = tempfile::tempdir().unwrap();
+        let existing = dir.path().join("password-002.txt");
+        fs::write(&existing, "old").unwrap();
+
+        let passwords = ["a".repeat(16), "b".repeat(16), "c".repeat(16)];
+        assert!(matches!(
+            write_files(dir.path(), &passwords, false, false),
+            Err(AppError::Runtime(_))
+        ));
+        // Checked before anything is written, so there's no partial batch.
+        assert!(!dir.path().join("password-001.txt").exists());
+        assert_eq!(fs::read_to_string(&existing).unwrap(), "old");
+
+        assert!(write_files(dir.path(), &passwords, true, false).is_ok());
+        assert_eq!(fs::read_to_string(&existing).unwrap(), passwords[1]);
+    }
+
+    #[test]
+    fn erase_contents_overwrites_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("password-001.txt");
+        let old = "x".repeat(10_000);
+        fs::write(&path, &old).unwrap();
+
+        erase_contents(&path).unwrap();
+        let erased = fs::read(&path).unwrap();
+        assert_eq!(erased.len(), old.len());
+        assert_ne!(erased, old.as_bytes());
+    }
+
+    #[test]
+    fn secure_erase_erases_before_rotating() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("password-001.txt");
+        fs::write(&path, "old password").unwrap();
+        let passwords = ["n3wPassw0rd!xyz9".to_string()];
+
+        let before = ERASED.with(Cell::get);
+        assert!(write_files(dir.path(), &passwords, true, false).is_ok());
+        assert_eq!(ERASED.with(Cell::get), before);
+
+        fs::write(&path, "old password").unwrap();
+        assert!(write_files(dir.path(), &passwords, true, true).is_ok());
+        assert_eq!(ERASED.with(Cell::get), before + 1);
+        assert_eq!(fs::read_to_string(&path).unwrap(), passwords[0]);
+    }
+
+    #[test]
+    fn csv_header_lists_the_schema_properties_in_order() {
+        let schema: serde_json::Value = serde_json::from_str(&csv_schema()).unwrap();
+        let properties: Vec<&String> = schema["properties"].as_object().unwrap().keys().collect();
+        let header = |generated_at| {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            writer
+                .serialize(CsvRow {
+                    index: 1,
+                    password: "a,b",
+                    length: 3,
+                    entropy_bits: 4.2,
+                    generated_at,
+                })
+                .unwrap();
+            let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+            output.lines().next().unwrap().to_string()
+        };
+        let with_time = properties
+            .iter()
+            .map(|name| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(header(Some("2024-05-01T09:30:00Z")), with_time.join(","));
+        assert_eq!(header(None), with_time[..4].join(","));
+        assert_eq!(
+            schema["required"],
+            serde_json::json!(["index", "password", "length", "entropy_bits"])
+        );
+    }
+
+    #[test]
+    fn clipboard_payload_puts_one_password_per_line() {
+        let passwords: Vec<String> = (0..5).map(|i| format!("password-{}", i)).collect();
+        let payload = clipboard_payload(&passwords);
+        assert_eq!(payload.lines().count(), 5);
+        assert_eq!(payload.split('\n').collect::<Vec<_>>(), passwords);
+        assert!(!payload.ends_with('\n'));
+
+        assert_eq!(clipboard_payload(&passwords[..1]), "password-0");
+    }
+}
@@ -0,0 +1,407 @@
+//! `passphrase`: random words from the embedded EFF wordlist.
+
+use clap::builder::ArgPredicate;
+use clap::{Args, ValueEnum};
+use password_generator_rust::constraints::DEFAULT_MAX_RETRIES;
+use password_generator_rust::words::{
+    CaseMode, PassphraseDraw, draw_passphrase, draw_passphrase_with_separators, generate_diceware,
+    generate_passphrase_with_case, generate_passphrase_with_separators, generate_word_digits,
+    passphrase_entropy_bits, separators_entropy_bits, word_digits_entropy_bits, wordlist,
+};
+
+use super::{AppError, Logger, OutputArgs, warn_if_weak};
+
+#[derive(Args, Debug)]
+pub(super) struct PassphraseArgs {
+    /// Number of words [default with --xkcd: 4]
+    #[arg(
+        short,
+        long,
+        default_value_t = 6,
+        default_value_if("xkcd", ArgPredicate::IsPresent, "4")
+    )]
+    words: usize,
+
+    /// Use as many words as it takes to reach BITS of entropy, instead of --words
+    /// Counts the extra bits from --case-mode, --separators and --word-digits
+    #[arg(long, value_name = "BITS", conflicts_with = "words")]
+    passphrase_entropy_target: Option<f64>,
+
+    /// Separator placed between words [default with --xkcd: none]
+    #[arg(
+        short,
+        long,
+        default_value = "-",
+        default_value_if("xkcd", ArgPredicate::IsPresent, "")
+    )]
+    separator: String,
+
+    /// The classic "correcthorsebatterystaple": four lowercase words, no separator
+    /// --words and --separator still apply
+    #[arg(long, conflicts_with_all = ["separators", "case_mode", "diceware_numbered", "word_digits"])]
+    xkcd: bool,
+
+    /// Join each pair of words with one character picked at random from CHARS, e.g. `-_.`
+    /// Each junction adds log2(distinct CHARS) bits
+    #[arg(long, value_name = "CHARS", allow_hyphen_values = true, conflicts_with_all = ["separator", "diceware_numbered"])]
+    separators: Option<String>,
+
+    /// How words are capitalized; `random` adds log2(3) ≈ 1.6 bits per word
+    #[arg(long, value_enum, default_value_t = CaseModeArg::Lower)]
+    case_mode: CaseModeArg,
+
+    /// Also list each word with its five-digit diceware roll on stderr, e.g. `42613 correct`
+    /// The rolls are the same draws that chose the words
+    #[arg(long)]
+    diceware_numbered: bool,
+
+    /// Title-case every word and follow it with a random digit, with no separators,
+    /// e.g. `Horse7Battery3Staple9`
+    /// Each digit adds log2(10) ≈ 3.3 bits
+    #[arg(
+        long,
+        visible_alias = "wordcase-separator-digits",
+        conflicts_with_all = ["separator", "separators", "case_mode", "diceware_numbered"]
+    )]
+    word_digits: bool,
+
+    /// Append one random symbol to a --word-digits passphrase, e.g. `Horse7Battery3Staple9!`
+    #[arg(long, requires = "word_digits")]
+    trailing_symbol: bool,
+
+    #[command(flatten)]
+    output: OutputArgs,
+
+    /// Warn on stderr when the passphrase's entropy falls below this many bits
+    #[arg(long, value_name = "BITS", default_value_t = 60.0)]
+    warn_below: f64,
+
+    /// Refuse to generate when the configuration provides fewer than BITS of entropy
+    #[arg(long, value_name = "BITS")]
+    min_entropy: Option<f64>,
+
+    /// With --min-entropy, redraw a passphrase whose random case or separators came
+    /// out uniform (every word in one case, one separator throughout), which earns
+    /// them no bits; only --case-mode random and --separators vary from draw to draw
+    #[arg(long, requires = "min_entropy", conflicts_with_all = ["xkcd", "word_digits", "diceware_numbered"])]
+    regen_on_weak: bool,
+
+    /// Fail instead of printing a passphrase that is still short of --min-entropy
+    /// after --regen-on-weak's attempts
+    #[arg(long, requires = "regen_on_weak")]
+    strict: bool,
+}
+
+/// Most words `--passphrase-entropy-target` may pick, so an absurd target
+/// doesn't produce a book.
+const TARGET_MAX_WORDS: usize = 100;
+
+/// Capitalization modes selectable with `--case-mode`.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum CaseModeArg {
+    /// all lowercase (no extra entropy)
+    Lower,
+    /// ALL UPPERCASE (no extra entropy)
+    Upper,
+    /// Capitalize Each Word (no extra entropy)
+    Title,
+    /// Pick lower, upper or title case per word
+    Random,
+}
+
+impl CaseModeArg {
+    fn case_mode(self) -> CaseMode {
+        match self {
+            CaseModeArg::Lower => CaseMode::Lower,
+            CaseModeArg::Upper => CaseMode::Upper,
+            CaseModeArg::Title => CaseMode::Title,
+            CaseModeArg::Random => CaseMode::Random,
+        }
+    }
+}
+
+pub(super) fn validate(args: &PassphraseArgs) -> Result<(), String> {
+    if args.words == 0 {
+        return Err("Passphrase must contain at least one word.".to_string());
+    }
+    if let Some(target) = args.passphrase_entropy_target {
+        if !(target.is_finite() && target > 0.0) {
+            return Err(
+                "--passphrase-entropy-target must be a positive number of bits.".to_string(),
+            );
+        }
+        if words_for_bits(args, target).is_none() {
+            return Err(format!(
+                "--passphrase-entropy-target {} needs more than {} words.",
+                target, TARGET_MAX_WORDS
+            ));
+        }
+    }
+    if let Some(separators) = &args.separators {
+        if separators.is_empty() {
+            return Err("--separators must contain at least one character.".to_string());
+        }
+        if separators.contains(['\n', '\r']) {
+            return Err("--separators must not contain newlines.".to_string());
+        }
+    }
+    if let Some(bits) = args.min_entropy
+        && (!bits.is_finite() || bits < 0.0)
+    {
+        return Err("--min-entropy must be a non-negative number.".to_string());
+    }
+    let random_case = matches!(args.case_mode, CaseModeArg::Random);
+    let random_separators = args
+        .separators
+        .as_ref()
+        .is_some_and(|set| separators_entropy_bits(2, &set.chars().collect::<Vec<_>>()) > 0.0);
+    if args.regen_on_weak && !random_case && !random_separators {
+        return Err(
+            "--regen-on-weak only applies with --case-mode random or --separators of two or more characters; any other passphrase has the same entropy every time.".to_string(),
+        );
+    }
+    Ok(())
+}
+
+/// Entropy in bits of a `words`-word passphrase with the other flags in `args`.
+fn entropy_bits(args: &PassphraseArgs, words: usize) -> f64 {
+    if args.word_digits {
+        return word_digits_entropy_bits(words, args.trailing_symbol);
+    }
+    let separators: Option<Vec<char>> = args.separators.as_ref().map(|s| s.chars().collect());
+    passphrase_entropy_bits(words)
+        + words as f64 * args.case_mode.case_mode().entropy_bits_per_word()
+        + separators
+            .as_ref()
+            .map_or(0.0, |set| separators_entropy_bits(words, set))
+}
+
+/// The fewest words that reach `bits` of entropy, or `None` if that takes
+/// more than [`TARGET_MAX_WORDS`].
+fn words_for_bits(args: &PassphraseArgs, bits: f64) -> Option<usize> {
+    (1..=TARGET_MAX_WORDS).find(|&words| entropy_bits(args, words) >= bits)
+}
+
+pub(super) fn run(mut args: PassphraseArgs) -> Result<(), AppError> {
+    let log = Logger::new(&args.output);
+
+    if let Some(target) = args.passphrase_entropy_target {
+        args.words = words_for_bits(&args, target).expect("validated above");
+        // The entropy line below reports what they add up to.
+        log.info(format!("Words: {}", args.words));
+    }
+
+    let bits = entropy_bits(&args, args.words);
+    if let Some(required) = args.min_entropy
+        && bits < required
+    {
+        return Err(AppError::Runtime(format!(
+            "This configuration provides only {:.1} bits of entropy; --min-entropy requires {} bits. Add words or use --passphrase-entropy-target {}.",
+            bits, required, required
+        )));
+    }
+    if args.word_digits {
+        log.info(format!("Entropy: {:.1} bits", bits));
+        warn_if_weak(log, bits, args.warn_below);
+        println!("{}", generate_word_digits(args.words, args.trailing_symbol));
+        return Ok(());
+    }
+
+    let case = args.case_mode.case_mode();
+    let separators: Option<Vec<char>> = args.separators.as_ref().map(|s| s.chars().collect());
+    if args.xkcd {
+        log.info(format!(
+            "Entropy: {:.1} bits ({} words from a {}-word list)",
+            bits,
+            args.words,
+            wordlist().len()
+        ));
+    } else {
+        log.info(format!("Entropy: {:.1} bits", bits));
+    }
+    warn_if_weak(log, bits, args.warn_below);
+
+    if args.regen_on_weak {
+        let required = args
+            .min_entropy
+            .expect("--regen-on-weak requires --min-entropy");
+        let draw = || match &separators {
+            Some(set) => draw_passphrase_with_separators(args.words, set, case),
+            None => draw_passphrase(args.words, &args.separator, case),
+        };
+        let passphrase = regenerate(required, args.strict, log, draw)?;
+        println!("{}", passphrase.passphrase);
+        return Ok(());
+    }
+
+    if let Some(set) = &separators {
+        println!(
+            "{}",
+            generate_passphrase_with_separators(args.words, set, case)
+        );
+        return Ok(());
+    }
+
+    if args.diceware_numbered {
+        let (passphrase, words) = generate_diceware(args.words, &args.separator, case);
+        for entry in &words {
+            // Explicitly requested, so shown even under --quiet.
+            eprintln!("{} {}", entry.roll, entry.word);
+        }
+        println!("{}", passphrase);
+        return Ok(());
+    }
+
+    println!(
+        "{}",
+        generate_passphrase_with_case(args.words, &args.separator, case)
+    );
+    Ok(())
+}
+
+/// `--regen-on-weak`: draws until a passphrase's
+/// [structural entropy](PassphraseDraw::structural_entropy_bits) reaches
+/// `required` bits, at most [`DEFAULT_MAX_RETRIES`] times.
+///
+/// If every draw falls short, the last one is returned with a warning, or
+/// with `strict` the run fails.
+fn regenerate(
+    required: f64,
+    strict: bool,
+    log: Logger,
+    mut draw: impl FnMut() -> PassphraseDraw,
+) -> Result<PassphraseDraw, AppError> {
+    for attempt in 1..=DEFAULT_MAX_RETRIES {
+        let passphrase = draw();
+        let bits = passphrase.structural_entropy_bits();
+        if bits >= required {
+            if attempt > 1 {
+                log.verbose(format!("Regenerated {} weak passphrase(s)", attempt - 1));
+            }
+            return Ok(passphrase);
+        }
+        if attempt == DEFAULT_MAX_RETRIES {
+            let message = format!(
+                "--regen-on-weak: no passphrase reached --min-entropy {} bits in {} attempts",
+                required, DEFAULT_MAX_RETRIES
+            );
+            if strict {
+                return Err(AppError::Runtime(format!("{}.", message)));
+            }
+            log.warn(format!("{}; printing one with {:.1} bits.", message, bits));
+            return Ok(passphrase);
+        }
+    }
+    unreachable!("DEFAULT_MAX_RETRIES is at least 1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Command;
+    use crate::cli::test_support::parse;
+
+    fn passphrase_args(argv: &[&str]) -> PassphraseArgs {
+        let argv: Vec<&str> = std::iter::once("passphrase")
+            .chain(argv.iter().copied())
+            .collect();
+        match parse(&argv).expect("the command line parses").command {
+            Some(Command::Passphrase(args)) => args,
+            other => panic!("expected passphrase, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn regen_on_weak_needs_a_varying_option() {
+        for argv in [
+            &["--min-entropy", "50", "--regen-on-weak"][..],
+            &[
+                "--min-entropy",
+                "50",
+                "--regen-on-weak",
+                "--case-mode",
+                "title",
+            ],
+            &[
+                "--min-entropy",
+                "50",
+                "--regen-on-weak",
+                "--separators",
+                "--",
+            ],
+        ] {
+            let message = validate(&passphrase_args(argv)).unwrap_err();
+            assert!(
+                message.contains("--regen-on-weak only applies"),
+                "{:?}",
+                argv
+            );
+        }
+        for argv in [
+            &[
+                "--min-entropy",
+                "50",
+                "--regen-on-weak",
+                "--case-mode",
+                "random",
+            ][..],
+            &[
+                "--min-entropy",
+                "50",
+                "--regen-on-weak",
+                "--separators",
+                "-_",
+            ],
+        ] {
+            assert_eq!(validate(&passphrase_args(argv)), Ok(()), "{:?}", argv);
+        }
+    }
+
+    #[test]
+    fn regen_on_weak_requires_min_entropy() {
+        let argv = ["passphrase", "--regen-on-weak", "--case-mode", "random"];
+        assert!(parse(&argv).is_err());
+    }
+
+    #[test]
+    fn regenerates_until_case_and_separators_vary() {
+        let args = passphrase_args(&[]);
+        let log = Logger::new(&args.output);
+        // Three words in random case with two separators: uniform draws are common.
+        let full = passphrase_entropy_bits(3)
+            + 3.0 * CaseMode::Random.entropy_bits_per_word()
+            + separators_entropy_bits(3, &['-', '_']);
+        for _ in 0..50 {
+            let draw = || draw_passphrase_with_separators(3, &['-', '_'], CaseMode::Random);
+            let passphrase = regenerate(full, true, log, draw).unwrap();
+            assert!(passphrase.cases.iter().any(|&c| c != passphrase.cases[0]));
+            assert!(
+                passphrase
+                    .junctions
+                    .iter()
+                    .any(|&c| c != passphrase.junctions[0])
+            );
+        }
+    }
+
+    #[test]
+    fn unmet_regen_warns_or_fails_under_strict() {
+        let args = passphrase_args(&[]);
+        let log = Logger::new(&args.output);
+        let draw = || draw_passphrase(2, "-", CaseMode::Random);
+        assert!(regenerate(1000.0, false, log, draw).is_ok());
+        match regenerate(1000.0, true, log, draw) {
+            Err(AppError::Runtime(message)) => {
+                assert!(message.contains("no passphrase reached --min-entropy 1000 bits"))
+            }
+            other => panic!("expected a runtime error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unmet_min_entropy_is_a_runtime_error() {
+        let args = passphrase_args(&["--words", "2", "--min-entropy", "100"]);
+        assert_eq!(validate(&args), Ok(()));
+        assert!(matches!(run(args), Err(AppError::Runtime(_))));
+    }
+}
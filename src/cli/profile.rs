@@ -0,0 +1,138 @@
+//! `--profile`: named sets of `generate` flags shared through a JSON file.
+//!
+//! A profile file maps each profile name to the flags it sets, keyed by their
+//! long names without the dashes:
+//!
+//! ```json
+//! {
+//!   "team": { "length": 20, "exclude": "O0", "no-edge-symbol": true, "require-from": ["@#$"] }
+//! }
+//! ```
+//!
+//! Selecting a profile turns its settings into the defaults of those flags
+//! before the command line is parsed, so flags and environment variables
+//! given explicitly still win and every setting goes through the same
+//! validation as the flag itself.
+
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use clap::Command;
+use serde_json::Value;
+
+/// The settings of one profile, by flag name.
+type Profile = BTreeMap<String, Value>;
+
+/// The per-user profile file location.
+///
+/// Uses `$XDG_CONFIG_HOME`, then `~/.config`, then `%APPDATA%`.
+fn default_path() -> Option<PathBuf> {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))?;
+    Some(
+        config_dir
+            .join("password-generator-rust")
+            .join("profiles.json"),
+    )
+}
+
+/// Finds the value of `--{flag} VALUE` or `--{flag}=VALUE` in `argv`.
+///
+/// The profile has to be known before clap parses anything, so this scans the
+/// raw arguments; clap still parses (and checks) the flag afterwards.
+fn find_flag(argv: &[OsString], flag: &str) -> Option<String> {
+    let long = format!("--{}", flag);
+    let mut args = argv.iter().map(|arg| arg.to_string_lossy());
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        if arg == long {
+            return args.next().map(|value| value.into_owned());
+        }
+        if let Some(value) = arg.strip_prefix(&format!("{}=", long)) {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+/// Applies the `--profile` named in `argv`, if any, to `command` and its
+/// `generate` subcommand.
+pub(super) fn apply(command: Command, argv: &[OsString]) -> Result<Command, String> {
+    let Some(name) = find_flag(argv, "profile") else {
+        return Ok(command);
+    };
+    let path = match find_flag(argv, "profile-file") {
+        Some(path) => PathBuf::from(path),
+        None => default_path().ok_or("No profile file location; pass --profile-file.")?,
+    };
+    let profile = load(&path, &name)?;
+
+    let command = set_defaults(command, &profile, &name)?;
+    let generate = command
+        .find_subcommand("generate")
+        .cloned()
+        .expect("generate is a subcommand");
+    let generate = set_defaults(generate, &profile, &name)?;
+    Ok(command.mut_subcommand("generate", |_| generate))
+}
+
+/// Reads profile `name` from the file at `path`.
+fn load(path: &Path, name: &str) -> Result<Profile, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|err| format!("Failed to read profile file {}: {}", path.display(), err))?;
+    let mut profiles: BTreeMap<String, Profile> = serde_json::from_str(&contents)
+        .map_err(|err| format!("Invalid profile file {}: {}", path.display(), err))?;
+    profiles.remove(name).ok_or_else(|| {
+        let known: Vec<&str> = profiles.keys().map(String::as_str).collect();
+        format!(
+            "Unknown profile '{}' in {}; it defines: {}",
+            name,
+            path.display(),
+            if known.is_empty() {
+                "none".to_string()
+            } else {
+                known.join(", ")
+            }
+        )
+    })
+}
+
+/// Makes every setting of `profile` the default of the flag it names.
+fn set_defaults(mut command: Command, profile: &Profile, name: &str) -> Result<Command, String> {
+    for (flag, value) in profile {
+        let id = command
+            .get_arguments()
+            .find(|arg| arg.get_long() == Some(flag.as_str()))
+            .filter(|arg| !matches!(arg.get_id().as_str(), "profile" | "profile_file"))
+            .map(|arg| arg.get_id().clone())
+            .ok_or_else(|| format!("Profile '{}' sets unknown flag '{}'.", name, flag))?;
+        let values = match value {
+            Value::Array(items) => items
+                .iter()
+                .map(|item| scalar(item, name, flag))
+                .collect::<Result<Vec<_>, _>>()?,
+            _ => vec![scalar(value, name, flag)?],
+        };
+        command = command.mut_arg(id, |arg| arg.default_values(values));
+    }
+    Ok(command)
+}
+
+/// The command-line spelling of one JSON setting.
+fn scalar(value: &Value, name: &str, flag: &str) -> Result<String, String> {
+    match value {
+        Value::String(text) => Ok(text.clone()),
+        Value::Bool(set) => Ok(set.to_string()),
+        Value::Number(number) => Ok(number.to_string()),
+        _ => Err(format!(
+            "Profile '{}' sets '{}' to {}; use a string, number, boolean or a list of them.",
+            name, flag, value
+        )),
+    }
+}
@@ -0,0 +1,36 @@
+//! `selftest`: sanity-checks the operating system's RNG.
+
+use clap::Args;
+use password_generator_rust::selftest::{DEFAULT_SAMPLE_SIZE, check_sample};
+use rand::RngCore;
+use rand::rngs::OsRng;
+
+use super::{AppError, Logger, OutputArgs};
+
+#[derive(Args, Debug)]
+pub(super) struct SelftestArgs {
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+pub(super) fn run(args: SelftestArgs) -> Result<(), AppError> {
+    let log = Logger::new(&args.output);
+
+    let mut sample = vec![0u8; DEFAULT_SAMPLE_SIZE];
+    OsRng.fill_bytes(&mut sample);
+    log.verbose(format!("Sampled {} bytes from OsRng", sample.len()));
+
+    let checks = check_sample(&sample);
+    for check in &checks {
+        let status = if check.passed { "PASS" } else { "FAIL" };
+        println!("{}  {}: {}", status, check.name, check.detail);
+    }
+
+    if checks.iter().all(|check| check.passed) {
+        println!("Self-test passed.");
+        Ok(())
+    } else {
+        println!("Self-test FAILED: the system RNG may be misconfigured.");
+        Err(AppError::CheckFailed)
+    }
+}
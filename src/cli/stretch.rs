@@ -0,0 +1,49 @@
+//! `stretch`: a deterministic password from a short memorable phrase.
+
+use clap::Args;
+use password_generator_rust::derive::{DEFAULT_STRETCH_SALT, stretch_password};
+
+use super::{
+    AppError, CharsetArgs, DEFAULT_MAX_LENGTH, charset_or_exit, read_secret, validate_charset,
+    validate_length,
+};
+
+#[derive(Args, Debug)]
+pub(super) struct StretchArgs {
+    /// Salt mixed into the derivation; a personal one defeats precomputed guesses
+    /// Changing it gives an unrelated password
+    #[arg(long, default_value = DEFAULT_STRETCH_SALT)]
+    salt: String,
+
+    /// Password length
+    #[arg(short, long, default_value_t = 20)]
+    length: usize,
+
+    #[command(flatten)]
+    charset: CharsetArgs,
+}
+
+pub(super) fn validate(args: &StretchArgs) -> Result<(), String> {
+    if args.salt.is_empty() {
+        return Err("--salt must not be empty.".to_string());
+    }
+    validate_length(args.length, DEFAULT_MAX_LENGTH)?;
+    validate_charset(&args.charset)?;
+    Ok(())
+}
+
+pub(super) fn run(args: StretchArgs) -> Result<(), AppError> {
+    let phrase = read_secret("Phrase: ").map_err(|source| AppError::Io {
+        context: "Failed to read phrase".to_string(),
+        source,
+    })?;
+    if phrase.is_empty() {
+        return Err(AppError::Runtime("Phrase must not be empty.".to_string()));
+    }
+
+    let charset = charset_or_exit(&args.charset);
+    let password = stretch_password(phrase.as_bytes(), &args.salt, args.length, &charset)
+        .map_err(|err| AppError::Runtime(format!("Key derivation failed: {}", err)))?;
+    println!("{}", password);
+    Ok(())
+}
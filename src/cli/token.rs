@@ -0,0 +1,65 @@
+//! `token`: raw random bytes in a text encoding.
+
+use clap::{Args, ValueEnum};
+use password_generator_rust::token::{Encoding, generate_token};
+
+use super::{Logger, OutputArgs, warn_if_weak};
+
+#[derive(Args, Debug)]
+pub(super) struct TokenArgs {
+    /// Number of random bytes (each carries 8 bits of entropy)
+    #[arg(short, long, default_value_t = 32)]
+    bytes: usize,
+
+    /// Text encoding of the token
+    #[arg(short, long, value_enum, default_value_t = EncodingArg::Hex)]
+    encoding: EncodingArg,
+
+    #[command(flatten)]
+    output: OutputArgs,
+
+    /// Warn on stderr when the token's entropy falls below this many bits
+    #[arg(long, value_name = "BITS", default_value_t = 60.0)]
+    warn_below: f64,
+}
+
+/// Token encodings selectable with `--encoding`.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum EncodingArg {
+    /// Lowercase hexadecimal
+    Hex,
+    /// Standard base64 with padding
+    Base64,
+    /// URL-safe base64 without padding
+    Base64url,
+    /// RFC 4648 base32 without padding
+    Base32,
+}
+
+impl EncodingArg {
+    fn encoding(self) -> Encoding {
+        match self {
+            EncodingArg::Hex => Encoding::Hex,
+            EncodingArg::Base64 => Encoding::Base64,
+            EncodingArg::Base64url => Encoding::Base64Url,
+            EncodingArg::Base32 => Encoding::Base32,
+        }
+    }
+}
+
+pub(super) fn validate(args: &TokenArgs) -> Result<(), String> {
+    if args.bytes == 0 {
+        return Err("Token must contain at least one byte.".to_string());
+    }
+    Ok(())
+}
+
+pub(super) fn run(args: TokenArgs) {
+    let log = Logger::new(&args.output);
+
+    let bits = (args.bytes * 8) as f64;
+    log.verbose(format!("Entropy: {} bits", bits));
+    warn_if_weak(log, bits, args.warn_below);
+
+    println!("{}", generate_token(args.bytes, args.encoding.encoding()));
+}
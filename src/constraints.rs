@@ -0,0 +1,265 @@
+//! Checks applied to generated candidates by the constraint flags.
+//!
+//! Constraints are enforced by rejection: a candidate that fails is discarded
+//! and a fresh one is drawn. Every surviving password is therefore still
+//! uniformly distributed over the passwords that satisfy the constraint, but
+//! the constraint itself shrinks the space and so lowers the real entropy
+//! below what `entropy_bits` reports for the unconstrained configuration.
+//!
+//! Every constraint-based feature goes through [`generate_with_constraints`],
+//! or [`try_generate_with_constraints`] when drawing itself can fail, so they
+//! all share one retry mechanism and one failure mode.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::charset::class_of;
+
+/// Default number of candidates drawn before giving up on a set of constraints.
+pub const DEFAULT_MAX_RETRIES: usize = 100;
+
+/// Draws candidates from `generate` until one passes `accept`, trying at most
+/// `max_retries` times.
+///
+/// Returns the accepted candidate together with the number of attempts it
+/// took, or `None` if every attempt was rejected. That usually means the
+/// constraints are too strict for the requested length.
+///
+/// ```
+/// use password_generator_rust::constraints::{generate_with_constraints, max_run};
+/// use password_generator_rust::generate_password;
+///
+/// let (password, attempts) =
+///     generate_with_constraints(100, || generate_password(8, b"ab"), |p| max_run(p) <= 2)
+///         .expect("satisfiable");
+/// assert!(max_run(&password) <= 2);
+/// assert!((1..=100).contains(&attempts));
+///
+/// assert!(generate_with_constraints(5, || generate_password(8, b"a"), |p| max_run(p) <= 2).is_none());
+/// ```
+pub fn generate_with_constraints(
+    max_retries: usize,
+    mut generate: impl FnMut() -> String,
+    mut accept: impl FnMut(&str) -> bool,
+) -> Option<(String, usize)> {
+    (1..=max_retries).find_map(|attempt| {
+        let candidate = generate();
+        accept(&candidate).then_some((candidate, attempt))
+    })
+}
+
+/// Like [`generate_with_constraints`], for a `generate` that can fail.
+///
+/// The first error from `generate` is returned as is; no further candidates
+/// are drawn.
+///
+/// ```
+/// use password_generator_rust::constraints::try_generate_with_constraints;
+///
+/// let found = try_generate_with_constraints(3, || Ok::<_, String>("abc".to_string()), |p| p == "abc");
+/// assert_eq!(found, Ok(Some(("abc".to_string(), 1))));
+///
+/// let failed = try_generate_with_constraints(3, || Err::<String, _>("no charset"), |_| true);
+/// assert_eq!(failed, Err("no charset"));
+/// ```
+pub fn try_generate_with_constraints<E>(
+    max_retries: usize,
+    mut generate: impl FnMut() -> Result<String, E>,
+    mut accept: impl FnMut(&str) -> bool,
+) -> Result<Option<(String, usize)>, E> {
+    for attempt in 1..=max_retries {
+        let candidate = generate()?;
+        if accept(&candidate) {
+            return Ok(Some((candidate, attempt)));
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the length of the longest run of consecutive characters that share
+/// a character class.
+///
+/// Characters outside the built-in classes form their own class, so a run of
+/// them counts like any other run.
+pub fn max_class_run(password: &str) -> usize {
+    let mut longest = 0;
+    let mut current = 0;
+    let mut previous = None;
+
+    for c in password.chars() {
+        let class = if c.is_ascii() {
+            class_of(c as u8)
+        } else {
+            None
+        };
+        if current > 0 && class == previous {
+            current += 1;
+        } else {
+            current = 1;
+        }
+        previous = class;
+        longest = longest.max(current);
+    }
+    longest
+}
+
+/// Returns how often the most frequent character appears anywhere in
+/// `password`, consecutively or not.
+///
+/// ```
+/// use password_generator_rust::constraints::max_char_count;
+///
+/// assert_eq!(max_char_count("abc"), 1);
+/// assert_eq!(max_char_count("abacada"), 4);
+/// assert_eq!(max_char_count(""), 0);
+/// ```
+pub fn max_char_count(password: &str) -> usize {
+    let mut counts: HashMap<char, usize> = HashMap::new();
+    for c in password.chars() {
+        *counts.entry(c).or_default() += 1;
+    }
+    counts.into_values().max().unwrap_or(0)
+}
+
+/// Returns how many different characters `password` contains.
+///
+/// ```
+/// use password_generator_rust::constraints::distinct_chars;
+///
+/// assert_eq!(distinct_chars("aaaaaaaa"), 1);
+/// assert_eq!(distinct_chars("abacada"), 4);
+/// assert_eq!(distinct_chars(""), 0);
+/// ```
+pub fn distinct_chars(password: &str) -> usize {
+    password.chars().collect::<HashSet<_>>().len()
+}
+
+/// Returns the length of the longest run of one repeated character.
+///
+/// ```
+/// use password_generator_rust::constraints::max_run;
+///
+/// assert_eq!(max_run("abc"), 1);
+/// assert_eq!(max_run("xaaab"), 3);
+/// assert_eq!(max_run(""), 0);
+/// ```
+pub fn max_run(password: &str) -> usize {
+    let mut longest = 0;
+    let mut current = 0;
+    let mut previous = None;
+
+    for c in password.chars() {
+        if previous == Some(c) {
+            current += 1;
+        } else {
+            current = 1;
+        }
+        previous = Some(c);
+        longest = longest.max(current);
+    }
+    longest
+}
+
+/// US QWERTY rows, unshifted and shifted, as `(row offset, keys)`.
+///
+/// The offset is each row's horizontal stagger in key widths, so key `i` of a
+/// row sits at `offset + i`.
+const QWERTY_ROWS: [(f64, &str, &str); 4] = [
+    (0.0, "`1234567890-=", "~!@#$%^&*()_+"),
+    (0.5, "qwertyuiop[]\\", "QWERTYUIOP{}|"),
+    (0.75, "asdfghjkl;'", "ASDFGHJKL:\""),
+    (1.25, "zxcvbnm,./", "ZXCVBNM<>?"),
+];
+
+/// Returns the `(row, x)` position of the key that types `c`, ignoring Shift.
+fn key_position(c: char) -> Option<(usize, f64)> {
+    QWERTY_ROWS
+        .iter()
+        .enumerate()
+        .find_map(|(row, &(offset, plain, shifted))| {
+            plain
+                .chars()
+                .position(|k| k == c)
+                .or_else(|| shifted.chars().position(|k| k == c))
+                .map(|i| (row, offset + i as f64))
+        })
+}
+
+/// Returns `true` if `a` and `b` are typed with physically neighboring keys.
+fn keys_adjacent(a: char, b: char) -> bool {
+    let (Some((row_a, x_a)), Some((row_b, x_b))) = (key_position(a), key_position(b)) else {
+        return false;
+    };
+    let dx = (x_a - x_b).abs();
+    match row_a.abs_diff(row_b) {
+        0 => dx == 1.0,
+        1 => dx < 1.0,
+        _ => false,
+    }
+}
+
+/// Returns the fraction of consecutive character pairs in `password` that are
+/// neighbors on a US QWERTY keyboard, from 0.0 to 1.0.
+///
+/// Shifted characters count as their base key (`!` is the `1` key) and a key
+/// is not its own neighbor. Characters not on the keyboard never count as
+/// adjacent. Other layouts are not modeled.
+///
+/// ```
+/// use password_generator_rust::constraints::keyboard_adjacency_ratio;
+///
+/// assert_eq!(keyboard_adjacency_ratio("asdf"), 1.0);
+/// assert_eq!(keyboard_adjacency_ratio("qaz"), 1.0);
+/// assert_eq!(keyboard_adjacency_ratio("a1p/"), 0.0);
+/// assert_eq!(keyboard_adjacency_ratio("x"), 0.0);
+/// ```
+pub fn keyboard_adjacency_ratio(password: &str) -> f64 {
+    let chars: Vec<char> = password.chars().collect();
+    if chars.len() < 2 {
+        return 0.0;
+    }
+    let adjacent = chars
+        .windows(2)
+        .filter(|pair| keys_adjacent(pair[0], pair[1]))
+        .count();
+    adjacent as f64 / (chars.len() - 1) as f64
+}
+
+/// Groups of characters that are easily mistaken for one another.
+///
+/// Two characters are confusable when they are different members of the same
+/// group. The groups follow [`AMBIGUOUS_LATIN`](crate::charset::AMBIGUOUS_LATIN):
+///
+/// | Group | Look-alikes |
+/// |-------|-------------|
+/// | zero  | `0` `O` `o` `Q` `D` |
+/// | one   | `1` `l` `I` `i` `\|` `!` |
+/// | five  | `5` `S` |
+/// | two   | `2` `Z` |
+/// | eight | `8` `B` |
+pub const CONFUSABLE_GROUPS: [&str; 5] = ["0OoQD", "1lIi|!", "5S", "2Z", "8B"];
+
+/// Returns `true` if `a` and `b` are different characters that look alike.
+fn confusable(a: char, b: char) -> bool {
+    a != b
+        && CONFUSABLE_GROUPS
+            .iter()
+            .any(|group| group.contains(a) && group.contains(b))
+}
+
+/// Returns `true` if two confusable characters from [`CONFUSABLE_GROUPS`] sit
+/// next to each other in `password`.
+///
+/// A character next to itself (`00`) is not a confusable pair.
+///
+/// ```
+/// use password_generator_rust::constraints::has_similar_adjacent;
+///
+/// assert!(has_similar_adjacent("ab0Ocd"));
+/// assert!(has_similar_adjacent("l1"));
+/// assert!(!has_similar_adjacent("0a1O"));
+/// assert!(!has_similar_adjacent("00"));
+/// ```
+pub fn has_similar_adjacent(password: &str) -> bool {
+    let chars: Vec<char> = password.chars().collect();
+    chars.windows(2).any(|pair| confusable(pair[0], pair[1]))
+}
@@ -0,0 +1,169 @@
+//! Deterministic "site password" derivation.
+//!
+//! Regenerates the same strong password for a site from a master secret
+//! without storing anything, like a password manager's derived-password
+//! feature. Unlike the rest of the crate this does NOT use `OsRng`: the output
+//! is a pure function of the master secret, site and counter.
+//!
+//! [`stretch_password`] applies the same derivation to a short memorable
+//! phrase and a salt, with no notion of sites.
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use rand_chacha::ChaCha20Rng;
+use rand_chacha::rand_core::{RngCore, SeedableRng};
+
+/// Domain-separation prefix mixed into every salt.
+///
+/// Changing this (or any Argon2 parameter below) changes every derived
+/// password, so it is versioned.
+const SALT_PREFIX: &str = "password-generator-rust/derive/v1";
+
+/// Domain-separation prefix for [`stretch_password`], so a phrase stretched
+/// with some salt never gives the same key as a site derivation.
+const STRETCH_SALT_PREFIX: &str = "password-generator-rust/stretch/v1";
+
+/// The salt [`stretch_password`] callers use when the user supplies none.
+///
+/// A fixed salt means anyone can precompute guesses against it; a personal
+/// salt makes such tables useless.
+pub const DEFAULT_STRETCH_SALT: &str = "password-generator-rust";
+
+// Argon2id parameters are pinned explicitly rather than taken from the crate's
+// defaults, which may change between releases.
+const ARGON2_MEMORY_KIB: u32 = 64 * 1024;
+const ARGON2_ITERATIONS: u32 = 3;
+const ARGON2_PARALLELISM: u32 = 1;
+
+/// Derives a password for `site` from `master`.
+///
+/// # Arguments
+///
+/// * `master` - The master secret. Security rests entirely on its strength.
+/// * `site` - The site label, e.g. `example.com`.
+/// * `counter` - Bump this to rotate the password for a site.
+/// * `length` - The length of the password to derive.
+/// * `charset` - A slice of bytes representing the allowed characters.
+///
+/// # Security
+///
+/// - Argon2id stretches the master secret with a salt built from the site and
+///   counter, so each site gets an independent 32-byte key.
+/// - The key seeds a ChaCha20 keystream, which is consumed byte by byte.
+/// - Bytes are mapped onto the charset with rejection sampling: any byte at or
+///   above the largest multiple of `charset.len()` is discarded, so every
+///   character is exactly equally likely (no modulo bias).
+///
+/// # Examples
+///
+/// The same inputs always give the same password; changing any of them gives
+/// an unrelated one:
+///
+/// ```
+/// use password_generator_rust::derive::derive_password;
+///
+/// let charset = b"abcdefghijklmnopqrstuvwxyz0123456789";
+/// let first = derive_password(b"correct horse", "example.com", 1, 16, charset).unwrap();
+/// let again = derive_password(b"correct horse", "example.com", 1, 16, charset).unwrap();
+/// let rotated = derive_password(b"correct horse", "example.com", 2, 16, charset).unwrap();
+///
+/// assert_eq!(first, again);
+/// assert_ne!(first, rotated);
+/// assert_eq!(first.len(), 16);
+/// ```
+pub fn derive_password(
+    master: &[u8],
+    site: &str,
+    counter: u32,
+    length: usize,
+    charset: &[u8],
+) -> Result<String, argon2::Error> {
+    if charset.is_empty() {
+        return Ok(String::new());
+    }
+
+    let key = derive_key(master, &format!("{SALT_PREFIX}\0{site}\0{counter}"))?;
+    let mut stream = ChaCha20Rng::from_seed(key);
+    Ok(map_unbiased(&mut stream, length, charset))
+}
+
+/// Stretches a short memorable `phrase` into a password, deterministically.
+///
+/// The phrase goes through the same Argon2id derivation and unbiased charset
+/// mapping as [`derive_password`], salted with `salt` (see
+/// [`DEFAULT_STRETCH_SALT`]).
+///
+/// # Security
+///
+/// Stretching only makes each guess expensive. The result is never harder to
+/// guess than the phrase itself: a weak phrase gives a weak password, however
+/// long.
+///
+/// # Examples
+///
+/// ```
+/// use password_generator_rust::derive::{DEFAULT_STRETCH_SALT, derive_password, stretch_password};
+///
+/// let charset = b"abcdefghijklmnopqrstuvwxyz0123456789";
+/// let first = stretch_password(b"tea at noon", DEFAULT_STRETCH_SALT, 20, charset).unwrap();
+/// let again = stretch_password(b"tea at noon", DEFAULT_STRETCH_SALT, 20, charset).unwrap();
+/// let salted = stretch_password(b"tea at noon", "my own salt", 20, charset).unwrap();
+///
+/// assert_eq!(first, again);
+/// assert_ne!(first, salted);
+/// assert_eq!(first.len(), 20);
+/// assert!(first.bytes().all(|b| charset.contains(&b)));
+///
+/// // Distinct from a site derivation using the same secret.
+/// let site = derive_password(b"tea at noon", DEFAULT_STRETCH_SALT, 1, 20, charset).unwrap();
+/// assert_ne!(first, site);
+/// ```
+pub fn stretch_password(
+    phrase: &[u8],
+    salt: &str,
+    length: usize,
+    charset: &[u8],
+) -> Result<String, argon2::Error> {
+    if charset.is_empty() {
+        return Ok(String::new());
+    }
+
+    let key = derive_key(phrase, &format!("{STRETCH_SALT_PREFIX}\0{salt}"))?;
+    let mut stream = ChaCha20Rng::from_seed(key);
+    Ok(map_unbiased(&mut stream, length, charset))
+}
+
+/// Runs `secret` and `salt` through Argon2id with the pinned parameters.
+fn derive_key(secret: &[u8], salt: &str) -> Result<[u8; 32], argon2::Error> {
+    let params = Params::new(
+        ARGON2_MEMORY_KIB,
+        ARGON2_ITERATIONS,
+        ARGON2_PARALLELISM,
+        Some(32),
+    )?;
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+
+    let mut key = [0u8; 32];
+    argon2.hash_password_into(secret, salt.as_bytes(), &mut key)?;
+    Ok(key)
+}
+
+/// Maps bytes from `stream` onto `charset` without modulo bias.
+///
+/// `charset` must hold between 1 and 256 entries so each byte can address it.
+fn map_unbiased(stream: &mut impl RngCore, length: usize, charset: &[u8]) -> String {
+    debug_assert!(!charset.is_empty() && charset.len() <= 256);
+
+    // Largest multiple of the charset size that fits in a byte.
+    let limit = 256 - (256 % charset.len());
+
+    let mut byte = [0u8; 1];
+    let mut chosen = Vec::with_capacity(length);
+    while chosen.len() < length {
+        stream.fill_bytes(&mut byte);
+        let value = byte[0] as usize;
+        if value < limit {
+            chosen.push(charset[value % charset.len()] as char);
+        }
+    }
+    chosen.into_iter().collect()
+}
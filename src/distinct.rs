@@ -0,0 +1,71 @@
+//! Passwords in which no character appears twice.
+//!
+//! Redrawing candidates until no character repeats gets hopeless as the
+//! length approaches the charset size, so the characters are instead drawn
+//! without replacement: a shuffle of the charset, cut to length.
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+/// Generates a password of `length` distinct characters from `charset`, e.g.
+/// `k3Vq!` but never `k3Vk!`.
+///
+/// Duplicates in `charset` are ignored, so every distinct byte is equally
+/// likely at every position and every repetition-free password is equally
+/// likely overall, exactly as if candidates with a repeat had been redrawn.
+/// Returns `None` if `charset` has fewer than `length` distinct bytes.
+///
+/// ```
+/// use password_generator_rust::distinct::generate_distinct_with_rng;
+/// use rand::rngs::OsRng;
+///
+/// let password = generate_distinct_with_rng(&mut OsRng, 10, b"0123456789").unwrap();
+/// let mut sorted: Vec<u8> = password.bytes().collect();
+/// sorted.sort_unstable();
+/// assert_eq!(sorted, b"0123456789");
+///
+/// assert_eq!(generate_distinct_with_rng(&mut OsRng, 4, b"aabc"), None);
+/// ```
+pub fn generate_distinct_with_rng<R: Rng + ?Sized>(
+    rng: &mut R,
+    length: usize,
+    charset: &[u8],
+) -> Option<String> {
+    let mut distinct = charset.to_vec();
+    distinct.sort_unstable();
+    distinct.dedup();
+    if distinct.len() < length {
+        return None;
+    }
+
+    Some(
+        distinct
+            .choose_multiple(rng, length)
+            .map(|&b| b as char)
+            .collect(),
+    )
+}
+
+/// Entropy in bits of a password from [`generate_distinct_with_rng`]:
+/// `log2(n! / (n - length)!)` for `n` distinct characters.
+///
+/// Each position has one fewer character to choose from than the one
+/// before it, so this is below [`entropy_bits`](crate::entropy_bits) for
+/// the same length and grows slower the closer `length` gets to `n`.
+/// Returns `0.0` when no such password exists.
+///
+/// ```
+/// use password_generator_rust::distinct::distinct_entropy_bits;
+///
+/// // 10 * 9 * 8 choices for three distinct digits.
+/// assert!((distinct_entropy_bits(3, 10) - 720f64.log2()).abs() < 1e-9);
+/// assert_eq!(distinct_entropy_bits(11, 10), 0.0);
+/// ```
+pub fn distinct_entropy_bits(length: usize, charset_len: usize) -> f64 {
+    if length > charset_len {
+        return 0.0;
+    }
+    (charset_len - length + 1..=charset_len)
+        .map(|n| (n as f64).log2())
+        .sum()
+}
@@ -0,0 +1,127 @@
+//! C-compatible interface to the generator (enabled with the `ffi` feature).
+//!
+//! Build the shared library with `cargo build --release --features ffi` and link
+//! against `libpassword_generator_rust` from C or any language with a C FFI.
+
+use crate::options::Options;
+
+/// Exclude symbols from the password (mirrors `--no-symbols`).
+pub const PG_NO_SYMBOLS: u32 = 1 << 0;
+/// Exclude digits from the password (mirrors `--no-numbers`).
+pub const PG_NO_NUMBERS: u32 = 1 << 1;
+/// Use only letters (mirrors `--only-letters`).
+pub const PG_ONLY_LETTERS: u32 = 1 << 2;
+
+const PG_ALL_FLAGS: u32 = PG_NO_SYMBOLS | PG_NO_NUMBERS | PG_ONLY_LETTERS;
+
+/// The password was written successfully.
+pub const PG_OK: i32 = 0;
+/// `out` was a null pointer.
+pub const PG_ERR_NULL: i32 = -1;
+/// `out_len` is too small to hold the password and its NUL terminator.
+pub const PG_ERR_BUFFER_TOO_SMALL: i32 = -2;
+/// `length` is below the minimum of 8 characters enforced by the CLI.
+pub const PG_ERR_LENGTH: i32 = -3;
+/// `flags` contains unknown bits.
+pub const PG_ERR_FLAGS: i32 = -4;
+
+/// Generates a password into a caller-provided buffer.
+///
+/// On success, `length` password bytes followed by a NUL terminator are written
+/// to `out` and `PG_OK` is returned. The buffer must therefore be at least
+/// `length + 1` bytes long. On any error except `PG_ERR_NULL`, the first
+/// `out_len` bytes of `out` are zeroed and a negative status code is returned.
+///
+/// # Safety
+///
+/// `out` must either be null or point to at least `out_len` writable bytes.
+///
+/// # Examples
+///
+/// ```
+/// use password_generator_rust::ffi::{PG_NO_SYMBOLS, PG_OK, pg_generate};
+///
+/// let mut buf = [0u8; 17];
+/// let status = unsafe { pg_generate(16, PG_NO_SYMBOLS, buf.as_mut_ptr(), buf.len()) };
+/// assert_eq!(status, PG_OK);
+/// assert!(buf[..16].iter().all(u8::is_ascii_alphanumeric));
+/// assert_eq!(buf[16], 0);
+/// ```
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pg_generate(
+    length: usize,
+    flags: u32,
+    out: *mut u8,
+    out_len: usize,
+) -> i32 {
+    if out.is_null() {
+        return PG_ERR_NULL;
+    }
+
+    // SAFETY: the caller guarantees `out` points to `out_len` writable bytes.
+    let buf = unsafe { std::slice::from_raw_parts_mut(out, out_len) };
+
+    let status = write_password(length, flags, buf);
+    if status != PG_OK {
+        buf.fill(0);
+    }
+    status
+}
+
+fn write_password(length: usize, flags: u32, buf: &mut [u8]) -> i32 {
+    if flags & !PG_ALL_FLAGS != 0 {
+        return PG_ERR_FLAGS;
+    }
+    if length < 8 {
+        return PG_ERR_LENGTH;
+    }
+    if buf.len() <= length {
+        return PG_ERR_BUFFER_TOO_SMALL;
+    }
+
+    let only_letters = flags & PG_ONLY_LETTERS != 0;
+    let options = Options {
+        length,
+        digits: !only_letters && flags & PG_NO_NUMBERS == 0,
+        symbols: !only_letters && flags & PG_NO_SYMBOLS == 0,
+        ..Options::default()
+    };
+
+    let password = options.generate();
+    buf[..length].copy_from_slice(password.as_bytes());
+    buf[length] = 0;
+    PG_OK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Calls `pg_generate` on a buffer of `out_len` bytes pre-filled with
+    /// `0xFF`, returning the status and what the buffer holds afterwards.
+    fn generate_into_dirty_buffer(length: usize, flags: u32, out_len: usize) -> (i32, Vec<u8>) {
+        let mut buf = vec![0xFF; out_len];
+        let status = unsafe { pg_generate(length, flags, buf.as_mut_ptr(), buf.len()) };
+        (status, buf)
+    }
+
+    #[test]
+    fn errors_zero_the_whole_buffer() {
+        let cases = [
+            (16, 0, 16, PG_ERR_BUFFER_TOO_SMALL),
+            (16, 1 << 31, 17, PG_ERR_FLAGS),
+            (4, 0, 17, PG_ERR_LENGTH),
+        ];
+        for (length, flags, out_len, expected) in cases {
+            let (status, buf) = generate_into_dirty_buffer(length, flags, out_len);
+            assert_eq!(status, expected, "length {}, flags {:#x}", length, flags);
+            assert!(buf.iter().all(|&b| b == 0), "{:?}", buf);
+        }
+    }
+
+    #[test]
+    fn null_buffer_is_rejected() {
+        let status = unsafe { pg_generate(16, 0, std::ptr::null_mut(), 17) };
+        assert_eq!(status, PG_ERR_NULL);
+    }
+}
@@ -0,0 +1,100 @@
+//! Opt-in history of recently generated passwords.
+//!
+//! The history file stores one salted Argon2id hash per line in PHC string
+//! format. It never contains a plaintext password: a generated candidate is
+//! only ever compared against the stored hashes, so reading the file reveals
+//! nothing beyond how many passwords were recorded.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use argon2::Argon2;
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{self, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+
+/// Hashes of the most recently generated passwords, newest last.
+#[derive(Debug)]
+pub struct History {
+    path: PathBuf,
+    entries: Vec<String>,
+}
+
+impl History {
+    /// The per-user history file location.
+    ///
+    /// Uses `$XDG_DATA_HOME`, then `~/.local/share`, then `%APPDATA%`.
+    pub fn default_path() -> Option<PathBuf> {
+        let data_dir = std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))
+            .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))?;
+        Some(data_dir.join("password-generator-rust").join("history"))
+    }
+
+    /// Loads the history at `path`. A missing file is an empty history.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let entries = match fs::read_to_string(path) {
+            Ok(contents) => contents
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect(),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err),
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            entries,
+        })
+    }
+
+    /// Returns `true` if `password` matches any stored hash.
+    ///
+    /// Verification compares hashes in constant time. Malformed lines are
+    /// ignored rather than treated as a match.
+    pub fn contains(&self, password: &str) -> bool {
+        self.entries.iter().any(|entry| {
+            PasswordHash::new(entry).is_ok_and(|hash| {
+                Argon2::default()
+                    .verify_password(password.as_bytes(), &hash)
+                    .is_ok()
+            })
+        })
+    }
+
+    /// Appends a salted hash of `password`, keeping only the newest `capacity`
+    /// entries.
+    pub fn record(&mut self, password: &str, capacity: usize) -> password_hash::Result<()> {
+        let salt = SaltString::generate(&mut OsRng);
+        let hash = Argon2::default().hash_password(password.as_bytes(), &salt)?;
+        self.entries.push(hash.to_string());
+
+        let excess = self.entries.len().saturating_sub(capacity);
+        self.entries.drain(..excess);
+        Ok(())
+    }
+
+    /// Writes the history back to disk, creating parent directories as needed.
+    ///
+    /// On Unix the file is created with `0600` permissions.
+    pub fn save(&self) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        let mut file = options.open(&self.path)?;
+        for entry in &self.entries {
+            writeln!(file, "{}", entry)?;
+        }
+        Ok(())
+    }
+}
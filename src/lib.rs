@@ -0,0 +1,643 @@
+//! Secure password generation library.
+//!
+//! The CLI in `main.rs` is a thin wrapper around the functions exposed here, so
+//! library consumers get exactly the same generation logic as the binary.
+//!
+//! # Examples
+//!
+//! Build a custom charset from the same constants the CLI uses and generate a
+//! password from it:
+//!
+//! ```
+//! use password_generator_rust::charset::{DIGITS, UPPERCASE};
+//! use password_generator_rust::generate_password;
+//!
+//! let charset = [UPPERCASE, DIGITS].concat();
+//! let password = generate_password(20, &charset);
+//!
+//! assert_eq!(password.len(), 20);
+//! assert!(password.bytes().all(|b| charset.contains(&b)));
+//! ```
+
+use std::time::Duration;
+
+use rand::RngCore;
+use rand::rngs::OsRng;
+use rand::seq::SliceRandom; // Trait for choosing random elements from a slice
+use subtle::ConstantTimeEq;
+use unicode_normalization::UnicodeNormalization;
+
+pub mod alternate;
+pub mod audit;
+pub mod balanced;
+pub mod blocklist;
+pub mod charset;
+pub mod checksum;
+pub mod compat;
+pub mod constraints;
+pub mod derive;
+pub mod distinct;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod history;
+pub mod options;
+pub mod policy;
+pub mod positions;
+pub mod qr;
+pub mod rng;
+pub mod sections;
+pub mod selftest;
+pub mod shamir;
+pub mod strength;
+pub mod template;
+pub mod token;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod words;
+
+/// Generates a cryptographically secure password of the specified length.
+///
+/// # Arguments
+///
+/// * `length` - The length of the password to generate.
+/// * `charset` - A slice of bytes representing the allowed characters.
+///
+/// # Returns
+///
+/// A `String` containing the generated password. A `length` of zero or an
+/// empty `charset` yields an empty string without drawing any randomness.
+///
+/// # Security
+///
+/// - Uses `rand::rngs::OsRng` to ensure randomness is sourced from the operating system's
+///   CSPRNG (Cryptographically Secure Pseudo-Random Number Generator).
+/// - We explicitly avoid `rand::thread_rng` because while it is currently secure,
+///   `OsRng` is the most direct interface to the OS entropy source, minimizing user-space buffering
+///   or state that could theoretically be compromised or seeded poorly in some environments.
+/// - Maps bulk random bytes onto the charset with rejection sampling, which guarantees
+///   uniform distribution (no modulo bias). Charsets too large for a byte to index fall
+///   back to `SliceRandom::choose`, which is equally unbiased.
+///
+/// # Examples
+///
+/// Each byte is one character. Bytes above `0x7F` are read as Latin-1, so a
+/// custom charset never produces invalid UTF-8:
+///
+/// ```
+/// use password_generator_rust::generate_password;
+///
+/// assert_eq!(generate_password(4, b"x"), "xxxx");
+/// assert_eq!(generate_password(2, &[0xE9]), "éé");
+/// ```
+///
+/// The CLI enforces a minimum length, but the library accepts zero:
+///
+/// ```
+/// use password_generator_rust::generate_password;
+/// use password_generator_rust::charset::{LOWERCASE, SYMBOLS};
+///
+/// assert_eq!(generate_password(0, LOWERCASE), "");
+/// assert_eq!(generate_password(0, SYMBOLS), "");
+/// assert_eq!(generate_password(0, b""), "");
+/// ```
+pub fn generate_password(length: usize, charset: &[u8]) -> String {
+    // We use OsRng directly for cryptographic security.
+    generate_password_with_rng(&mut OsRng, length, charset)
+}
+
+/// Lazily generates `count` passwords of `length` characters from `charset`.
+///
+/// Each password is drawn with `OsRng` only when the iterator reaches it, so
+/// a large batch can be streamed into a writer or channel without holding
+/// every password in memory. Passwords are generated exactly like
+/// [`generate_password`].
+///
+/// # Examples
+///
+/// ```
+/// use password_generator_rust::generate_batch;
+///
+/// let passwords: Vec<String> = generate_batch(5, 12, b"abc123").collect();
+/// assert_eq!(passwords.len(), 5);
+/// assert!(passwords.iter().all(|p| p.len() == 12));
+///
+/// // Nothing is generated up front, so even an absurd count is fine as
+/// // long as only a few are taken.
+/// let first: Vec<String> = generate_batch(usize::MAX, 12, b"abc123").take(3).collect();
+/// assert_eq!(first.len(), 3);
+///
+/// assert_eq!(generate_batch(0, 12, b"abc123").count(), 0);
+/// ```
+pub fn generate_batch(count: usize, length: usize, charset: &[u8]) -> impl Iterator<Item = String> {
+    (0..count).map(move |_| generate_password(length, charset))
+}
+
+/// Like [`generate_password`], but draws from `rng` instead of `OsRng`.
+///
+/// The output is only as strong as `rng`; see [`rng::RngSource`] for the
+/// trade-offs. A seeded RNG makes the output reproducible:
+///
+/// ```
+/// use password_generator_rust::generate_password_with_rng;
+/// use password_generator_rust::rng::RngSource;
+///
+/// let mut a = RngSource::Seeded(42).build();
+/// let mut b = RngSource::Seeded(42).build();
+/// assert_eq!(
+///     generate_password_with_rng(&mut a, 16, b"abc123"),
+///     generate_password_with_rng(&mut b, 16, b"abc123"),
+/// );
+/// ```
+///
+/// A `length` of zero returns an empty string for any charset and never
+/// touches `rng`:
+///
+/// ```
+/// use password_generator_rust::generate_password_with_rng;
+/// use rand::RngCore;
+///
+/// struct Untouchable;
+///
+/// impl RngCore for Untouchable {
+///     fn next_u32(&mut self) -> u32 { unreachable!("RNG was called") }
+///     fn next_u64(&mut self) -> u64 { unreachable!("RNG was called") }
+///     fn fill_bytes(&mut self, _: &mut [u8]) { unreachable!("RNG was called") }
+///     fn try_fill_bytes(&mut self, _: &mut [u8]) -> Result<(), rand::Error> {
+///         unreachable!("RNG was called")
+///     }
+/// }
+///
+/// let huge: Vec<u8> = (0..=255).chain(0..=255).collect();
+/// for charset in [&b""[..], b"x", b"abc123", &huge] {
+///     assert_eq!(generate_password_with_rng(&mut Untouchable, 0, charset), "");
+/// }
+/// ```
+pub fn generate_password_with_rng<R: RngCore + ?Sized>(
+    rng: &mut R,
+    length: usize,
+    charset: &[u8],
+) -> String {
+    // Ensure we have a valid charset to avoid runtime panics, and don't draw
+    // randomness for nothing.
+    if charset.is_empty() || length == 0 {
+        return String::new();
+    }
+
+    // We collect the chosen bytes first and convert them to a String once.
+    let bytes = if charset.len() <= 256 {
+        fill_unbiased(rng, length, charset)
+    } else {
+        // Too many characters for one byte to address; choose one at a time.
+        (0..length)
+            .map(|_| *charset.choose(rng).expect("Charset must not be empty"))
+            .collect()
+    };
+    bytes_to_string(bytes)
+}
+
+/// Picks `length` characters from `charset` using bulk random bytes.
+///
+/// Requesting bytes in blocks with `fill_bytes` is much cheaper than one
+/// `choose` call per character. Each byte is mapped to a charset index with
+/// rejection sampling: bytes at or above the largest multiple of the charset
+/// size are discarded, so every character stays exactly equally likely (no
+/// modulo bias). A 64-character charset rejects nothing; the worst case
+/// (129 characters) rejects just under half.
+///
+/// `charset` must hold between 1 and 256 entries.
+fn fill_unbiased<R: RngCore + ?Sized>(rng: &mut R, length: usize, charset: &[u8]) -> Vec<u8> {
+    debug_assert!(!charset.is_empty() && charset.len() <= 256);
+
+    // Largest multiple of the charset size that fits in a byte.
+    let limit = 256 - (256 % charset.len());
+
+    let mut chosen = Vec::with_capacity(length);
+    let mut buf = [0u8; 64];
+    while chosen.len() < length {
+        // Request what is still missing plus some slack for rejected bytes.
+        let missing = length - chosen.len();
+        let request = (missing + missing / 2 + 4).min(buf.len());
+        rng.fill_bytes(&mut buf[..request]);
+
+        let accepted = buf[..request]
+            .iter()
+            .map(|&b| b as usize)
+            .filter(|&value| value < limit)
+            .map(|value| charset[value % charset.len()])
+            .take(missing);
+        chosen.extend(accepted);
+    }
+    chosen
+}
+
+/// Generates a password whose first and last characters are drawn from
+/// `edge_charset` while the interior is drawn from `charset`.
+///
+/// Many web forms reject passwords that start or end with a symbol; passing
+/// the alphanumeric subset as `edge_charset` satisfies them. Each position is
+/// still chosen uniformly and independently with `OsRng`, and the ends are not
+/// reshuffled afterwards. Returns an empty string if either charset is empty
+/// or `length` is zero; a `length` of one draws its only character from
+/// `edge_charset`.
+///
+/// # Examples
+///
+/// ```
+/// use password_generator_rust::charset::{DIGITS, LOWERCASE, SYMBOLS};
+/// use password_generator_rust::generate_password_with_edges;
+///
+/// let charset = [LOWERCASE, DIGITS, SYMBOLS].concat();
+/// let edges = [LOWERCASE, DIGITS].concat();
+/// let password = generate_password_with_edges(8, &charset, &edges);
+///
+/// let bytes = password.as_bytes();
+/// assert_eq!(bytes.len(), 8);
+/// assert!(bytes[0].is_ascii_alphanumeric() && bytes[7].is_ascii_alphanumeric());
+/// ```
+pub fn generate_password_with_edges(length: usize, charset: &[u8], edge_charset: &[u8]) -> String {
+    generate_password_with_edges_rng(&mut OsRng, length, charset, edge_charset)
+}
+
+/// Like [`generate_password_with_edges`], but draws from `rng` instead of `OsRng`.
+pub fn generate_password_with_edges_rng<R: RngCore + ?Sized>(
+    rng: &mut R,
+    length: usize,
+    charset: &[u8],
+    edge_charset: &[u8],
+) -> String {
+    if charset.is_empty() || edge_charset.is_empty() {
+        return String::new();
+    }
+
+    let bytes = (0..length)
+        .map(|i| {
+            let pool = if i == 0 || i + 1 == length {
+                edge_charset
+            } else {
+                charset
+            };
+            *pool.choose(rng).expect("Charset must not be empty")
+        })
+        .collect();
+    bytes_to_string(bytes)
+}
+
+/// Generates a password from a pool of arbitrary `char`s, such as a charset
+/// extended with emoji.
+///
+/// Unlike the byte-based functions, every entry may be any Unicode scalar
+/// value; each is chosen uniformly with `SliceRandom::choose`. Entropy is
+/// `length * log2(pool.len())` counted in chars, not bytes. Returns an empty
+/// string if `pool` is empty.
+///
+/// For ASCII charsets prefer [`generate_password_with_rng`]: its byte path
+/// draws randomness in bulk and is an order of magnitude faster
+/// (`cargo bench` compares the two).
+///
+/// ```
+/// use password_generator_rust::generate_password_from_chars;
+/// use rand::rngs::OsRng;
+///
+/// let password = generate_password_from_chars(&mut OsRng, 8, &['a', '😀']);
+/// assert_eq!(password.chars().count(), 8);
+/// ```
+pub fn generate_password_from_chars<R: RngCore + ?Sized>(
+    rng: &mut R,
+    length: usize,
+    pool: &[char],
+) -> String {
+    if pool.is_empty() {
+        return String::new();
+    }
+    (0..length)
+        .map(|_| *pool.choose(rng).expect("Pool must not be empty"))
+        .collect()
+}
+
+/// Generates a password of `length` chars containing at least one char from
+/// each of the `required` sets.
+///
+/// One char is drawn from each set and the remaining positions from `pool`,
+/// then all of them are shuffled with `rng`, so the placed chars land at
+/// random positions. Unlike redrawing until a candidate happens to qualify,
+/// this never fails however small the sets or short the password. The
+/// required chars are slightly over-represented compared to a uniform draw
+/// over all qualifying passwords. Returns `None` if `pool` or a set is empty,
+/// or if there are more sets than positions.
+///
+/// ```
+/// use password_generator_rust::generate_password_with_required;
+/// use rand::rngs::OsRng;
+///
+/// let required = [vec!['#'], vec!['7']];
+/// let password = generate_password_with_required(&mut OsRng, 8, &['a', 'b', '#', '7'], &required)
+///     .expect("two sets fit in eight positions");
+/// assert!(password.contains('#') && password.contains('7'));
+///
+/// assert_eq!(generate_password_with_required(&mut OsRng, 1, &['a'], &required), None);
+/// ```
+pub fn generate_password_with_required<R: RngCore + ?Sized>(
+    rng: &mut R,
+    length: usize,
+    pool: &[char],
+    required: &[Vec<char>],
+) -> Option<String> {
+    if pool.is_empty() || required.len() > length {
+        return None;
+    }
+    let mut chars = required
+        .iter()
+        .map(|set| set.choose(rng).copied())
+        .collect::<Option<Vec<char>>>()?;
+    chars.extend((chars.len()..length).map(|_| *pool.choose(rng).expect("pool is not empty")));
+    chars.shuffle(rng);
+    Some(chars.into_iter().collect())
+}
+
+/// Entropy in bits of [`generate_password_with_required`].
+///
+/// Counts each placed char by the size of its set and every other position
+/// by the pool, ignoring which positions the placed chars took, so it
+/// slightly understates the true figure rather than overstating it.
+///
+/// ```
+/// use password_generator_rust::{entropy_bits, required_entropy_bits};
+///
+/// let required = [vec!['a', 'b'], vec!['c', 'd']];
+/// assert_eq!(required_entropy_bits(10, 16, &required), 2.0 + entropy_bits(8, 16));
+/// ```
+pub fn required_entropy_bits(length: usize, pool_size: usize, required: &[Vec<char>]) -> f64 {
+    let placed: f64 = required.iter().map(|set| (set.len() as f64).log2()).sum();
+    placed + entropy_bits(length.saturating_sub(required.len()), pool_size)
+}
+
+/// Returns `password` in Unicode Normalization Form C.
+///
+/// A password drawn from a pool that mixes precomposed and decomposed forms
+/// (say `é` next to `e` plus a combining accent) can look identical to one
+/// that a system has normalized on storage, yet compare unequal to it. NFC
+/// puts it in the canonical composed form most systems store. ASCII is
+/// always already in NFC and is returned unchanged.
+///
+/// ```
+/// use password_generator_rust::normalize_nfc;
+///
+/// let decomposed = "cafe\u{301}!"; // `e` + COMBINING ACUTE ACCENT
+/// assert_eq!(normalize_nfc(decomposed), "caf\u{e9}!");
+/// assert_eq!(normalize_nfc("caf\u{e9}!"), "caf\u{e9}!");
+/// assert_eq!(normalize_nfc("k3Vq9xLm"), "k3Vq9xLm");
+/// ```
+pub fn normalize_nfc(password: &str) -> String {
+    if password.is_ascii() {
+        return password.to_string();
+    }
+    password.nfc().collect()
+}
+
+/// Converts chosen charset bytes into a `String`.
+///
+/// All built-in charsets are ASCII, which is already valid UTF-8 and converts
+/// without copying. Any other byte is read as Latin-1 (`byte as char`), the
+/// same mapping the character-by-character conversion used.
+fn bytes_to_string(bytes: Vec<u8>) -> String {
+    if bytes.is_ascii() {
+        String::from_utf8(bytes).expect("ASCII is valid UTF-8")
+    } else {
+        bytes.into_iter().map(char::from).collect()
+    }
+}
+
+/// Computes the entropy in bits of a password of `length` characters drawn
+/// uniformly from a charset of `charset_len` characters, without generating
+/// one.
+///
+/// The formula is `length * log2(charset_len)`. A charset of 0 or 1
+/// characters can only ever produce one password (or none), so it yields
+/// `0.0`. The CLI's `--show-entropy`, `--min-entropy` and weak-configuration
+/// warnings are all computed with this function.
+///
+/// # Examples
+///
+/// ```
+/// use password_generator_rust::entropy_bits;
+///
+/// // 16 characters from A-Z, a-z and 0-9: 16 * log2(62) ≈ 95.3 bits.
+/// assert!((entropy_bits(16, 62) - 95.27).abs() < 0.01);
+/// assert_eq!(entropy_bits(8, 2), 8.0);
+/// assert_eq!(entropy_bits(16, 1), 0.0);
+/// assert_eq!(entropy_bits(16, 0), 0.0);
+/// ```
+pub fn entropy_bits(length: usize, charset_len: usize) -> f64 {
+    length as f64 * entropy_per_char(charset_len)
+}
+
+/// Computes the entropy in bits each character drawn uniformly from a
+/// charset of `charset_len` characters adds, i.e. `log2(charset_len)`.
+///
+/// This is the charset's inherent strength, independent of length: a
+/// charset with less per character needs a longer password for the same
+/// total.
+///
+/// # Examples
+///
+/// ```
+/// use password_generator_rust::entropy_per_char;
+///
+/// assert!((entropy_per_char(10) - 3.32).abs() < 0.01); // digits
+/// assert!((entropy_per_char(16) - 4.0).abs() < 1e-9); // hex
+/// assert!((entropy_per_char(52) - 5.70).abs() < 0.01); // letters
+/// assert!((entropy_per_char(62) - 5.95).abs() < 0.01); // letters and digits
+/// assert!((entropy_per_char(87) - 6.44).abs() < 0.01); // the default charset
+/// assert_eq!(entropy_per_char(1), 0.0);
+/// assert_eq!(entropy_per_char(0), 0.0);
+/// ```
+pub fn entropy_per_char(charset_len: usize) -> f64 {
+    if charset_len <= 1 {
+        return 0.0;
+    }
+    (charset_len as f64).log2()
+}
+
+/// Computes the minimum length needed to reach `bits` of entropy with a
+/// charset of `charset_len` characters, i.e. `ceil(bits / log2(charset_len))`.
+///
+/// Returns `None` when no length can reach the target (a charset of 0 or 1
+/// characters provides no entropy).
+pub fn length_for_bits(bits: f64, charset_len: usize) -> Option<usize> {
+    if charset_len <= 1 {
+        return None;
+    }
+    Some((bits / (charset_len as f64).log2()).ceil().max(0.0) as usize)
+}
+
+/// Finds the shortest length from `start` up to `max` (inclusive) whose
+/// entropy, as computed by `entropy_at`, reaches `bits`.
+///
+/// Unlike [`length_for_bits`], this works for any way of computing the
+/// entropy of a length and never goes below `start`. Returns `None` if even
+/// `max` falls short.
+///
+/// ```
+/// use password_generator_rust::{entropy_bits, grow_length};
+///
+/// // 16 characters from 62 give ~95 bits; reaching 128 takes 22.
+/// assert_eq!(grow_length(16, 64, 128.0, |length| entropy_bits(length, 62)), Some(22));
+/// // A start that already meets the target is kept.
+/// assert_eq!(grow_length(30, 64, 128.0, |length| entropy_bits(length, 62)), Some(30));
+/// // Digits only can't reach 128 bits within 32 characters.
+/// assert_eq!(grow_length(16, 32, 128.0, |length| entropy_bits(length, 10)), None);
+/// ```
+pub fn grow_length(
+    start: usize,
+    max: usize,
+    bits: f64,
+    entropy_at: impl Fn(usize) -> f64,
+) -> Option<usize> {
+    (start..=max).find(|&length| entropy_at(length) >= bits)
+}
+
+/// Estimates the probability that a batch of `count` passwords, each with
+/// `entropy_bits` of entropy, contains at least one duplicate.
+///
+/// Uses the birthday approximation `1 - exp(-n(n-1) / 2N)` with
+/// `N = 2^entropy_bits`, computed with `exp_m1` so tiny probabilities don't
+/// round to zero.
+///
+/// ```
+/// use password_generator_rust::collision_probability;
+///
+/// // 23 people, 365 birthdays: just over one half.
+/// let p = collision_probability(23, 365f64.log2());
+/// assert!((p - 0.5).abs() < 0.01);
+///
+/// // 2^32 tokens of 64 bits: about 39%.
+/// let p = collision_probability(1 << 32, 64.0);
+/// assert!((p - 0.393).abs() < 0.001);
+///
+/// // A million 128-bit tokens: negligible, but not zero.
+/// let p = collision_probability(1_000_000, 128.0);
+/// assert!(p > 0.0 && p < 1e-26);
+///
+/// assert_eq!(collision_probability(1, 8.0), 0.0);
+/// ```
+pub fn collision_probability(count: usize, entropy_bits: f64) -> f64 {
+    let n = count as f64;
+    let pairs = n * (n - 1.0) / 2.0;
+    -(-pairs * (-entropy_bits).exp2()).exp_m1()
+}
+
+/// Estimates how long an attacker making `guesses_per_second` guesses needs
+/// to find a password with `entropy_bits` of entropy.
+///
+/// On average an attacker searches half the space, so the estimate is
+/// `2^entropy_bits / 2 / guesses_per_second` seconds. Results too long for a
+/// `Duration` (over about 584 billion years, already some forty times the age
+/// of the universe) and a speed that is zero, negative or not a number all
+/// return the [`CRACK_TIME_CAP`] sentinel.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use password_generator_rust::{CRACK_TIME_CAP, crack_time_estimate};
+///
+/// // 2^40 / 2 guesses at 1e10 per second: just under a minute.
+/// assert_eq!(crack_time_estimate(40.0, 1e10).as_secs(), 54);
+/// // A single possible password falls to half a guess on average.
+/// assert!(crack_time_estimate(0.0, 1e10) < Duration::from_secs(1));
+/// // 128 bits is far beyond anything representable.
+/// assert_eq!(crack_time_estimate(128.0, 1e10), CRACK_TIME_CAP);
+/// assert_eq!(crack_time_estimate(f64::INFINITY, 1e10), CRACK_TIME_CAP);
+/// assert_eq!(crack_time_estimate(64.0, 0.0), CRACK_TIME_CAP);
+/// ```
+pub fn crack_time_estimate(entropy_bits: f64, guesses_per_second: f64) -> Duration {
+    if guesses_per_second.is_nan() || guesses_per_second <= 0.0 {
+        return CRACK_TIME_CAP;
+    }
+    let seconds = (entropy_bits - 1.0).exp2() / guesses_per_second;
+    Duration::try_from_secs_f64(seconds).unwrap_or(CRACK_TIME_CAP)
+}
+
+/// Returned by [`crack_time_estimate`] when the estimate doesn't fit in a
+/// `Duration`. Treat it as "effectively never".
+pub const CRACK_TIME_CAP: Duration = Duration::MAX;
+
+/// Compares two secrets without leaking where they differ.
+///
+/// A naive `==` returns as soon as it finds a mismatching byte, so its running
+/// time reveals the length of the matching prefix. Any comparison involving
+/// secret material (e.g. checking a candidate against a stored password) must
+/// go through this function instead.
+///
+/// The constant-time property is provided by the `subtle` crate. Slices of
+/// different lengths compare unequal; only the length itself is not hidden.
+///
+/// [`blocklist::Blocklist::find_in`] uses it for every window of a candidate,
+/// so both `--dictionary-check` during generation and `check
+/// --dictionary-check` on an existing password compare in constant time.
+/// Password history lookups already go through Argon2's own constant-time
+/// verification.
+///
+/// # Examples
+///
+/// ```
+/// use password_generator_rust::constant_time_eq;
+///
+/// assert!(constant_time_eq(b"hunter22", b"hunter22"));
+/// assert!(!constant_time_eq(b"hunter22", b"hunter23"));
+/// assert!(!constant_time_eq(b"hunter22", b"hunter2"));
+/// ```
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_chacha::ChaCha20Rng;
+    use rand_chacha::rand_core::SeedableRng;
+
+    use super::*;
+
+    /// The conversion `generate_password_with_rng` used before
+    /// [`bytes_to_string`]: one `byte as char` per position, drawn the same
+    /// way from the same RNG.
+    fn per_char<R: RngCore>(rng: &mut R, length: usize, charset: &[u8]) -> String {
+        if charset.len() <= 256 {
+            fill_unbiased(rng, length, charset)
+                .into_iter()
+                .map(|b| b as char)
+                .collect()
+        } else {
+            (0..length)
+                .map(|_| *charset.choose(rng).expect("Charset must not be empty") as char)
+                .collect()
+        }
+    }
+
+    #[test]
+    fn byte_path_matches_the_per_char_method_for_a_seed() {
+        let latin1: Vec<u8> = (0x20..=0x7E).chain(0xA0..=0xFF).collect();
+        let huge: Vec<u8> = (0..=255).chain(0..=255).collect();
+        let charsets: [&[u8]; 5] = [b"x", b"abc123", crate::charset::SYMBOLS, &latin1, &huge];
+        for charset in charsets {
+            for length in [1, 16, 200] {
+                for seed in 0..8 {
+                    let old = per_char(&mut ChaCha20Rng::seed_from_u64(seed), length, charset);
+                    let new = generate_password_with_rng(
+                        &mut ChaCha20Rng::seed_from_u64(seed),
+                        length,
+                        charset,
+                    );
+                    assert_eq!(new, old, "charset of {}, length {}", charset.len(), length);
+                    assert_eq!(new.chars().count(), length);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn non_ascii_bytes_are_latin1() {
+        assert_eq!(bytes_to_string(vec![b'a', 0xE9, 0xFF]), "a\u{e9}\u{ff}");
+        assert_eq!(bytes_to_string(b"plain".to_vec()), "plain");
+    }
+}
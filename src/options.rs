@@ -0,0 +1,132 @@
+//! Generation options shared by the CLI and the library entry points.
+//!
+//! [`Options`] is the one place the character set is assembled from class
+//! toggles and exclusions, so the CLI, the FFI and the WebAssembly exports
+//! cannot drift apart.
+
+use crate::charset::{CharClass, DIGITS, LOWERCASE, SYMBOLS, UPPERCASE};
+use crate::generate_password;
+
+/// Length and character set selection for [`generate_password`].
+///
+/// # Examples
+///
+/// ```
+/// use password_generator_rust::options::Options;
+///
+/// let options = Options::default();
+/// assert_eq!(options.length, 16);
+/// assert_eq!(options.charset().len(), 26 + 26 + 10 + 25);
+///
+/// let letters = Options { digits: false, symbols: false, ..Options::default() };
+/// assert!(letters.generate().bytes().all(|b| b.is_ascii_alphabetic()));
+///
+/// let dashes = Options { symbol_set: Some(b"-_-".to_vec()), ..Options::default() };
+/// assert_eq!(dashes.charset().len(), 26 + 26 + 10 + 2);
+///
+/// let octal = Options { digit_set: Some(b"01234567".to_vec()), ..Options::default() };
+/// assert_eq!(octal.charset().len(), 26 + 26 + 8 + 25);
+/// assert!(!octal.charset().contains(&b'9'));
+///
+/// // A custom symbol set is the only source of symbols.
+/// let custom = Options { length: 200, symbol_set: Some(b"!@#".to_vec()), ..Options::default() };
+/// let password = custom.generate();
+/// assert!(password.bytes().filter(u8::is_ascii_punctuation).all(|b| b"!@#".contains(&b)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// Password length in characters.
+    pub length: usize,
+    /// Include `A-Z`.
+    pub uppercase: bool,
+    /// Include `a-z`.
+    pub lowercase: bool,
+    /// Include `0-9`.
+    pub digits: bool,
+    /// Include [`SYMBOLS`].
+    pub symbols: bool,
+    /// Replaces [`SYMBOLS`] when `symbols` is set. Duplicates are ignored.
+    pub symbol_set: Option<Vec<u8>>,
+    /// Replaces [`DIGITS`] when `digits` is set. Duplicates are ignored.
+    pub digit_set: Option<Vec<u8>>,
+    /// Characters removed after the classes are combined.
+    pub exclude: Vec<char>,
+}
+
+impl Default for Options {
+    /// 16 characters drawn from all four classes.
+    fn default() -> Self {
+        Self {
+            length: 16,
+            uppercase: true,
+            lowercase: true,
+            digits: true,
+            symbols: true,
+            symbol_set: None,
+            digit_set: None,
+            exclude: Vec::new(),
+        }
+    }
+}
+
+impl Options {
+    /// The enabled classes, in [`CharClass::ALL`] order, minus every excluded
+    /// character. May be empty.
+    pub fn charset(&self) -> Vec<u8> {
+        self.entries().into_iter().map(|(b, _)| b).collect()
+    }
+
+    /// Like [`charset`](Self::charset), with the class each character was
+    /// added under, e.g. for a UI that colors the available characters by
+    /// class. Characters from `symbol_set` count as [`CharClass::Symbol`] and
+    /// those from `digit_set` as [`CharClass::Digit`].
+    ///
+    /// ```
+    /// use password_generator_rust::charset::CharClass;
+    /// use password_generator_rust::options::Options;
+    ///
+    /// let options = Options {
+    ///     uppercase: false,
+    ///     lowercase: false,
+    ///     symbol_set: Some(b"~".to_vec()),
+    ///     exclude: vec!['1', '2', '3', '4', '5', '6', '7', '8', '9'],
+    ///     ..Options::default()
+    /// };
+    /// let pairs: Vec<(char, CharClass)> = options.classified_charset().collect();
+    /// assert_eq!(pairs, [('0', CharClass::Digit), ('~', CharClass::Symbol)]);
+    /// ```
+    pub fn classified_charset(&self) -> impl Iterator<Item = (char, CharClass)> {
+        self.entries()
+            .into_iter()
+            .map(|(b, class)| (b as char, class))
+    }
+
+    /// The charset as `(byte, class)` pairs, deduplicated and filtered.
+    fn entries(&self) -> Vec<(u8, CharClass)> {
+        let symbols = self.symbol_set.as_deref().unwrap_or(SYMBOLS);
+        let digits = self.digit_set.as_deref().unwrap_or(DIGITS);
+        let mut entries: Vec<(u8, CharClass)> = Vec::new();
+        for (enabled, class, bytes) in [
+            (self.uppercase, CharClass::Upper, UPPERCASE),
+            (self.lowercase, CharClass::Lower, LOWERCASE),
+            (self.digits, CharClass::Digit, digits),
+            (self.symbols, CharClass::Symbol, symbols),
+        ] {
+            if enabled {
+                for &b in bytes {
+                    if !entries.iter().any(|&(seen, _)| seen == b) {
+                        entries.push((b, class));
+                    }
+                }
+            }
+        }
+        entries.retain(|&(b, _)| !self.exclude.contains(&(b as char)));
+        entries
+    }
+
+    /// Generates a password with these options. Returns an empty string if
+    /// the charset is empty.
+    pub fn generate(&self) -> String {
+        generate_password(self.length, &self.charset())
+    }
+}
@@ -0,0 +1,136 @@
+//! Named password policy presets.
+//!
+//! Each preset encodes the length bounds and character-class requirements of a
+//! well-known standard so users don't have to memorize each platform's rules.
+
+use crate::charset::{DIGITS, LOWERCASE, SYMBOLS, UPPERCASE};
+
+/// Length bounds and class requirements of a named policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Policy {
+    /// Human-readable name of the policy.
+    pub name: &'static str,
+    /// Minimum password length (inclusive).
+    pub min_length: usize,
+    /// Maximum password length (inclusive).
+    pub max_length: usize,
+    /// How many of the four classes (upper, lower, digit, symbol) must appear.
+    pub min_classes: usize,
+    /// Minimum number of letters (either case).
+    pub min_letters: usize,
+    /// Minimum number of digits.
+    pub min_digits: usize,
+}
+
+/// Active Directory "password must meet complexity requirements":
+/// 8-128 characters containing at least 3 of the 4 character classes.
+pub const WINDOWS_AD: Policy = Policy {
+    name: "windows-ad",
+    min_length: 8,
+    max_length: 128,
+    min_classes: 3,
+    min_letters: 0,
+    min_digits: 0,
+};
+
+/// PCI DSS v4.0 requirement 8.3.6: at least 12 characters containing both
+/// numeric and alphabetic characters.
+pub const PCI: Policy = Policy {
+    name: "pci",
+    min_length: 12,
+    max_length: 128,
+    min_classes: 0,
+    min_letters: 1,
+    min_digits: 1,
+};
+
+/// NIST SP 800-63B: at least 15 characters for single-factor use, at least 64
+/// characters accepted, and no composition rules.
+pub const NIST: Policy = Policy {
+    name: "nist",
+    min_length: 15,
+    max_length: 64,
+    min_classes: 0,
+    min_letters: 0,
+    min_digits: 0,
+};
+
+/// Every named preset, in order of increasing strictness of composition rules.
+pub const PRESETS: [Policy; 3] = [NIST, PCI, WINDOWS_AD];
+
+impl Policy {
+    /// Clamps a requested length into this policy's bounds.
+    pub fn clamp_length(&self, length: usize) -> usize {
+        length.clamp(self.min_length, self.max_length)
+    }
+
+    /// The charset every policy draws from: all four classes.
+    pub fn charset(&self) -> Vec<u8> {
+        [UPPERCASE, LOWERCASE, DIGITS, SYMBOLS].concat()
+    }
+
+    /// Returns `true` if `password` meets every requirement of this policy.
+    pub fn is_satisfied_by(&self, password: &str) -> bool {
+        self.violations(password).is_empty()
+    }
+
+    /// Describes every requirement of this policy that `password` misses,
+    /// e.g. `too short (minimum 12)`. Empty if the password complies.
+    ///
+    /// ```
+    /// use password_generator_rust::policy::{PCI, WINDOWS_AD};
+    ///
+    /// assert_eq!(PCI.violations("abcdefgh"), ["too short (minimum 12)", "needs at least 1 digit"]);
+    /// assert_eq!(WINDOWS_AD.violations("abcdefgh12"), ["uses 2 of the 4 character classes, needs 3"]);
+    /// assert!(PCI.violations("abcdefgh1234").is_empty());
+    /// ```
+    pub fn violations(&self, password: &str) -> Vec<String> {
+        let bytes = password.as_bytes();
+        let count = |set: &[u8]| bytes.iter().filter(|b| set.contains(b)).count();
+
+        let upper = count(UPPERCASE);
+        let lower = count(LOWERCASE);
+        let digits = count(DIGITS);
+        let symbols = count(SYMBOLS);
+        let classes = [upper, lower, digits, symbols]
+            .iter()
+            .filter(|&&n| n > 0)
+            .count();
+
+        let length = password.chars().count();
+
+        let mut violations = Vec::new();
+        if length < self.min_length {
+            violations.push(format!("too short (minimum {})", self.min_length));
+        }
+        if length > self.max_length {
+            violations.push(format!("too long (maximum {})", self.max_length));
+        }
+        if classes < self.min_classes {
+            violations.push(format!(
+                "uses {} of the 4 character classes, needs {}",
+                classes, self.min_classes
+            ));
+        }
+        if upper + lower < self.min_letters {
+            violations.push(format!(
+                "needs at least {} letter{}",
+                self.min_letters,
+                plural(self.min_letters)
+            ));
+        }
+        if digits < self.min_digits {
+            violations.push(format!(
+                "needs at least {} digit{}",
+                self.min_digits,
+                plural(self.min_digits)
+            ));
+        }
+        violations
+    }
+}
+
+/// The plural suffix for `count` of something.
+fn plural(count: usize) -> &'static str {
+    if count == 1 { "" } else { "s" }
+}
@@ -0,0 +1,343 @@
+//! Per-position class rules like `1-3=alpha,4=digit`.
+//!
+//! Some policies pin the class of a few positions, e.g. "the first three
+//! characters are letters and the fourth is a digit". A [`PositionRules`]
+//! spec names only the constrained positions; every other position draws
+//! from the whole charset. A [`ClassMask`] instead spells out every
+//! position, each allowing any mix of classes. Either way each position is
+//! drawn from its own pool, so no candidate is ever rejected.
+//!
+//! The pools don't have to come from a spec. A numeric PIN with no leading
+//! zero, for instance, is one pool without `0` followed by full ones:
+//!
+//! ```
+//! use password_generator_rust::positions::generate_from_pools_with_rng;
+//! use rand::rngs::OsRng;
+//!
+//! let digits = b"0123456789".to_vec();
+//! let mut pools = vec![digits; 8];
+//! pools[0].retain(|&b| b != b'0');
+//! for _ in 0..1000 {
+//!     let pin = generate_from_pools_with_rng(&mut OsRng, &pools);
+//!     assert!(pin.len() == 8 && !pin.starts_with('0'));
+//! }
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use rand::RngCore;
+
+use crate::generate_password_with_rng;
+
+/// Whether a charset byte belongs to a class.
+type Keeps = fn(&u8) -> bool;
+
+/// Class names a rule may use, with the bytes of a charset each one keeps.
+const CLASSES: [(&str, Keeps); 6] = [
+    ("upper", u8::is_ascii_uppercase),
+    ("lower", u8::is_ascii_lowercase),
+    ("alpha", u8::is_ascii_alphabetic),
+    ("digit", u8::is_ascii_digit),
+    ("alnum", u8::is_ascii_alphanumeric),
+    ("symbol", u8::is_ascii_punctuation),
+];
+
+/// Class codes a [`ClassMask`] entry may combine, with the bytes of a
+/// charset each one keeps.
+const MASK_CLASSES: [(char, Keeps); 4] = [
+    ('A', u8::is_ascii_uppercase),
+    ('L', u8::is_ascii_lowercase),
+    ('D', u8::is_ascii_digit),
+    ('S', u8::is_ascii_punctuation),
+];
+
+/// One rule: positions `start..=end` (1-based) draw only from `class`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PositionRule {
+    /// First constrained position, counting from 1.
+    pub start: usize,
+    /// Last constrained position, inclusive.
+    pub end: usize,
+    /// The class name from the spec, e.g. `alpha`.
+    pub class: &'static str,
+}
+
+impl fmt::Display for PositionRule {
+    /// Formats the rule as it is written in a spec, e.g. `1-3=alpha` or `4=digit`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.start == self.end {
+            write!(f, "{}={}", self.start, self.class)
+        } else {
+            write!(f, "{}-{}={}", self.start, self.end, self.class)
+        }
+    }
+}
+
+/// A parsed `POS=CLASS,START-END=CLASS,...` spec.
+///
+/// Classes are `upper`, `lower`, `alpha`, `digit`, `alnum` and `symbol`.
+/// Ranges may not overlap.
+///
+/// ```
+/// use password_generator_rust::positions::PositionRules;
+///
+/// let rules: PositionRules = "1-3=alpha,4=digit".parse().unwrap();
+/// assert_eq!(rules.last_position(), 4);
+///
+/// assert!("1-3=alpha,3=digit".parse::<PositionRules>().is_err()); // overlap
+/// assert!("0=digit".parse::<PositionRules>().is_err());
+/// assert!("4-2=digit".parse::<PositionRules>().is_err());
+/// assert!("1=emoji".parse::<PositionRules>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PositionRules(pub Vec<PositionRule>);
+
+impl FromStr for PositionRules {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rules: Vec<PositionRule> = Vec::new();
+        for spec in s.split(',') {
+            let spec = spec.trim();
+            let (positions, class) = spec
+                .split_once('=')
+                .ok_or_else(|| format!("invalid rule '{}': expected POS=CLASS", spec))?;
+            let (start, end) = positions.split_once('-').unwrap_or((positions, positions));
+            let parse = |position: &str| {
+                position
+                    .trim()
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&position| position > 0)
+                    .ok_or_else(|| {
+                        format!(
+                            "invalid rule '{}': '{}' is not a position (they count from 1)",
+                            spec, position
+                        )
+                    })
+            };
+            let (start, end) = (parse(start)?, parse(end)?);
+            if start > end {
+                return Err(format!(
+                    "invalid rule '{}': the range ends before it starts",
+                    spec
+                ));
+            }
+            let &(class, _) = CLASSES
+                .iter()
+                .find(|(name, _)| *name == class.trim())
+                .ok_or_else(|| {
+                    let names: Vec<&str> = CLASSES.iter().map(|(name, _)| *name).collect();
+                    format!(
+                        "invalid rule '{}': unknown class '{}', expected one of {}",
+                        spec,
+                        class,
+                        names.join(", ")
+                    )
+                })?;
+            if let Some(other) = rules
+                .iter()
+                .find(|rule| rule.start <= end && start <= rule.end)
+            {
+                return Err(format!("invalid rule '{}': overlaps {}", spec, other));
+            }
+            rules.push(PositionRule { start, end, class });
+        }
+        rules.sort_by_key(|rule| rule.start);
+        Ok(PositionRules(rules))
+    }
+}
+
+impl PositionRules {
+    /// The highest position any rule constrains.
+    pub fn last_position(&self) -> usize {
+        self.0.iter().map(|rule| rule.end).max().unwrap_or(0)
+    }
+
+    /// The pool each of `length` positions draws from: the ruled class within
+    /// `charset`, or all of `charset` where no rule applies.
+    ///
+    /// Fails if a rule reaches past `length` or leaves a position with no
+    /// character of its class in `charset`.
+    ///
+    /// ```
+    /// use password_generator_rust::positions::PositionRules;
+    ///
+    /// let rules: PositionRules = "2=digit".parse().unwrap();
+    /// let pools = rules.pools(3, b"ab12").unwrap();
+    /// assert_eq!(pools, [b"ab12".to_vec(), b"12".to_vec(), b"ab12".to_vec()]);
+    ///
+    /// assert!(rules.pools(1, b"ab12").is_err()); // position 2 is past the end
+    /// assert!(rules.pools(3, b"abc").is_err()); // no digits to draw from
+    /// ```
+    pub fn pools(&self, length: usize, charset: &[u8]) -> Result<Vec<Vec<u8>>, String> {
+        if self.last_position() > length {
+            return Err(format!(
+                "position {} is past the end of a {}-character password",
+                self.last_position(),
+                length
+            ));
+        }
+        let mut pools = vec![charset.to_vec(); length];
+        for rule in &self.0 {
+            let (_, keeps) = CLASSES
+                .iter()
+                .find(|(name, _)| *name == rule.class)
+                .expect("parsed from CLASSES");
+            let pool: Vec<u8> = charset.iter().copied().filter(keeps).collect();
+            if pool.is_empty() {
+                return Err(format!(
+                    "{} needs a {} character, but the character set has none",
+                    rule, rule.class
+                ));
+            }
+            pools[rule.start - 1..rule.end].fill(pool);
+        }
+        Ok(pools)
+    }
+}
+
+/// A parsed class mask like `AL,D,*`, with one comma-separated entry per
+/// position.
+///
+/// Each entry lists the classes its position allows: `A` for uppercase, `L`
+/// for lowercase, `D` for digits and `S` for symbols, in any combination and
+/// either case. `*` allows the whole charset. The mask sets the length.
+///
+/// ```
+/// use password_generator_rust::positions::ClassMask;
+///
+/// let mask: ClassMask = "AL,D,*".parse().unwrap();
+/// assert_eq!(mask.length(), 3);
+/// assert_eq!(mask.to_string(), "AL,D,*");
+///
+/// assert!("AL,,D".parse::<ClassMask>().is_err()); // empty entry
+/// assert!("AX".parse::<ClassMask>().is_err()); // unknown class
+/// assert!("A*".parse::<ClassMask>().is_err()); // `*` stands alone
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassMask(Vec<String>);
+
+impl FromStr for ClassMask {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut entries = Vec::new();
+        for (index, entry) in s.split(',').enumerate() {
+            let entry = entry.trim().to_ascii_uppercase();
+            if entry.is_empty() {
+                return Err(format!("position {} has no classes", index + 1));
+            }
+            if entry != "*"
+                && let Some(c) = entry
+                    .chars()
+                    .find(|&c| !MASK_CLASSES.iter().any(|&(code, _)| code == c))
+            {
+                return Err(format!(
+                    "position {}: unknown class '{}', expected A (upper), L (lower), D (digit), S (symbol) or a lone *",
+                    index + 1,
+                    c
+                ));
+            }
+            entries.push(entry);
+        }
+        Ok(ClassMask(entries))
+    }
+}
+
+impl fmt::Display for ClassMask {
+    /// Formats the mask as it is written on the command line, e.g. `AL,D,*`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0.join(","))
+    }
+}
+
+impl ClassMask {
+    /// The number of positions, which is the password length.
+    pub fn length(&self) -> usize {
+        self.0.len()
+    }
+
+    /// The pool each position draws from: the bytes of `charset` in the
+    /// classes its entry allows.
+    ///
+    /// Fails if an entry allows no character of `charset`, e.g. `D` when
+    /// digits are excluded.
+    ///
+    /// ```
+    /// use password_generator_rust::positions::ClassMask;
+    ///
+    /// let mask: ClassMask = "AL,D,*".parse().unwrap();
+    /// let pools = mask.pools(b"aZ1!").unwrap();
+    /// assert_eq!(pools, [b"aZ".to_vec(), b"1".to_vec(), b"aZ1!".to_vec()]);
+    ///
+    /// assert!(mask.pools(b"aZ!").is_err()); // no digits for position 2
+    /// ```
+    pub fn pools(&self, charset: &[u8]) -> Result<Vec<Vec<u8>>, String> {
+        self.0
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                if entry == "*" {
+                    return Ok(charset.to_vec());
+                }
+                let keeps: Vec<Keeps> = MASK_CLASSES
+                    .iter()
+                    .filter(|(code, _)| entry.contains(*code))
+                    .map(|&(_, keeps)| keeps)
+                    .collect();
+                let pool: Vec<u8> = charset
+                    .iter()
+                    .copied()
+                    .filter(|byte| keeps.iter().any(|keeps| keeps(byte)))
+                    .collect();
+                if pool.is_empty() {
+                    return Err(format!(
+                        "position {} allows {}, but the character set has no such characters",
+                        index + 1,
+                        entry
+                    ));
+                }
+                Ok(pool)
+            })
+            .collect()
+    }
+}
+
+/// Generates one character from each pool in turn, e.g. from
+/// [`PositionRules::pools`].
+///
+/// ```
+/// use password_generator_rust::positions::{PositionRules, generate_from_pools_with_rng};
+/// use rand::rngs::OsRng;
+///
+/// let rules: PositionRules = "1-3=alpha,4=digit".parse().unwrap();
+/// let pools = rules.pools(8, b"abcXYZ0123!?").unwrap();
+/// for _ in 0..100 {
+///     let password = generate_from_pools_with_rng(&mut OsRng, &pools);
+///     let bytes = password.as_bytes();
+///     assert_eq!(bytes.len(), 8);
+///     assert!(bytes[..3].iter().all(u8::is_ascii_alphabetic));
+///     assert!(bytes[3].is_ascii_digit());
+/// }
+/// ```
+pub fn generate_from_pools_with_rng<R: RngCore + ?Sized>(rng: &mut R, pools: &[Vec<u8>]) -> String {
+    pools
+        .iter()
+        .map(|pool| generate_password_with_rng(rng, 1, pool))
+        .collect()
+}
+
+/// Entropy in bits of a password from [`generate_from_pools_with_rng`]: the
+/// sum of `log2(pool size)` over the positions.
+///
+/// ```
+/// use password_generator_rust::positions::pools_entropy_bits;
+///
+/// let pools = [b"0123456789".to_vec(), b"ab".to_vec()];
+/// assert!((pools_entropy_bits(&pools) - (10f64.log2() + 1.0)).abs() < 1e-9);
+/// ```
+pub fn pools_entropy_bits(pools: &[Vec<u8>]) -> f64 {
+    pools.iter().map(|pool| (pool.len() as f64).log2()).sum()
+}
@@ -0,0 +1,28 @@
+//! Terminal QR code rendering for transferring a password to a phone.
+
+use qrcode::render::unicode::Dense1x2;
+use qrcode::types::QrError;
+use qrcode::{EcLevel, QrCode};
+
+/// Renders `text` as a QR code for display in a terminal.
+///
+/// With `unicode` set, half-block characters pack two modules per character
+/// cell. Otherwise the code is drawn with plain `#` and space characters for
+/// terminals that can't render block characters.
+pub fn render(text: &str, unicode: bool) -> Result<String, QrError> {
+    let code = QrCode::with_error_correction_level(text.as_bytes(), EcLevel::M)?;
+
+    let rendered = if unicode {
+        code.render::<Dense1x2>()
+            .dark_color(Dense1x2::Light)
+            .light_color(Dense1x2::Dark)
+            .build()
+    } else {
+        code.render::<char>()
+            .dark_color('#')
+            .light_color(' ')
+            .module_dimensions(2, 1)
+            .build()
+    };
+    Ok(rendered)
+}
@@ -0,0 +1,160 @@
+//! Selectable random number sources.
+//!
+//! Everything in this crate draws from `OsRng` unless told otherwise. The
+//! alternatives exist for constrained environments and for reproducible
+//! tests; only [`RngSource::Os`] and [`RngSource::ChaCha`] are suitable for real
+//! passwords.
+
+use std::fmt;
+use std::str::FromStr;
+
+use rand::RngCore;
+use rand::rngs::OsRng;
+use rand_chacha::ChaCha20Rng;
+use rand_chacha::rand_core::SeedableRng;
+
+/// Where generated characters get their randomness from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RngSource {
+    /// The operating system's CSPRNG, queried for every draw (the default).
+    #[default]
+    Os,
+    /// A ChaCha20 stream cipher RNG seeded once from `OsRng`. Just as
+    /// unpredictable, with fewer system calls; the seed lives in process memory.
+    ChaCha,
+    /// A ChaCha20 RNG seeded from a fixed number. Fully deterministic: anyone
+    /// who knows the seed can reproduce the output. For testing only.
+    Seeded(u64),
+    /// Like [`RngSource::Seeded`], but each password of a batch gets its own
+    /// ChaCha20 stream, numbered by its index. Any password can be recovered
+    /// from the seed and its index alone, for recovery drills. For testing only.
+    Indexed(u64),
+}
+
+impl RngSource {
+    /// Creates the RNG for this source.
+    pub fn build(self) -> Box<dyn RngCore> {
+        match self {
+            RngSource::Os => Box::new(OsRng),
+            RngSource::ChaCha => {
+                Box::new(ChaCha20Rng::from_rng(OsRng).expect("OsRng must not fail"))
+            }
+            RngSource::Seeded(seed) => Box::new(ChaCha20Rng::seed_from_u64(seed)),
+            RngSource::Indexed(_) => self.build_for_index(0),
+        }
+    }
+
+    /// Creates the RNG for the password at `index` of a batch (counting from
+    /// 0): stream `index` of the seed for [`RngSource::Indexed`], the same as
+    /// [`build`](Self::build) for every other source.
+    ///
+    /// ```
+    /// use password_generator_rust::rng::RngSource;
+    ///
+    /// let source = RngSource::Indexed(7);
+    /// assert!(source.per_index());
+    ///
+    /// // The same seed and index always give the same stream, however many
+    /// // other passwords were drawn, and different indexes give different ones.
+    /// let batch = |count: u64| -> Vec<u64> {
+    ///     (0..count).map(|index| source.build_for_index(index).next_u64()).collect()
+    /// };
+    /// assert_eq!(batch(5), batch(5));
+    /// assert_eq!(batch(5)[..3], batch(3)[..]);
+    /// assert_ne!(batch(2)[0], batch(2)[1]);
+    /// ```
+    pub fn build_for_index(self, index: u64) -> Box<dyn RngCore> {
+        match self {
+            RngSource::Indexed(seed) => {
+                let mut rng = ChaCha20Rng::seed_from_u64(seed);
+                rng.set_stream(index);
+                Box::new(rng)
+            }
+            _ => self.build(),
+        }
+    }
+
+    /// Returns `true` if every password of a batch needs its own RNG from
+    /// [`build_for_index`](Self::build_for_index).
+    pub fn per_index(self) -> bool {
+        matches!(self, RngSource::Indexed(_))
+    }
+
+    /// Returns `true` if rebuilding this source gives a freshly seeded RNG
+    /// worth switching to.
+    ///
+    /// Only [`RngSource::ChaCha`] qualifies: `OsRng` holds no state to
+    /// refresh, and reseeding [`RngSource::Seeded`] from the OS would break the
+    /// reproducibility it exists for, so callers keep its single stream.
+    ///
+    /// ```
+    /// use password_generator_rust::rng::RngSource;
+    ///
+    /// assert!(RngSource::ChaCha.reseeds());
+    /// assert!(!RngSource::Os.reseeds());
+    ///
+    /// // A seeded source is never reseeded, so the same seed keeps giving the
+    /// // same stream.
+    /// let source = RngSource::Seeded(7);
+    /// assert!(!source.reseeds());
+    /// assert_eq!(source.build().next_u64(), source.build().next_u64());
+    /// ```
+    pub fn reseeds(self) -> bool {
+        matches!(self, RngSource::ChaCha)
+    }
+
+    /// Returns `true` unless the output is reproducible from public input.
+    pub fn is_secure(self) -> bool {
+        !matches!(self, RngSource::Seeded(_) | RngSource::Indexed(_))
+    }
+}
+
+/// Parses `os`, `chacha`, `seeded:<u64>` or `indexed:<u64>`.
+///
+/// ```
+/// use password_generator_rust::rng::RngSource;
+///
+/// assert_eq!("os".parse(), Ok(RngSource::Os));
+/// assert_eq!("seeded:7".parse(), Ok(RngSource::Seeded(7)));
+/// assert_eq!("indexed:7".parse(), Ok(RngSource::Indexed(7)));
+/// assert!("seeded:x".parse::<RngSource>().is_err());
+/// ```
+impl FromStr for RngSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "os" => Ok(RngSource::Os),
+            "chacha" => Ok(RngSource::ChaCha),
+            _ => {
+                let (source, seed): (fn(u64) -> RngSource, _) = match (
+                    s.strip_prefix("seeded:"),
+                    s.strip_prefix("indexed:"),
+                ) {
+                    (Some(seed), _) => (RngSource::Seeded, seed),
+                    (_, Some(seed)) => (RngSource::Indexed, seed),
+                    _ => {
+                        return Err(format!(
+                            "unknown RNG source '{}': expected os, chacha, seeded:<u64> or indexed:<u64>",
+                            s
+                        ));
+                    }
+                };
+                seed.parse()
+                    .map(source)
+                    .map_err(|_| format!("invalid seed '{}': expected an unsigned integer", seed))
+            }
+        }
+    }
+}
+
+impl fmt::Display for RngSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RngSource::Os => write!(f, "os"),
+            RngSource::ChaCha => write!(f, "chacha"),
+            RngSource::Seeded(seed) => write!(f, "seeded:{}", seed),
+            RngSource::Indexed(seed) => write!(f, "indexed:{}", seed),
+        }
+    }
+}
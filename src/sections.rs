@@ -0,0 +1,141 @@
+//! Passwords built from independent sections, like `k3Vq9xLm.4821.pQ7zW2aB`.
+//!
+//! Some credential formats are made of parts with their own length and
+//! character class. A [`Sections`] spec such as `8:alnum,4:digits,8:alnum`
+//! describes those parts; each is drawn on its own and the results are joined
+//! with a separator.
+
+use std::str::FromStr;
+
+use rand::RngCore;
+
+use crate::charset::{DIGITS, LOWERCASE, SYMBOLS, UPPERCASE};
+use crate::{entropy_bits, generate_password_with_rng};
+
+/// Class names a section may use, with the bytes each one draws from.
+const CLASSES: [(&str, &[&[u8]]); 8] = [
+    ("upper", &[UPPERCASE]),
+    ("lower", &[LOWERCASE]),
+    ("alpha", &[UPPERCASE, LOWERCASE]),
+    ("digits", &[DIGITS]),
+    ("alnum", &[UPPERCASE, LOWERCASE, DIGITS]),
+    ("hex", &[b"0123456789abcdef"]),
+    ("symbols", &[SYMBOLS]),
+    ("all", &[UPPERCASE, LOWERCASE, DIGITS, SYMBOLS]),
+];
+
+/// One part of a sectioned password: `length` characters from `charset`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    /// Number of characters in this section.
+    pub length: usize,
+    /// The class name from the spec, e.g. `alnum`.
+    pub class: &'static str,
+    /// The bytes this section draws from.
+    pub charset: Vec<u8>,
+}
+
+/// A parsed `LEN:CLASS,LEN:CLASS,...` spec.
+///
+/// Classes are `upper`, `lower`, `alpha`, `digits`, `alnum`, `hex` (lowercase),
+/// `symbols` and `all`.
+///
+/// ```
+/// use password_generator_rust::sections::Sections;
+///
+/// let sections: Sections = "8:alnum,4:digits".parse().unwrap();
+/// assert_eq!(sections.0.len(), 2);
+/// assert_eq!(sections.total_length(), 12);
+///
+/// assert!("8:alnum,4:emoji".parse::<Sections>().is_err());
+/// assert!("0:digits".parse::<Sections>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sections(pub Vec<Section>);
+
+impl FromStr for Sections {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(|spec| {
+                let spec = spec.trim();
+                let (length, class) = spec
+                    .split_once(':')
+                    .ok_or_else(|| format!("invalid section '{}': expected LEN:CLASS", spec))?;
+                let length: usize = length.parse().map_err(|_| {
+                    format!("invalid section '{}': '{}' is not a length", spec, length)
+                })?;
+                if length == 0 {
+                    return Err(format!(
+                        "invalid section '{}': length must be at least 1",
+                        spec
+                    ));
+                }
+                let (class, parts) =
+                    CLASSES
+                        .iter()
+                        .find(|(name, _)| *name == class)
+                        .ok_or_else(|| {
+                            let names: Vec<&str> = CLASSES.iter().map(|(name, _)| *name).collect();
+                            format!(
+                                "invalid section '{}': unknown class '{}', expected one of {}",
+                                spec,
+                                class,
+                                names.join(", ")
+                            )
+                        })?;
+                Ok(Section {
+                    length,
+                    class,
+                    charset: parts.concat(),
+                })
+            })
+            .collect::<Result<_, _>>()
+            .map(Sections)
+    }
+}
+
+impl Sections {
+    /// The number of characters across all sections, separators excluded.
+    pub fn total_length(&self) -> usize {
+        self.0.iter().map(|section| section.length).sum()
+    }
+
+    /// Entropy in bits: the sum over sections, since each is drawn
+    /// independently. The separator is fixed and adds nothing.
+    ///
+    /// ```
+    /// use password_generator_rust::sections::Sections;
+    ///
+    /// let sections: Sections = "4:digits,2:hex".parse().unwrap();
+    /// let expected = 4.0 * 10f64.log2() + 2.0 * 16f64.log2();
+    /// assert!((sections.entropy_bits() - expected).abs() < 1e-9);
+    /// ```
+    pub fn entropy_bits(&self) -> f64 {
+        self.0
+            .iter()
+            .map(|section| entropy_bits(section.length, section.charset.len()))
+            .sum()
+    }
+
+    /// Draws every section from `rng` and joins them with `separator`.
+    ///
+    /// ```
+    /// use password_generator_rust::sections::Sections;
+    /// use rand::rngs::OsRng;
+    ///
+    /// let sections: Sections = "3:upper,4:digits".parse().unwrap();
+    /// let password = sections.generate_with_rng(&mut OsRng, ".");
+    /// let (letters, digits) = password.split_once('.').unwrap();
+    /// assert!(letters.len() == 3 && letters.bytes().all(|b| b.is_ascii_uppercase()));
+    /// assert!(digits.len() == 4 && digits.bytes().all(|b| b.is_ascii_digit()));
+    /// ```
+    pub fn generate_with_rng<R: RngCore + ?Sized>(&self, rng: &mut R, separator: &str) -> String {
+        self.0
+            .iter()
+            .map(|section| generate_password_with_rng(rng, section.length, &section.charset))
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
+}
@@ -0,0 +1,180 @@
+//! Sanity checks on the output of a random number generator.
+//!
+//! This is NOT a certified statistical test suite. It only catches gross
+//! misconfiguration, such as a stubbed RNG that returns zeros or a constant,
+//! and its thresholds are deliberately lenient so legitimately random data
+//! essentially never fails.
+
+/// Default number of bytes sampled by the CLI `selftest` command.
+pub const DEFAULT_SAMPLE_SIZE: usize = 1 << 20;
+
+/// Longest run of one repeated byte tolerated in a sample.
+///
+/// In a megabyte of uniform bytes the longest run is usually 3 and a run of 8
+/// has a probability of roughly 2^-40.
+pub const MAX_CONSTANT_RUN: usize = 8;
+
+/// Upper bound on the chi-square statistic of the byte frequencies.
+///
+/// With 255 degrees of freedom the statistic has mean 255 and standard
+/// deviation about 22.6, so 400 is more than six deviations out.
+pub const MAX_CHI_SQUARE: f64 = 400.0;
+
+/// The outcome of one check.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+    /// Short name of the check.
+    pub name: &'static str,
+    /// Whether the sample passed.
+    pub passed: bool,
+    /// The measured value and its limit, for display.
+    pub detail: String,
+}
+
+/// Runs every check against `sample`.
+///
+/// The frequency check needs a few thousand bytes per possible byte value to
+/// be meaningful; [`DEFAULT_SAMPLE_SIZE`] is plenty.
+///
+/// ```
+/// use password_generator_rust::selftest::{DEFAULT_SAMPLE_SIZE, check_sample};
+/// use rand::RngCore;
+/// use rand::rngs::OsRng;
+///
+/// let mut sample = vec![0u8; DEFAULT_SAMPLE_SIZE];
+/// assert!(check_sample(&sample).iter().all(|check| !check.passed));
+///
+/// OsRng.fill_bytes(&mut sample);
+/// assert!(check_sample(&sample).iter().all(|check| check.passed));
+/// ```
+pub fn check_sample(sample: &[u8]) -> Vec<Check> {
+    let nonzero = sample.iter().any(|&b| b != 0);
+    let run = longest_run(sample);
+    let chi_square = chi_square(sample);
+
+    vec![
+        Check {
+            name: "non-zero output",
+            passed: nonzero,
+            detail: if nonzero {
+                "sample contains non-zero bytes".to_string()
+            } else {
+                "every byte is zero".to_string()
+            },
+        },
+        Check {
+            name: "constant runs",
+            passed: run <= MAX_CONSTANT_RUN,
+            detail: format!("longest run {} (limit {})", run, MAX_CONSTANT_RUN),
+        },
+        Check {
+            name: "byte frequency",
+            passed: chi_square <= MAX_CHI_SQUARE,
+            detail: format!("chi-square {:.1} (limit {:.1})", chi_square, MAX_CHI_SQUARE),
+        },
+    ]
+}
+
+/// Length of the longest run of one repeated byte.
+fn longest_run(sample: &[u8]) -> usize {
+    sample
+        .chunk_by(|a, b| a == b)
+        .map(<[u8]>::len)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Pearson's chi-square statistic of the byte frequencies against a uniform
+/// distribution over all 256 values.
+fn chi_square(sample: &[u8]) -> f64 {
+    if sample.is_empty() {
+        return f64::INFINITY;
+    }
+
+    let mut counts = [0usize; 256];
+    for &b in sample {
+        counts[b as usize] += 1;
+    }
+
+    let expected = sample.len() as f64 / 256.0;
+    counts
+        .iter()
+        .map(|&count| {
+            let diff = count as f64 - expected;
+            diff * diff / expected
+        })
+        .sum()
+}
+
+/// Default number of characters sampled by the CLI `--histogram` diagnostic.
+pub const HISTOGRAM_SAMPLE_SIZE: usize = 1_000_000;
+
+/// How many standard deviations a character's count may stray from its
+/// expected value before [`histogram`] flags it.
+///
+/// Five deviations keeps false alarms below one in a million per character,
+/// while modulo bias on a typical charset shifts the affected counts by tens
+/// of deviations at the default sample size.
+pub const HISTOGRAM_BAND_SIGMAS: f64 = 5.0;
+
+/// Observed frequency of one charset character.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bucket {
+    /// The character.
+    pub ch: char,
+    /// How often it appeared in the sample.
+    pub observed: usize,
+    /// How often a uniform generator would produce it on average.
+    pub expected: f64,
+    /// Whether `observed` lies outside the [`HISTOGRAM_BAND_SIGMAS`] band.
+    pub outlier: bool,
+}
+
+/// Counts each character of `charset` in `sample` and flags counts that
+/// deviate from a uniform distribution beyond [`HISTOGRAM_BAND_SIGMAS`].
+///
+/// Characters of `sample` outside `charset` are ignored. This doubles as a
+/// regression test for the no-modulo-bias guarantee: 62 does not divide 256,
+/// so a naive `byte % 62` mapping would make the first eight characters a
+/// quarter more likely than the rest.
+///
+/// ```
+/// use password_generator_rust::charset::{DIGITS, LOWERCASE, UPPERCASE};
+/// use password_generator_rust::generate_password;
+/// use password_generator_rust::selftest::histogram;
+///
+/// let charset = [UPPERCASE, LOWERCASE, DIGITS].concat();
+/// let chars: Vec<char> = charset.iter().map(|&b| b as char).collect();
+///
+/// let sample = generate_password(200_000, &charset);
+/// assert!(histogram(&sample, &chars).iter().all(|bucket| !bucket.outlier));
+///
+/// // A generator that never produces `a` is caught.
+/// let skewed = sample.replace('a', "b");
+/// assert!(histogram(&skewed, &chars).iter().any(|bucket| bucket.outlier));
+/// ```
+pub fn histogram(sample: &str, charset: &[char]) -> Vec<Bucket> {
+    let mut counts = vec![0usize; charset.len()];
+    let mut total = 0usize;
+    for c in sample.chars() {
+        if let Some(index) = charset.iter().position(|&x| x == c) {
+            counts[index] += 1;
+            total += 1;
+        }
+    }
+
+    let p = 1.0 / charset.len() as f64;
+    let expected = total as f64 * p;
+    let sigma = (total as f64 * p * (1.0 - p)).sqrt();
+
+    charset
+        .iter()
+        .zip(counts)
+        .map(|(&ch, observed)| Bucket {
+            ch,
+            observed,
+            expected,
+            outlier: (observed as f64 - expected).abs() > HISTOGRAM_BAND_SIGMAS * sigma,
+        })
+        .collect()
+}
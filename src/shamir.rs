@@ -0,0 +1,167 @@
+//! Splitting a password into Shamir secret shares, and joining them again.
+//!
+//! A [`Scheme`] such as `2-of-3` turns one password into `shares` text shares,
+//! any `threshold` of which recover it; fewer reveal nothing about it beyond
+//! its length. The arithmetic is done by the `sharks` crate over GF(256),
+//! one polynomial per byte of the password.
+//!
+//! A share is written as `K-HEX`: the threshold, so [`combine`] can tell how
+//! many are needed, then the share's x coordinate and bytes in lowercase hex.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use data_encoding::HEXLOWER;
+use rand::Rng;
+use sharks::{Share, Sharks};
+
+/// Most shares a password can be split into: one per non-zero x in GF(256).
+pub const MAX_SHARES: u8 = 255;
+
+/// A `K-of-N` split: `shares` shares, any `threshold` of which recover the
+/// password.
+///
+/// ```
+/// use password_generator_rust::shamir::Scheme;
+///
+/// let scheme: Scheme = "2-of-3".parse().unwrap();
+/// assert_eq!((scheme.threshold, scheme.shares), (2, 3));
+/// assert_eq!(scheme.to_string(), "2-of-3");
+///
+/// assert!("1-of-3".parse::<Scheme>().is_err()); // every share would be the password
+/// assert!("4-of-3".parse::<Scheme>().is_err());
+/// assert!("2-of-300".parse::<Scheme>().is_err());
+/// assert!("2/3".parse::<Scheme>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Scheme {
+    /// How many shares recover the password.
+    pub threshold: u8,
+    /// How many shares are handed out.
+    pub shares: u8,
+}
+
+impl FromStr for Scheme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (threshold, shares) = s
+            .split_once("-of-")
+            .ok_or_else(|| format!("invalid split '{}': expected K-of-N, e.g. 2-of-3", s))?;
+        // Parsing as u8 already caps both at MAX_SHARES.
+        let parse = |number: &str| {
+            number.trim().parse::<u8>().map_err(|_| {
+                format!(
+                    "invalid split '{}': '{}' is not a number from 2 to {}",
+                    s, number, MAX_SHARES
+                )
+            })
+        };
+        let (threshold, shares) = (parse(threshold)?, parse(shares)?);
+        if threshold < 2 {
+            return Err(format!(
+                "invalid split '{}': K must be at least 2, or every share is the password itself",
+                s
+            ));
+        }
+        if threshold > shares {
+            return Err(format!(
+                "invalid split '{}': K can't be more than the {} shares",
+                s, shares
+            ));
+        }
+        Ok(Scheme { threshold, shares })
+    }
+}
+
+impl fmt::Display for Scheme {
+    /// Formats the scheme as it is written on the command line, e.g. `2-of-3`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-of-{}", self.threshold, self.shares)
+    }
+}
+
+impl Scheme {
+    /// Splits `password` into [`shares`](Self::shares) encoded shares, drawing
+    /// the polynomial coefficients from `rng`.
+    ///
+    /// ```
+    /// use password_generator_rust::shamir::{Scheme, combine};
+    /// use rand::rngs::OsRng;
+    ///
+    /// let scheme: Scheme = "2-of-3".parse().unwrap();
+    /// let shares = scheme.split_with_rng(&mut OsRng, "k3Vq9xLm!4821pQ7");
+    /// assert_eq!(shares.len(), 3);
+    /// assert!(shares.iter().all(|share| share.starts_with("2-")));
+    ///
+    /// assert_eq!(combine(&shares[1..]).unwrap(), "k3Vq9xLm!4821pQ7");
+    /// assert_eq!(combine(&[&shares[2], &shares[0]]).unwrap(), "k3Vq9xLm!4821pQ7");
+    /// assert!(combine(&shares[..1]).is_err()); // below the threshold
+    /// ```
+    pub fn split_with_rng<R: Rng>(&self, rng: &mut R, password: &str) -> Vec<String> {
+        Sharks(self.threshold)
+            .dealer_rng(password.as_bytes(), rng)
+            .take(self.shares as usize)
+            .map(|share| format!("{}-{}", self.threshold, HEXLOWER.encode(&Vec::from(&share))))
+            .collect()
+    }
+}
+
+/// Recovers the password from at least the threshold number of shares made
+/// by [`Scheme::split_with_rng`].
+///
+/// Surrounding whitespace is ignored and a share given twice counts once.
+/// Fails on malformed shares, on shares from different splits, or if fewer
+/// distinct shares than the threshold are given. Shares of the same split
+/// can't be told apart from shares of another split with the same threshold
+/// and length, which combine to garbage; an invalid UTF-8 result is the
+/// usual sign.
+pub fn combine<S: AsRef<str>>(shares: &[S]) -> Result<String, String> {
+    let mut threshold = None;
+    let mut seen = HashSet::new();
+    let mut parsed = Vec::new();
+    for (index, share) in shares.iter().enumerate() {
+        let share = share.as_ref().trim();
+        let invalid = || format!("share {} is not a share from --split", index + 1);
+        let (k, hex) = share.split_once('-').ok_or_else(invalid)?;
+        let k: u8 = k.parse().map_err(|_| invalid())?;
+        let bytes = HEXLOWER
+            .decode(hex.to_ascii_lowercase().as_bytes())
+            .map_err(|_| invalid())?;
+        // x = 0 is where the polynomial holds the password; no share uses it.
+        if bytes.first() == Some(&0) {
+            return Err(invalid());
+        }
+        let share = Share::try_from(bytes.as_slice()).map_err(|_| invalid())?;
+        match threshold {
+            None => threshold = Some(k),
+            Some(threshold) if threshold != k => {
+                return Err(format!(
+                    "share {} needs {} shares but the first needs {}; they are from different splits",
+                    index + 1,
+                    k,
+                    threshold
+                ));
+            }
+            Some(_) => {}
+        }
+        if seen.insert(bytes[0]) {
+            parsed.push(share);
+        }
+    }
+    let threshold = threshold.ok_or("no shares given")?;
+    if parsed.len() < threshold as usize {
+        return Err(format!(
+            "{} distinct share(s) given, but this split needs {}",
+            parsed.len(),
+            threshold
+        ));
+    }
+    let secret = Sharks(threshold)
+        .recover(&parsed)
+        .map_err(|err| err.to_lowercase())?;
+    String::from_utf8(secret).map_err(|_| {
+        "the shares combine to invalid UTF-8; they are probably from different splits".to_string()
+    })
+}
@@ -0,0 +1,151 @@
+//! Strength estimation for existing passwords (the `check` command).
+//!
+//! Generated passwords have an exact entropy known from their configuration.
+//! An arbitrary password does not, so this module estimates it from the
+//! character classes it uses: `length * log2(pool size)`, where the pool is
+//! the union of every class that appears. This is an upper bound; a
+//! human-chosen password is usually far weaker than its estimate.
+
+use std::fmt;
+
+use crate::charset::{CharClass, class_bytes, class_of};
+
+/// How many characters of each class a password contains.
+///
+/// Displays as `upper=4 lower=6 digit=3 symbol=3`, with `other=N` appended
+/// when the password has characters outside the built-in classes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClassCounts {
+    pub upper: usize,
+    pub lower: usize,
+    pub digit: usize,
+    pub symbol: usize,
+    /// Characters outside the built-in classes.
+    pub other: usize,
+}
+
+impl ClassCounts {
+    /// The count for one built-in class.
+    pub fn get(&self, class: CharClass) -> usize {
+        match class {
+            CharClass::Upper => self.upper,
+            CharClass::Lower => self.lower,
+            CharClass::Digit => self.digit,
+            CharClass::Symbol => self.symbol,
+        }
+    }
+}
+
+impl fmt::Display for ClassCounts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "upper={} lower={} digit={} symbol={}",
+            self.upper, self.lower, self.digit, self.symbol
+        )?;
+        if self.other > 0 {
+            write!(f, " other={}", self.other)?;
+        }
+        Ok(())
+    }
+}
+
+/// Counts the characters of each class in `password`.
+///
+/// ```
+/// use password_generator_rust::strength::classify;
+///
+/// let counts = classify("Ab3!x");
+/// assert_eq!(counts.to_string(), "upper=1 lower=2 digit=1 symbol=1");
+/// ```
+pub fn classify(password: &str) -> ClassCounts {
+    let mut counts = ClassCounts::default();
+    for c in password.chars() {
+        match char_class(c) {
+            Some(CharClass::Upper) => counts.upper += 1,
+            Some(CharClass::Lower) => counts.lower += 1,
+            Some(CharClass::Digit) => counts.digit += 1,
+            Some(CharClass::Symbol) => counts.symbol += 1,
+            None => counts.other += 1,
+        }
+    }
+    counts
+}
+
+/// Class counts plus the class at every position, e.g. for a GUI that
+/// highlights each class in its own color.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasswordStats {
+    /// How many characters of each class the password contains.
+    pub counts: ClassCounts,
+    /// The class of each character in order, one entry per `char`; `None`
+    /// for characters outside the built-in classes (counted as `other`).
+    pub positions: Vec<Option<CharClass>>,
+}
+
+/// Counts the characters of each class in `password` and records where each
+/// class appears.
+///
+/// ```
+/// use password_generator_rust::charset::CharClass;
+/// use password_generator_rust::strength::analyze;
+///
+/// let stats = analyze("aB3!é");
+/// assert_eq!(stats.counts.to_string(), "upper=1 lower=1 digit=1 symbol=1 other=1");
+/// assert_eq!(
+///     stats.positions,
+///     [
+///         Some(CharClass::Lower),
+///         Some(CharClass::Upper),
+///         Some(CharClass::Digit),
+///         Some(CharClass::Symbol),
+///         None,
+///     ]
+/// );
+/// ```
+pub fn analyze(password: &str) -> PasswordStats {
+    PasswordStats {
+        counts: classify(password),
+        positions: password.chars().map(char_class).collect(),
+    }
+}
+
+/// The built-in class of `c`, or `None` for anything else (including every
+/// non-ASCII character).
+fn char_class(c: char) -> Option<CharClass> {
+    if c.is_ascii() {
+        class_of(c as u8)
+    } else {
+        None
+    }
+}
+
+/// Returns the built-in character classes that appear in `password`, in
+/// [`CharClass::ALL`] order.
+pub fn classes_present(password: &str) -> Vec<CharClass> {
+    let counts = classify(password);
+    CharClass::ALL
+        .into_iter()
+        .filter(|&class| counts.get(class) > 0)
+        .collect()
+}
+
+/// Estimates the entropy in bits of `password` from the classes it uses.
+///
+/// Characters outside the built-in classes each add one to the pool.
+pub fn estimate_entropy_bits(password: &str) -> f64 {
+    let mut others: Vec<char> = password
+        .chars()
+        .filter(|&c| char_class(c).is_none())
+        .collect();
+    others.sort_unstable();
+    others.dedup();
+
+    let pool: usize = classes_present(password)
+        .into_iter()
+        .map(|class| class_bytes(class).len())
+        .sum::<usize>()
+        + others.len();
+
+    crate::entropy_bits(password.chars().count(), pool)
+}
@@ -0,0 +1,160 @@
+//! Output templates like `{index}: {password} ({bits} bits)`.
+//!
+//! A [`Template`] is parsed once and then rendered for every password, so a
+//! typo in a placeholder is reported before anything is generated. `{{` and
+//! `}}` stand for literal braces.
+
+use std::str::FromStr;
+
+/// The placeholders a template may use, in the order they are listed in
+/// errors.
+const PLACEHOLDERS: [(&str, Placeholder); 5] = [
+    ("password", Placeholder::Password),
+    ("index", Placeholder::Index),
+    ("length", Placeholder::Length),
+    ("bits", Placeholder::Bits),
+    ("charset_size", Placeholder::CharsetSize),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Placeholder {
+    Password,
+    Index,
+    Length,
+    Bits,
+    CharsetSize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Piece {
+    Literal(String),
+    Placeholder(Placeholder),
+}
+
+/// The values substituted into a [`Template`] for one password.
+#[derive(Debug, Clone, Copy)]
+pub struct Fields<'a> {
+    /// The password itself, for `{password}`; `{length}` counts its characters.
+    pub password: &'a str,
+    /// Position in the batch, starting at 1, for `{index}`.
+    pub index: usize,
+    /// Entropy in bits, for `{bits}` (printed to one decimal).
+    pub bits: f64,
+    /// Size of the character set, for `{charset_size}`. `None` renders as `-`,
+    /// for passwords not drawn from a single character set.
+    pub charset_size: Option<usize>,
+}
+
+/// A parsed output template.
+///
+/// ```
+/// use password_generator_rust::template::{Fields, Template};
+///
+/// let template: Template = "{index}: {password} ({bits} bits, {{{length}}})".parse().unwrap();
+/// let fields = Fields { password: "k3Vq9xLm", index: 2, bits: 47.63, charset_size: Some(62) };
+/// assert_eq!(template.render(&fields), "2: k3Vq9xLm (47.6 bits, {8})");
+///
+/// let error = "{pasword}".parse::<Template>().unwrap_err();
+/// assert!(error.contains("unknown placeholder {pasword}"));
+/// assert!("{password".parse::<Template>().is_err());
+/// assert!("a } b".parse::<Template>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template(Vec<Piece>);
+
+impl FromStr for Template {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut pieces = Vec::new();
+        let mut literal = String::new();
+        let mut chars = s.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(format!("unclosed placeholder {{{}", name)),
+                        }
+                    }
+                    let placeholder = PLACEHOLDERS
+                        .iter()
+                        .find(|(known, _)| *known == name)
+                        .map(|&(_, placeholder)| placeholder);
+                    let Some(placeholder) = placeholder else {
+                        let known: Vec<String> = PLACEHOLDERS
+                            .iter()
+                            .map(|(known, _)| format!("{{{}}}", known))
+                            .collect();
+                        return Err(format!(
+                            "unknown placeholder {{{}}}; expected one of {} (use {{{{ and }}}} for literal braces)",
+                            name,
+                            known.join(", ")
+                        ));
+                    };
+                    if !literal.is_empty() {
+                        pieces.push(Piece::Literal(std::mem::take(&mut literal)));
+                    }
+                    pieces.push(Piece::Placeholder(placeholder));
+                }
+                '}' => {
+                    return Err("unmatched '}'; use }} for a literal brace".to_string());
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            pieces.push(Piece::Literal(literal));
+        }
+        Ok(Template(pieces))
+    }
+}
+
+impl Template {
+    /// Returns `true` if the template contains `{password}`.
+    ///
+    /// ```
+    /// use password_generator_rust::template::Template;
+    ///
+    /// let env: Template = "DB_USER=app\nDB_PASSWORD={password}\n".parse().unwrap();
+    /// assert!(env.uses_password());
+    /// assert!(!"{index}: {{password}}".parse::<Template>().unwrap().uses_password());
+    /// ```
+    pub fn uses_password(&self) -> bool {
+        self.0.contains(&Piece::Placeholder(Placeholder::Password))
+    }
+
+    /// Substitutes `fields` into the template.
+    pub fn render(&self, fields: &Fields) -> String {
+        let mut line = String::new();
+        for piece in &self.0 {
+            match piece {
+                Piece::Literal(text) => line.push_str(text),
+                Piece::Placeholder(Placeholder::Password) => line.push_str(fields.password),
+                Piece::Placeholder(Placeholder::Index) => line.push_str(&fields.index.to_string()),
+                Piece::Placeholder(Placeholder::Length) => {
+                    line.push_str(&fields.password.chars().count().to_string())
+                }
+                Piece::Placeholder(Placeholder::Bits) => {
+                    line.push_str(&format!("{:.1}", fields.bits))
+                }
+                Piece::Placeholder(Placeholder::CharsetSize) => match fields.charset_size {
+                    Some(size) => line.push_str(&size.to_string()),
+                    None => line.push('-'),
+                },
+            }
+        }
+        line
+    }
+}
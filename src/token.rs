@@ -0,0 +1,43 @@
+//! Random tokens: raw bytes from `OsRng` in a text encoding.
+//!
+//! Unlike passwords, tokens are sized in bytes of entropy and rendered with a
+//! standard encoding, which suits API keys, session secrets and the like.
+
+use data_encoding::{BASE32_NOPAD, BASE64, BASE64URL_NOPAD, HEXLOWER};
+use rand::RngCore;
+use rand::rngs::OsRng;
+
+/// Text encodings available for tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// Lowercase hexadecimal.
+    Hex,
+    /// Standard base64 with padding.
+    Base64,
+    /// URL-safe base64 without padding.
+    Base64Url,
+    /// RFC 4648 base32 without padding.
+    Base32,
+}
+
+impl Encoding {
+    /// Encodes `bytes` as text.
+    pub fn encode(self, bytes: &[u8]) -> String {
+        match self {
+            Encoding::Hex => HEXLOWER.encode(bytes),
+            Encoding::Base64 => BASE64.encode(bytes),
+            Encoding::Base64Url => BASE64URL_NOPAD.encode(bytes),
+            Encoding::Base32 => BASE32_NOPAD.encode(bytes),
+        }
+    }
+}
+
+/// Generates `bytes` random bytes with `OsRng` and encodes them.
+///
+/// The token carries exactly `bytes * 8` bits of entropy regardless of the
+/// encoding, which only changes its textual length.
+pub fn generate_token(bytes: usize, encoding: Encoding) -> String {
+    let mut buf = vec![0u8; bytes];
+    OsRng.fill_bytes(&mut buf);
+    encoding.encode(&buf)
+}
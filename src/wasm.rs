@@ -0,0 +1,52 @@
+//! JavaScript interface to the generator (enabled with the `wasm` feature).
+//!
+//! Build for the browser with
+//! `cargo build --release --lib --target wasm32-unknown-unknown --features wasm`
+//! and run `wasm-bindgen` on the output. `OsRng` draws from
+//! `crypto.getRandomValues` through `getrandom`'s `js` backend, so the
+//! randomness guarantees match the native build. Nothing on this path touches
+//! the filesystem or exits the process.
+//!
+//! ```js
+//! import init, { generate_wasm } from "./password_generator_rust.js";
+//!
+//! await init();
+//! const password = generate_wasm(20, { noSymbols: true });
+//! ```
+
+use js_sys::Reflect;
+use wasm_bindgen::prelude::*;
+
+use crate::options::Options;
+
+/// Generates a password of `length` characters.
+///
+/// `opts` is an optional object whose boolean `noSymbols`, `noNumbers` and
+/// `onlyLetters` properties mirror the CLI flags; missing properties default
+/// to `false`. Throws if `length` is below the minimum of 8 characters.
+#[wasm_bindgen]
+pub fn generate_wasm(length: usize, opts: JsValue) -> Result<String, JsError> {
+    if length < 8 {
+        return Err(JsError::new(
+            "Password length must be at least 8 characters.",
+        ));
+    }
+
+    let flag = |name: &str| {
+        opts.is_object()
+            && Reflect::get(&opts, &JsValue::from_str(name))
+                .ok()
+                .and_then(|value| value.as_bool())
+                .unwrap_or(false)
+    };
+
+    let only_letters = flag("onlyLetters");
+    let options = Options {
+        length,
+        digits: !only_letters && !flag("noNumbers"),
+        symbols: !only_letters && !flag("noSymbols"),
+        ..Options::default()
+    };
+
+    Ok(options.generate())
+}
@@ -0,0 +1,410 @@
+//! Word-based generation backed by the embedded EFF large wordlist.
+//!
+//! The list (`eff_large_wordlist.txt`) contains 7776 words, one per line, each
+//! prefixed by its five-digit diceware roll and a tab.
+
+use std::sync::OnceLock;
+
+use rand::Rng;
+use rand::rngs::OsRng;
+use rand::seq::SliceRandom;
+
+use crate::charset::{DIGITS, SYMBOLS};
+
+const RAW_WORDLIST: &str = include_str!("eff_large_wordlist.txt");
+
+/// Returns the embedded wordlist, parsed once on first use.
+pub fn wordlist() -> &'static [&'static str] {
+    static WORDS: OnceLock<Vec<&'static str>> = OnceLock::new();
+    WORDS.get_or_init(|| {
+        RAW_WORDLIST
+            .lines()
+            .filter_map(|line| line.split('\t').nth(1))
+            .collect()
+    })
+}
+
+/// How the words of a passphrase are capitalized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CaseMode {
+    /// `correct-horse` (the wordlist's own case).
+    #[default]
+    Lower,
+    /// `CORRECT-HORSE`.
+    Upper,
+    /// `Correct-Horse`.
+    Title,
+    /// Each word independently lower, upper or title case, chosen with `OsRng`.
+    Random,
+}
+
+impl CaseMode {
+    /// Entropy in bits this mode adds to each word.
+    ///
+    /// The fixed modes add nothing; `Random` adds `log2(3)` per word for its
+    /// three equally likely choices.
+    pub fn entropy_bits_per_word(self) -> f64 {
+        match self {
+            CaseMode::Random => 3f64.log2(),
+            CaseMode::Lower | CaseMode::Upper | CaseMode::Title => 0.0,
+        }
+    }
+
+    fn apply(self, word: &str, rng: &mut OsRng) -> String {
+        self.resolve(rng).write(word)
+    }
+
+    /// The fixed mode a word is written in: `self`, or for `Random` one of
+    /// the three drawn from `rng`.
+    fn resolve(self, rng: &mut OsRng) -> CaseMode {
+        match self {
+            CaseMode::Random => *[CaseMode::Lower, CaseMode::Upper, CaseMode::Title]
+                .choose(rng)
+                .expect("Case modes must not be empty"),
+            fixed => fixed,
+        }
+    }
+
+    fn write(self, word: &str) -> String {
+        match self {
+            CaseMode::Upper => word.to_ascii_uppercase(),
+            CaseMode::Title => capitalize(word),
+            CaseMode::Lower | CaseMode::Random => word.to_string(),
+        }
+    }
+}
+
+/// A passphrase along with the random choices that shaped it, from
+/// [`draw_passphrase`] or [`draw_passphrase_with_separators`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PassphraseDraw {
+    /// The passphrase itself.
+    pub passphrase: String,
+    /// The case each word was written in; never [`CaseMode::Random`].
+    pub cases: Vec<CaseMode>,
+    /// The separator picked for each junction; empty with a fixed separator.
+    pub junctions: Vec<char>,
+    /// The requested case mode, before resolving `Random`.
+    case: CaseMode,
+    /// How many distinct separators each junction was picked from.
+    separator_choices: usize,
+}
+
+impl PassphraseDraw {
+    /// Entropy in bits of this particular passphrase's structure.
+    ///
+    /// Random case and random separators only earn their bits when the draw
+    /// actually varies: words all in one case, or every junction using the
+    /// same separator, are among the first patterns an attacker tries, so
+    /// such a draw counts only its words. Otherwise this is the configuration's
+    /// entropy, the words plus [`CaseMode::entropy_bits_per_word`] per word
+    /// plus [`separators_entropy_bits`].
+    ///
+    /// ```
+    /// use password_generator_rust::words::{
+    ///     CaseMode, draw_passphrase, draw_passphrase_with_separators, passphrase_entropy_bits,
+    ///     separators_entropy_bits,
+    /// };
+    ///
+    /// // Fixed choices never vary, and add nothing either way.
+    /// let fixed = draw_passphrase(4, "-", CaseMode::Title);
+    /// assert_eq!(fixed.structural_entropy_bits(), passphrase_entropy_bits(4));
+    ///
+    /// let draw = draw_passphrase_with_separators(6, &['-', '_', '.'], CaseMode::Random);
+    /// let mut expected = passphrase_entropy_bits(6);
+    /// if draw.cases.iter().any(|&case| case != draw.cases[0]) {
+    ///     expected += 6.0 * CaseMode::Random.entropy_bits_per_word();
+    /// }
+    /// if draw.junctions.iter().any(|&c| c != draw.junctions[0]) {
+    ///     expected += separators_entropy_bits(6, &['-', '_', '.']);
+    /// }
+    /// assert_eq!(draw.structural_entropy_bits(), expected);
+    /// ```
+    pub fn structural_entropy_bits(&self) -> f64 {
+        let words = self.cases.len();
+        let mut bits = passphrase_entropy_bits(words);
+        if self.cases.iter().any(|&case| case != self.cases[0]) {
+            bits += words as f64 * self.case.entropy_bits_per_word();
+        }
+        if self.junctions.iter().any(|&c| c != self.junctions[0]) {
+            bits += words.saturating_sub(1) as f64 * (self.separator_choices as f64).log2();
+        }
+        bits
+    }
+}
+
+/// Generates a passphrase of `count` words joined by `separator`.
+///
+/// Each word is chosen uniformly from the wordlist with `OsRng`.
+pub fn generate_passphrase(count: usize, separator: &str) -> String {
+    generate_passphrase_with_case(count, separator, CaseMode::Lower)
+}
+
+/// Like [`generate_passphrase`], with each word capitalized according to `case`.
+///
+/// ```
+/// use password_generator_rust::words::{CaseMode, generate_passphrase_with_case};
+///
+/// let passphrase = generate_passphrase_with_case(3, " ", CaseMode::Upper);
+/// assert!(passphrase.split(' ').all(|w| w.chars().all(|c| !c.is_ascii_lowercase())));
+/// ```
+pub fn generate_passphrase_with_case(count: usize, separator: &str, case: CaseMode) -> String {
+    draw_passphrase(count, separator, case).passphrase
+}
+
+/// Like [`generate_passphrase_with_case`], also returning the case each word
+/// was drawn in.
+pub fn draw_passphrase(count: usize, separator: &str, case: CaseMode) -> PassphraseDraw {
+    let mut rng = OsRng;
+    let words = wordlist();
+
+    let mut cases = Vec::with_capacity(count);
+    let passphrase = (0..count)
+        .map(|_| {
+            let word = words.choose(&mut rng).expect("Wordlist must not be empty");
+            let resolved = case.resolve(&mut rng);
+            cases.push(resolved);
+            resolved.write(word)
+        })
+        .collect::<Vec<_>>()
+        .join(separator);
+    PassphraseDraw {
+        passphrase,
+        cases,
+        junctions: Vec::new(),
+        case,
+        separator_choices: 1,
+    }
+}
+
+/// Like [`generate_passphrase_with_case`], but every junction between two
+/// words is one character picked uniformly from `separators` with `OsRng`,
+/// e.g. `apple-tree_house.car`.
+///
+/// Duplicates in `separators` are ignored, so each distinct separator is
+/// equally likely and every junction adds `log2(distinct separators)` bits.
+/// An empty set joins the words directly.
+///
+/// ```
+/// use password_generator_rust::words::{CaseMode, generate_passphrase_with_separators};
+///
+/// let passphrase = generate_passphrase_with_separators(4, &['-', '_', '.'], CaseMode::Lower);
+/// let junctions = passphrase.chars().filter(|c| "-_.".contains(*c)).count();
+/// assert_eq!(junctions, 3);
+/// ```
+pub fn generate_passphrase_with_separators(
+    count: usize,
+    separators: &[char],
+    case: CaseMode,
+) -> String {
+    draw_passphrase_with_separators(count, separators, case).passphrase
+}
+
+/// Like [`generate_passphrase_with_separators`], also returning the case of
+/// each word and the separator picked for each junction.
+pub fn draw_passphrase_with_separators(
+    count: usize,
+    separators: &[char],
+    case: CaseMode,
+) -> PassphraseDraw {
+    let mut rng = OsRng;
+    let words = wordlist();
+    let separators = distinct(separators);
+
+    let mut passphrase = String::new();
+    let mut cases = Vec::with_capacity(count);
+    let mut junctions = Vec::with_capacity(count.saturating_sub(1));
+    for i in 0..count {
+        if i > 0
+            && let Some(&separator) = separators.choose(&mut rng)
+        {
+            passphrase.push(separator);
+            junctions.push(separator);
+        }
+        let word = words.choose(&mut rng).expect("Wordlist must not be empty");
+        let resolved = case.resolve(&mut rng);
+        cases.push(resolved);
+        passphrase.push_str(&resolved.write(word));
+    }
+    PassphraseDraw {
+        passphrase,
+        cases,
+        junctions,
+        case,
+        separator_choices: separators.len(),
+    }
+}
+
+/// Entropy in bits the junctions of [`generate_passphrase_with_separators`]
+/// add to a passphrase of `count` words: `(count - 1) * log2(distinct separators)`.
+pub fn separators_entropy_bits(count: usize, separators: &[char]) -> f64 {
+    let distinct = distinct(separators).len();
+    if distinct <= 1 {
+        return 0.0;
+    }
+    count.saturating_sub(1) as f64 * (distinct as f64).log2()
+}
+
+/// `chars` without duplicates, keeping the first occurrence of each.
+fn distinct(chars: &[char]) -> Vec<char> {
+    let mut unique = Vec::with_capacity(chars.len());
+    for &c in chars {
+        if !unique.contains(&c) {
+            unique.push(c);
+        }
+    }
+    unique
+}
+
+/// A word chosen by rolling five dice, as on a printed diceware list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DicewareWord {
+    /// The five rolls, e.g. `42613`.
+    pub roll: String,
+    /// The wordlist entry for `roll`, before any case mode is applied.
+    pub word: &'static str,
+}
+
+/// Like [`generate_passphrase_with_case`], but also returns the diceware roll
+/// of every word.
+///
+/// Each word is picked by simulating five fair dice with `OsRng` and looking
+/// the roll up in the list, so the rolls are the very draws that chose the
+/// words and can be checked against a printed copy of the EFF list. The
+/// entropy is the same as [`generate_passphrase`]: `6^5` rolls for 7776 words.
+///
+/// ```
+/// use password_generator_rust::words::{CaseMode, generate_diceware, wordlist};
+///
+/// let (passphrase, words) = generate_diceware(4, " ", CaseMode::Lower);
+/// assert_eq!(passphrase.split(' ').count(), 4);
+/// for entry in &words {
+///     let index = entry.roll.bytes().fold(0, |acc, d| acc * 6 + (d - b'1') as usize);
+///     assert_eq!(wordlist()[index], entry.word);
+/// }
+/// ```
+pub fn generate_diceware(
+    count: usize,
+    separator: &str,
+    case: CaseMode,
+) -> (String, Vec<DicewareWord>) {
+    let mut rng = OsRng;
+    let words = wordlist();
+
+    let chosen: Vec<DicewareWord> = (0..count)
+        .map(|_| {
+            let dice: Vec<usize> = (0..5).map(|_| rng.gen_range(0..6)).collect();
+            let index = dice.iter().fold(0, |acc, &die| acc * 6 + die);
+            DicewareWord {
+                roll: dice
+                    .iter()
+                    .map(|&die| char::from(b'1' + die as u8))
+                    .collect(),
+                word: words[index],
+            }
+        })
+        .collect();
+
+    let passphrase = chosen
+        .iter()
+        .map(|entry| case.apply(entry.word, &mut rng))
+        .collect::<Vec<_>>()
+        .join(separator);
+    (passphrase, chosen)
+}
+
+/// Entropy in bits of a passphrase of `count` words: `count * log2(wordlist size)`.
+///
+/// The separator is fixed and adds nothing. Add
+/// `count * CaseMode::entropy_bits_per_word()` for a non-default case mode.
+pub fn passphrase_entropy_bits(count: usize) -> f64 {
+    count as f64 * (wordlist().len() as f64).log2()
+}
+
+/// Generates a compound passphrase such as `Horse7Battery3Staple9`: `count`
+/// title-cased words, each followed by a random digit, with no separators.
+/// With `trailing_symbol` a random symbol is appended, e.g.
+/// `Horse7Battery3Staple9!`.
+///
+/// The result has uppercase, lowercase and digits (and a symbol if asked),
+/// so it passes strict validators while staying readable. All choices are
+/// made with `OsRng`.
+///
+/// ```
+/// use password_generator_rust::words::generate_word_digits;
+///
+/// let passphrase = generate_word_digits(3, true);
+/// let (body, symbol) = passphrase.split_at(passphrase.len() - 1);
+/// assert!(symbol.chars().all(|c| c.is_ascii_punctuation()));
+///
+/// let words: Vec<&str> = body.split_terminator(|c: char| c.is_ascii_digit()).collect();
+/// assert_eq!(words.len(), 3);
+/// assert!(words.iter().all(|w| w.starts_with(|c: char| c.is_ascii_uppercase())));
+/// assert_eq!(body.chars().filter(char::is_ascii_digit).count(), 3);
+/// ```
+pub fn generate_word_digits(count: usize, trailing_symbol: bool) -> String {
+    let mut rng = OsRng;
+    let words = wordlist();
+
+    let mut passphrase = String::new();
+    for _ in 0..count {
+        let word = words.choose(&mut rng).expect("Wordlist must not be empty");
+        passphrase.push_str(&capitalize(word));
+        passphrase.push(*DIGITS.choose(&mut rng).expect("Digits must not be empty") as char);
+    }
+    if trailing_symbol {
+        passphrase.push(*SYMBOLS.choose(&mut rng).expect("Symbols must not be empty") as char);
+    }
+    passphrase
+}
+
+/// Entropy in bits of a passphrase produced by [`generate_word_digits`].
+///
+/// Each word contributes `log2(wordlist size)` bits plus `log2(10)` for its
+/// digit, and the trailing symbol `log2(SYMBOLS.len())`. Capitalization is
+/// fixed and adds nothing.
+pub fn word_digits_entropy_bits(count: usize, trailing_symbol: bool) -> f64 {
+    let symbol_bits = if trailing_symbol {
+        (SYMBOLS.len() as f64).log2()
+    } else {
+        0.0
+    };
+    passphrase_entropy_bits(count) + count as f64 * (DIGITS.len() as f64).log2() + symbol_bits
+}
+
+/// Generates a memorable password such as `Tiger7!Forest`.
+///
+/// Two capitalized words are drawn from the wordlist and joined by a random
+/// digit followed by a random symbol, so the result contains uppercase,
+/// lowercase, digit and symbol characters and passes common complexity
+/// validators. All choices are made with `OsRng`.
+pub fn generate_memorable() -> String {
+    let mut rng = OsRng;
+    let words = wordlist();
+
+    let first = capitalize(words.choose(&mut rng).expect("Wordlist must not be empty"));
+    let second = capitalize(words.choose(&mut rng).expect("Wordlist must not be empty"));
+    let digit = *DIGITS.choose(&mut rng).expect("Digits must not be empty") as char;
+    let symbol = *SYMBOLS.choose(&mut rng).expect("Symbols must not be empty") as char;
+
+    format!("{first}{digit}{symbol}{second}")
+}
+
+/// Entropy in bits of a password produced by [`generate_memorable`].
+///
+/// Each word contributes `log2(wordlist size)` bits and the connector
+/// contributes `log2(10)` for the digit plus `log2(SYMBOLS.len())` for the symbol.
+/// Capitalization is fixed and adds nothing.
+pub fn memorable_entropy_bits() -> f64 {
+    2.0 * (wordlist().len() as f64).log2()
+        + (DIGITS.len() as f64).log2()
+        + (SYMBOLS.len() as f64).log2()
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}